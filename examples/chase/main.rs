@@ -3,10 +3,11 @@
 
 use ogc_engine::prelude::*;
 
+use core::time::Duration;
 use embedded_graphics::primitives::Rectangle;
 
 const MUSIC: &[u8] = include_bytes!("jojo.mp3");
-const PUNCH: &[u8] = include_bytes!("punch.mp3");
+const PUNCH: &[u8] = include_bytes!("punch.wav");
 
 #[derive(Default)]
 struct Player {
@@ -33,26 +34,26 @@ struct Enemy {
     dead: bool,
 }
 
-#[derive(Default)]
 struct Game {
     player: Player,
     enemies: Vec<Enemy>,
+    punch: Sound,
 }
 
 impl State for Game {
     fn init() {
-        Mp3Player::play_buffer(MUSIC);
+        Audio::play_music(MUSIC, true);
     }
 
     fn update(&mut self) {
-        if Pad::buttons_down(Controller::One) == Button::Start {
-            unsafe { ogc::ffi::exit(0); };
+        if InputFrame::pad_down(Controller::One).contains(Buttons::START) {
+            System::reset_system(0, ResetTypes::ReturnToMenu, 0);
         }
 
-        let (stick_x, stick_y) = (Pad::stick_x(Controller::One), Pad::stick_y(Controller::One));
+        let stick = StickConfig::new().apply(InputFrame::current().pad(Controller::One).stick());
 
-        self.player.x += (stick_x / (i8::MAX / 8)) as i32;
-        self.player.y -= (stick_y / (i8::MAX / 8)) as i32;
+        self.player.x += (stick.x * 8.0) as i32;
+        self.player.y -= (stick.y * 8.0) as i32;
         self.player.x = self.player.x.max(0);
         self.player.x = self.player.x.min(640 - self.player.size as i32);
         self.player.y = self.player.y.max(0);
@@ -63,10 +64,10 @@ impl State for Game {
                 enemy.x -= (enemy.x - (self.player.x + 25)) / enemy.size as i32;
                 enemy.y -= (enemy.y - (self.player.y + 25)) / enemy.size as i32;
             } else {
-                if Pad::buttons_down(Controller::One) == Button::A {
+                if InputFrame::pad_down(Controller::One).contains(Buttons::A) {
                     enemy.color = enemy.color.saturating_sub(32);
-                    Mp3Player::stop();
-                    Mp3Player::play_buffer(PUNCH);
+                    Rumble::pulse(Device::Pad(Controller::One), Duration::from_millis(100));
+                    let _ = Audio::play(&self.punch);
 
                     if enemy.color == 0 {
                         enemy.dead = true;
@@ -86,10 +87,6 @@ impl State for Game {
                 enemy.color = 255;
             }
         }
-
-        if !Mp3Player::is_playing() {
-            Mp3Player::play_buffer(MUSIC);
-        }
     }
 
     fn draw(&self, display: &mut Display) -> Result<(), DrawError> {
@@ -116,7 +113,11 @@ impl State for Game {
 
 #[start]
 fn main(_argc: isize, _argv: *const *const u8) -> isize {
-    let mut state = Game::default();
+    let mut state = Game {
+        player: Player::default(),
+        enemies: Vec::new(),
+        punch: Sound::from_wav(PUNCH).unwrap(),
+    };
     let mut enemy = Enemy::default();
     enemy.size = 30;
     enemy.color = 255;
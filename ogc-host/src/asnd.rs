@@ -0,0 +1,14 @@
+/// See `ogc::asnd::VoiceFormat`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VoiceFormat {
+    Mono8Bit,
+    Mono16Bit,
+    Mono16BitBe,
+    Stereo8Bit,
+    Stereo16Bit,
+    Stereo16BitBe,
+    Mono8BitU,
+    Mono16BitLe,
+    Stereo8BitU,
+    Stereo16BitLe,
+}
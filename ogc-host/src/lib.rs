@@ -0,0 +1,23 @@
+//! The parts of `ogc-rs` the engine uses off the Wii, with the values of libogc.
+
+extern crate alloc;
+
+#[path = "../../ogc-rs/src/error.rs"]
+pub mod error;
+pub use error::{OgcError, Result};
+
+pub mod asnd;
+pub mod pad;
+pub mod system;
+pub mod wpad;
+
+pub mod prelude {
+    pub use alloc::boxed::Box;
+    pub use alloc::string::{String, ToString};
+    pub use alloc::{vec, vec::Vec};
+
+    pub use crate::asnd::*;
+    pub use crate::pad::*;
+    pub use crate::system::*;
+    pub use crate::wpad::*;
+}
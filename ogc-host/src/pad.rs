@@ -0,0 +1,49 @@
+use bitflags::bitflags;
+
+/// See `ogc::pad::Controller`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Controller {
+    One = 0,
+    Two = 1,
+    Three = 2,
+    Four = 3,
+}
+
+impl Controller {
+    pub const ALL: [Controller; 4] = [
+        Controller::One,
+        Controller::Two,
+        Controller::Three,
+        Controller::Four,
+    ];
+}
+
+pub const STICK_MAX: i8 = 72;
+pub const SUB_STICK_MAX: i8 = 59;
+pub const TRIGGER_MAX: u8 = 150;
+
+bitflags! {
+    /// See `ogc::pad::Buttons`.
+    #[derive(Default)]
+    pub struct Buttons: u16 {
+        const LEFT  = 0x0001;
+        const RIGHT = 0x0002;
+        const DOWN  = 0x0004;
+        const UP    = 0x0008;
+        const Z     = 0x0010;
+        const R     = 0x0020;
+        const L     = 0x0040;
+        const A     = 0x0100;
+        const B     = 0x0200;
+        const X     = 0x0400;
+        const Y     = 0x0800;
+        const START = 0x1000;
+    }
+}
+
+impl Buttons {
+    /// Returns true if any of `other` is set.
+    pub fn any(&self, other: Buttons) -> bool {
+        self.intersects(other)
+    }
+}
@@ -0,0 +1,13 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// See `ogc::system::System`.
+pub struct System;
+
+impl System {
+    /// Nanoseconds since the Unix epoch, standing in for the time base of the Wii.
+    pub fn get_time() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64)
+    }
+}
@@ -0,0 +1,58 @@
+use bitflags::bitflags;
+
+/// See `ogc::wpad::Remote`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Remote {
+    One = 0,
+    Two = 1,
+    Three = 2,
+    Four = 3,
+}
+
+impl Remote {
+    pub const ALL: [Remote; 4] = [Remote::One, Remote::Two, Remote::Three, Remote::Four];
+}
+
+bitflags! {
+    /// See `ogc::wpad::WpadButtons`.
+    #[derive(Default)]
+    pub struct WpadButtons: u32 {
+        const TWO   = 0x0001;
+        const ONE   = 0x0002;
+        const B     = 0x0004;
+        const A     = 0x0008;
+        const MINUS = 0x0010;
+        const HOME  = 0x0080;
+        const LEFT  = 0x0100;
+        const RIGHT = 0x0200;
+        const DOWN  = 0x0400;
+        const UP    = 0x0800;
+        const PLUS  = 0x1000;
+
+        const NUNCHUK_Z = 0x0001 << 16;
+        const NUNCHUK_C = 0x0002 << 16;
+
+        const CLASSIC_UP     = 0x0001 << 16;
+        const CLASSIC_LEFT   = 0x0002 << 16;
+        const CLASSIC_ZR     = 0x0004 << 16;
+        const CLASSIC_X      = 0x0008 << 16;
+        const CLASSIC_A      = 0x0010 << 16;
+        const CLASSIC_Y      = 0x0020 << 16;
+        const CLASSIC_B      = 0x0040 << 16;
+        const CLASSIC_ZL     = 0x0080 << 16;
+        const CLASSIC_R      = 0x0200 << 16;
+        const CLASSIC_PLUS   = 0x0400 << 16;
+        const CLASSIC_HOME   = 0x0800 << 16;
+        const CLASSIC_MINUS  = 0x1000 << 16;
+        const CLASSIC_L      = 0x2000 << 16;
+        const CLASSIC_DOWN   = 0x4000 << 16;
+        const CLASSIC_RIGHT  = 0x8000 << 16;
+    }
+}
+
+impl WpadButtons {
+    /// Returns true if any of `other` is set.
+    pub fn any(&self, other: WpadButtons) -> bool {
+        self.intersects(other)
+    }
+}
@@ -29,8 +29,11 @@ fn main(_argc: isize, _argv: *const *const u8) -> isize {
 
     // Debugging
     Asnd::init();
-    let mut buffer = (0..255).cycle().take(32 * 32 * 32 * 32).collect::<Vec<_>>();
-    Asnd::set_voice(VoiceOptions::new().voice(1), &mut buffer).unwrap();
+    let samples = (0..255).cycle().take(32 * 32 * 32 * 32).collect::<Vec<_>>();
+    let mut voice = Voice::claim().unwrap();
+    voice
+        .play(VoiceOptions::new(), SoundBuffer::new(&samples))
+        .unwrap();
     Asnd::pause(false);
 
     loop {
@@ -37,33 +37,33 @@ fn main(_argc: isize, _argv: *const *const u8) -> isize {
         // Check for pressed buttons
         let pressed = Pad::buttons_down(Controller::One);
 
-        if pressed == Button::A {
+        if pressed.contains(Buttons::A) {
             println!("A was pressed");
         }
 
-        if pressed == Button::B {
+        if pressed.contains(Buttons::B) {
             println!("B was pressed");
         }
 
         // Check for released buttons
         let released = Pad::buttons_up(Controller::One);
 
-        if released == Button::X {
+        if released.contains(Buttons::X) {
             println!("X was released");
         }
 
-        if released == Button::Z {
+        if released.contains(Buttons::Z) {
             println!("Z was released");
         }
 
         // Check for held buttons, also shows how to test multiple buttons
         let held = Pad::buttons_held(Controller::One);
 
-        if held == (Button::Up | Button::A) {
+        if held.contains(Buttons::UP | Buttons::A) {
             println!("Up and A is being held");
         }
 
-        if held == (Button::Down | Button::B) {
+        if held.contains(Buttons::DOWN | Buttons::B) {
             println!("Down and B is being held");
         }
 
@@ -84,6 +84,13 @@ fn main(_argc: isize, _argv: *const *const u8) -> isize {
             println!("C-stick: ({}, {})", sub_stick_x, sub_stick_y);
         }
 
+        // Check for any of several buttons
+        if pressed.any(Buttons::L | Buttons::R) {
+            for button in pressed.iter() {
+                println!("{:?} was pressed", button);
+            }
+        }
+
         // Check for triggers
         let (trigger_l, trigger_r) = (
             Pad::trigger_l(Controller::One),
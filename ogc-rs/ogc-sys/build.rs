@@ -11,12 +11,27 @@ fn main() {
     );
     println!("cargo:rustc-link-search=native={}/libogc/lib/wii", dkp_path);
 
+    println!("cargo:rustc-link-lib=static=fat");
     println!("cargo:rustc-link-lib=static=c");
     println!("cargo:rustc-link-lib=static=sysbase");
     println!("cargo:rustc-link-lib=static=ogc");
 
+    let vorbis = std::env::var("CARGO_FEATURE_VORBIS").is_ok();
+    if vorbis {
+        println!("cargo:rustc-link-search=native={}/portlibs/ppc/lib", dkp_path);
+        println!("cargo:rustc-link-lib=static=vorbisidec");
+        println!("cargo:rustc-link-lib=static=ogg");
+    }
+
     println!("cargo:rerun-if-changed=wrapper.h");
-    let bindings = bindgen::Builder::default()
+    let mut builder = bindgen::Builder::default();
+    if vorbis {
+        builder = builder
+            .clang_arg("-DOGC_SYS_VORBIS")
+            .clang_arg(format!("-I{}/portlibs/ppc/include", dkp_path));
+    }
+
+    let bindings = builder
         .header("wrapper.h")
         .rust_target(bindgen::RustTarget::Nightly)
         .use_core()
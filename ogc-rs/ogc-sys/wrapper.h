@@ -8,6 +8,13 @@
 #include <gccore.h>
 #include <ogcsys.h>
 #include <wiiuse/wpad.h>
+#include <wiikeyboard/keyboard.h>
+#include <ogc/usbmouse.h>
 #include <asndlib.h>
 #include <mp3player.h>
+#include <mad.h>
+#include <fat.h>
+#ifdef OGC_SYS_VORBIS
+#include <tremor/ivorbisfile.h>
+#endif
 
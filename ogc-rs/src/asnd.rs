@@ -14,10 +14,219 @@
 //! },
 //! ```
 
-use crate::{OgcError, Result};
-use alloc::boxed::Box;
-use alloc::format;
-use core::mem;
+use crate::{system::System, OgcError, Result};
+use alloc::{
+    alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout},
+    format,
+    rc::Rc,
+};
+use core::{
+    mem,
+    sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+/// Number of voices `Asnd` mixes.
+pub const VOICE_COUNT: u32 = ogc_sys::MAX_SND_VOICES;
+
+/// Whether `Asnd::init` was called without `Asnd::end` since.
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Voices owned by a `Voice`, one bit per voice.
+static CLAIMED: AtomicU32 = AtomicU32::new(0);
+
+/// Callback of each voice as a `fn(u32)` address, 0 for none. See `voice_trampoline`.
+static VOICE_CALLBACKS: [AtomicUsize; VOICE_COUNT as usize] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+/// Global callback as a `fn()` address, 0 for none. See `global_trampoline`.
+static GLOBAL_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+
+/// Called by the IRQ when a voice needs another buffer, forwards to the Rust callback of the voice.
+extern "C" fn voice_trampoline(voice: i32) {
+    let callback = VOICE_CALLBACKS
+        .get(voice as usize)
+        .map_or(0, |callback| callback.load(Ordering::Acquire));
+    if callback != 0 {
+        let callback: fn(u32) = unsafe { mem::transmute(callback) };
+        callback(voice as u32);
+    }
+}
+
+/// Called by the IRQ on every tick, forwards to the Rust callback set with `Asnd::set_callback()`.
+extern "C" fn global_trampoline() {
+    let callback = GLOBAL_CALLBACK.load(Ordering::Acquire);
+    if callback != 0 {
+        let callback: fn() = unsafe { mem::transmute(callback) };
+        callback();
+    }
+}
+
+/// Fails unless `sound_buffer` is aligned and padded to 32 bytes, then flushes it from
+/// the data cache so the DSP reads what the CPU wrote.
+fn prepare_buffer(function: &str, sound_buffer: &[u8]) -> Result<()> {
+    if sound_buffer.as_ptr() as usize % 32 != 0 || sound_buffer.len() % 32 != 0 {
+        return Err(OgcError::Audio(format!(
+            "{} needs a buffer aligned and padded to 32 bytes, see `SoundBuffer`",
+            function
+        )));
+    }
+    System::flush_data_cache(sound_buffer);
+    Ok(())
+}
+
+/// Memory behind a `SoundBuffer`.
+struct Samples {
+    ptr: *mut u8,
+    layout: Layout,
+    silence: u8,
+}
+
+impl Drop for Samples {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.ptr, self.layout) };
+    }
+}
+
+/// Samples in memory the DSP can read: aligned and padded to 32 bytes, and flushed from
+/// the data cache whenever they change. Cloning is cheap, the samples are shared.
+///
+/// A `Voice` keeps a clone of every buffer it plays, so the samples stay alive until the
+/// voice is done with them, even if the game drops its own buffer early.
+///
+/// # Examples
+///
+/// ```rust
+/// const JUMP: &[u8] = include_bytes!("jump.pcm");
+///
+/// let jump = SoundBuffer::new(JUMP);
+/// let mut voice = Voice::claim()?;
+/// voice.play(VoiceOptions::new().format(VoiceFormat::Mono16Bit), jump.clone())?;
+/// ```
+#[derive(Clone)]
+pub struct SoundBuffer {
+    samples: Rc<Samples>,
+    len: usize,
+}
+
+impl SoundBuffer {
+    /// Silent samples, with room for `capacity` bytes.
+    pub fn zeroed(capacity: usize) -> Self {
+        Self::with_silence(capacity, 0)
+    }
+
+    /// Copies `data`, padding it with zeroes.
+    pub fn new(data: &[u8]) -> Self {
+        Self::with_padding(data, 0)
+    }
+
+    /// Copies `data`, padding it with `silence`, such as `0x80` for unsigned 8-bit samples.
+    pub fn with_padding(data: &[u8], silence: u8) -> Self {
+        let mut buffer = Self::with_silence(data.len(), silence);
+        // A new buffer is never shared.
+        let _ = buffer.write(|bytes| {
+            bytes[..data.len()].copy_from_slice(data);
+            data.len()
+        });
+        buffer
+    }
+
+    fn with_silence(capacity: usize, silence: u8) -> Self {
+        let size = ((capacity + 31) & !31).max(32);
+        let layout = Layout::from_size_align(size, 32).unwrap();
+        let ptr = unsafe { alloc_zeroed(layout) };
+        if ptr.is_null() {
+            handle_alloc_error(layout);
+        }
+
+        let samples = Samples {
+            ptr,
+            layout,
+            silence,
+        };
+        let buffer = unsafe { core::slice::from_raw_parts_mut(ptr, size) };
+        buffer.fill(silence);
+        System::flush_data_cache(buffer);
+
+        Self {
+            samples: Rc::new(samples),
+            len: 0,
+        }
+    }
+
+    /// Size of the samples, in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if `other` is a clone of this buffer, sharing its samples.
+    pub fn ptr_eq(&self, other: &SoundBuffer) -> bool {
+        Rc::ptr_eq(&self.samples, &other.samples) && self.len == other.len
+    }
+
+    /// Most bytes of samples the buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.samples.layout.size()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.samples.ptr, self.len) }
+    }
+
+    /// The samples padded to 32 bytes, as handed to the DSP.
+    fn padded(&self) -> &[u8] {
+        let len = ((self.len + 31) & !31).max(32);
+        unsafe { core::slice::from_raw_parts(self.samples.ptr, len) }
+    }
+
+    /// Rewrites the samples. `fill` gets the whole capacity and returns how many bytes
+    /// to keep. The rest is padded with silence and the buffer is flushed afterwards.
+    ///
+    /// Fails while a clone of the buffer exists, for instance while a `Voice` plays it.
+    pub fn write<F>(&mut self, fill: F) -> Result<()>
+    where
+        F: FnOnce(&mut [u8]) -> usize,
+    {
+        let samples = Rc::get_mut(&mut self.samples).ok_or_else(|| {
+            OgcError::Audio("SoundBuffer::write() failed, the buffer is shared".into())
+        })?;
+        let buffer = unsafe { core::slice::from_raw_parts_mut(samples.ptr, samples.layout.size()) };
+
+        let len = fill(buffer).min(buffer.len());
+        buffer[len..].fill(samples.silence);
+        System::flush_data_cache(buffer);
+        self.len = len;
+        Ok(())
+    }
+
+    /// Hands the samples over for good, for the functions of `Asnd` that take `'static` buffers.
+    pub fn leak(self) -> &'static [u8] {
+        let padded = self.padded();
+        let padded = unsafe { core::slice::from_raw_parts(padded.as_ptr(), padded.len()) };
+        mem::forget(self);
+        padded
+    }
+}
 
 macro_rules! if_not {
     ($valid:ident => $error_output:expr, $var:ident $(,)*) => {
@@ -45,7 +254,7 @@ pub struct VoiceOptions {
     delay: u32,
     volume_left: u8,
     volume_right: u8,
-    callback: Option<Box<fn(i32) -> ()>>,
+    callback: Option<fn(u32)>,
 }
 
 impl VoiceOptions {
@@ -99,14 +308,20 @@ impl VoiceOptions {
         }
     }
 
-    /// Optional callback function to use.
-    pub fn callback(self, callback: Box<fn(i32) -> ()>) -> Self {
+    /// Function to call with the voice slot when the voice needs another buffer, see
+    /// `Asnd::add_voice()`. It runs in interrupt context, so it must be short and can
+    /// only share state through atomics.
+    pub fn callback(self, callback: fn(u32)) -> Self {
         let callback = Some(callback);
         Self { callback, ..self }
     }
 }
 
 /// Source voice format.
+///
+/// Samples are signed and big-endian unless noted otherwise. The `Be` variants are
+/// the same formats as their unsuffixed counterparts, as in ``asnd.h``.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum VoiceFormat {
     Mono8Bit,
     Mono16Bit,
@@ -114,29 +329,66 @@ pub enum VoiceFormat {
     Stereo8Bit,
     Stereo16Bit,
     Stereo16BitBe,
+    /// Unsigned 8-bit samples.
     Mono8BitU,
-    Mono16BitLE,
+    /// Little-endian 16-bit samples.
+    Mono16BitLe,
+    /// Unsigned 8-bit samples.
     Stereo8BitU,
+    /// Little-endian 16-bit samples.
     Stereo16BitLe,
 }
 
 impl VoiceFormat {
     fn as_i32(self) -> i32 {
+        (match self {
+            VoiceFormat::Mono8Bit => ogc_sys::VOICE_MONO_8BIT,
+            VoiceFormat::Mono16Bit => ogc_sys::VOICE_MONO_16BIT,
+            VoiceFormat::Mono16BitBe => ogc_sys::VOICE_MONO_16BIT_BE,
+            VoiceFormat::Stereo8Bit => ogc_sys::VOICE_STEREO_8BIT,
+            VoiceFormat::Stereo16Bit => ogc_sys::VOICE_STEREO_16BIT,
+            VoiceFormat::Stereo16BitBe => ogc_sys::VOICE_STEREO_16BIT_BE,
+            VoiceFormat::Mono8BitU => ogc_sys::VOICE_MONO_8BIT_U,
+            VoiceFormat::Mono16BitLe => ogc_sys::VOICE_MONO_16BIT_LE,
+            VoiceFormat::Stereo8BitU => ogc_sys::VOICE_STEREO_8BIT_U,
+            VoiceFormat::Stereo16BitLe => ogc_sys::VOICE_STEREO_16BIT_LE,
+        }) as i32
+    }
+
+    /// Number of channels, 1 or 2.
+    pub fn channels(self) -> usize {
+        match self {
+            VoiceFormat::Mono8Bit
+            | VoiceFormat::Mono16Bit
+            | VoiceFormat::Mono16BitBe
+            | VoiceFormat::Mono8BitU
+            | VoiceFormat::Mono16BitLe => 1,
+            _ => 2,
+        }
+    }
+
+    /// Size of one sample of every channel, in bytes.
+    pub fn frame_size(self) -> usize {
         match self {
-            VoiceFormat::Mono8Bit => 0,
-            VoiceFormat::Mono16Bit => 1,
-            VoiceFormat::Mono16BitBe => 1,
-            VoiceFormat::Stereo8Bit => 2,
-            VoiceFormat::Stereo16Bit => 3,
-            VoiceFormat::Stereo16BitBe => 3,
-            VoiceFormat::Mono8BitU => 4,
-            VoiceFormat::Mono16BitLE => 5,
-            VoiceFormat::Stereo8BitU => 6,
-            VoiceFormat::Stereo16BitLe => 7,
+            VoiceFormat::Mono8Bit | VoiceFormat::Mono8BitU => 1,
+            VoiceFormat::Stereo8Bit | VoiceFormat::Stereo8BitU => 2,
+            VoiceFormat::Mono16Bit | VoiceFormat::Mono16BitBe | VoiceFormat::Mono16BitLe => 2,
+            VoiceFormat::Stereo16Bit | VoiceFormat::Stereo16BitBe | VoiceFormat::Stereo16BitLe => 4,
         }
     }
 }
 
+/// State of a voice, see `Voice::status`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VoiceStatus {
+    /// Not playing anything.
+    Unused,
+    /// Playing a buffer.
+    Working,
+    /// Paused, or waiting for its delay to run out.
+    Waiting,
+}
+
 /// Represents the asnd service.
 /// This service can only be created once!
 ///
@@ -144,8 +396,8 @@ impl VoiceFormat {
 ///
 /// ```rust
 /// let asnd = Asnd::init();
-/// let mut buffer = (0..255).cycle().take(32 * 32 * 32 * 32).collect::<Vec<_>>();
-/// Asnd::set_voice(VoiceOptions::new(), &mut buffer).unwrap();
+/// let samples = (0..255).cycle().take(32 * 32 * 32 * 32).collect::<Vec<_>>();
+/// Asnd::set_voice(VoiceOptions::new(), SoundBuffer::new(&samples).leak()).unwrap();
 /// Asnd::pause(false);
 /// ```
 pub struct Asnd;
@@ -156,12 +408,19 @@ impl Asnd {
     /// Initializes the asnd lib and fixes the hardware sample rate to 48000hz.
     pub fn init() -> Self {
         unsafe { ogc_sys::ASND_Init() };
+        INITIALIZED.store(true, Ordering::Release);
         Self
     }
 
     /// De-initializes the asnd lib.
     pub fn end() {
         unsafe { ogc_sys::ASND_End() };
+        INITIALIZED.store(false, Ordering::Release);
+    }
+
+    /// Returns true between `Asnd::init` and `Asnd::end`, while asnd owns the audio DMA.
+    pub fn is_initialized() -> bool {
+        INITIALIZED.load(Ordering::Acquire)
     }
 
     /// Pauses if true and resumes if false.
@@ -194,18 +453,11 @@ impl Asnd {
         unsafe { ogc_sys::ASND_SetTime(time) };
     }
 
-    /// Sets a global callback for general purposes. It is called by the IRQ.
-    pub fn set_callback<F>(callback: Box<F>)
-    where
-        F: Fn() -> (),
-    {
-        // TODO: Check if this implementation can be changed.
-        let ptr = Box::into_raw(callback);
-
-        unsafe {
-            let code: extern "C" fn() = mem::transmute(ptr);
-            ogc_sys::ASND_SetCallback(Some(code));
-        }
+    /// Sets a global callback for general purposes, or removes it with `None`. It is
+    /// called by the IRQ, so it must be short and can only share state through atomics.
+    pub fn set_callback(callback: Option<fn()>) {
+        GLOBAL_CALLBACK.store(callback.map_or(0, |f| f as usize), Ordering::Release);
+        unsafe { ogc_sys::ASND_SetCallback(callback.map(|_| global_trampoline as _)) };
     }
 
     /// Returs the current audio rate. Default is 48000hz.
@@ -215,13 +467,24 @@ impl Asnd {
 
     /// Sets a PCM voice to play. This function stops one previous voice. Use
     /// `Asnd::status_voice()` to test status. The voices are played in 16-bit stereo,
-    /// regardless of source format. The buffer MUST be aligned and padded to 32 bytes.
-    pub fn set_voice(options: VoiceOptions, sound_buffer: &mut [u8]) -> Result<()> {
-        let callback = options.callback.map(|f| {
-            let ptr = Box::into_raw(f);
-            let code: unsafe extern "C" fn(i32) = unsafe { mem::transmute(ptr) };
-            code
-        });
+    /// regardless of source format. The buffer must be aligned and padded to 32 bytes,
+    /// and is flushed from the data cache. The DSP keeps reading it after this returns,
+    /// hence `'static`: use `SoundBuffer::leak()`, or a `Voice` to free buffers afterwards.
+    pub fn set_voice(options: VoiceOptions, sound_buffer: &'static [u8]) -> Result<()> {
+        prepare_buffer("Asnd::set_voice()", sound_buffer)?;
+        unsafe { Self::set_voice_unchecked(options, sound_buffer) }
+    }
+
+    /// `Asnd::set_voice()` for a buffer that is already prepared and outlives playback.
+    unsafe fn set_voice_unchecked(options: VoiceOptions, sound_buffer: &[u8]) -> Result<()> {
+        assert!(options.voice < 16, "Voice index {} is >= 16", options.voice);
+        VOICE_CALLBACKS[options.voice as usize].store(
+            options.callback.map_or(0, |f| f as usize),
+            Ordering::Release,
+        );
+        let callback = options
+            .callback
+            .map(|_| voice_trampoline as unsafe extern "C" fn(i32));
 
         let err = unsafe {
             ogc_sys::ASND_SetVoice(
@@ -229,7 +492,7 @@ impl Asnd {
                 options.format.as_i32(),
                 options.pitch as _,
                 options.delay as _,
-                sound_buffer.as_mut_ptr() as *mut _,
+                sound_buffer.as_ptr() as *mut _,
                 sound_buffer.len() as _,
                 options.volume_left as _,
                 options.volume_right as _,
@@ -241,15 +504,24 @@ impl Asnd {
     }
 
     /// Sets a PCM voice to play infinitely. See `Asnd::set_voice()` as it is largely identical.
-    /// The buffer MUST be aligned and padded to 32 bytes.
-    pub fn set_infinite_voice(options: VoiceOptions, sound_buffer: &mut [u8]) -> Result<()> {
+    pub fn set_infinite_voice(options: VoiceOptions, sound_buffer: &'static [u8]) -> Result<()> {
+        prepare_buffer("Asnd::set_infinite_voice()", sound_buffer)?;
+        unsafe { Self::set_infinite_voice_unchecked(options, sound_buffer) }
+    }
+
+    /// `Asnd::set_infinite_voice()` for a buffer that is already prepared and outlives playback.
+    unsafe fn set_infinite_voice_unchecked(
+        options: VoiceOptions,
+        sound_buffer: &[u8],
+    ) -> Result<()> {
+        assert!(options.voice < 16, "Voice index {} is >= 16", options.voice);
         let err = unsafe {
             ogc_sys::ASND_SetInfiniteVoice(
                 options.voice as _,
                 options.format.as_i32(),
                 options.pitch as _,
                 options.delay as _,
-                sound_buffer.as_mut_ptr() as *mut _,
+                sound_buffer.as_ptr() as *mut _,
                 sound_buffer.len() as _,
                 options.volume_left as _,
                 options.volume_right as _,
@@ -261,15 +533,20 @@ impl Asnd {
 
     /// Adds a PCM voice to play from the second buffer. Sound buffer must be 32-byte
     /// aligned and have same sample format as first buffer. This must only be called after
-    /// `Asnd::set_voice()`, which must return `Ok()`.
-    /// The buffer MUST be aligned and padded to 32 bytes.
-    pub fn add_voice(voice: u32, sound_buffer: &mut [u8]) -> Result<()> {
+    /// `Asnd::set_voice()`, which must return `Ok()`. See `Asnd::set_voice()` for buffers.
+    pub fn add_voice(voice: u32, sound_buffer: &'static [u8]) -> Result<()> {
+        prepare_buffer("Asnd::add_voice()", sound_buffer)?;
+        unsafe { Self::add_voice_unchecked(voice, sound_buffer) }
+    }
+
+    /// `Asnd::add_voice()` for a buffer that is already prepared and outlives playback.
+    unsafe fn add_voice_unchecked(voice: u32, sound_buffer: &[u8]) -> Result<()> {
         assert!(voice < 16, "Voice index {} is >= 16", voice);
 
         let err = unsafe {
             ogc_sys::ASND_AddVoice(
                 voice as _,
-                sound_buffer.as_mut_ptr() as *mut _,
+                sound_buffer.as_ptr() as *mut _,
                 sound_buffer.len() as _,
             )
         };
@@ -371,3 +648,176 @@ impl Asnd {
         unsafe { ogc_sys::ASND_GetDSP_ProcessTime() }
     }
 }
+
+/// A voice slot owned by this handle. No other `Voice` can claim the slot until the
+/// handle is dropped, which stops the voice and releases the slot.
+///
+/// The voice holds on to the buffers it plays until it is done with them, so dropping
+/// a `SoundBuffer` early never frees samples the DSP still reads.
+///
+/// # Minimal Example
+///
+/// ```rust
+/// let mut voice = Voice::claim()?;
+/// voice.play(VoiceOptions::new().format(VoiceFormat::Mono16Bit), SoundBuffer::new(&samples))?;
+///
+/// while !voice.is_finished() {
+///     voice.set_volume(128, 128)?;
+/// }
+/// ```
+pub struct Voice {
+    index: u32,
+    /// The playing buffer and the one queued after it.
+    buffers: [Option<SoundBuffer>; 2],
+}
+
+impl Voice {
+    /// Claims the first voice that is neither claimed nor playing.
+    pub fn claim() -> Result<Self> {
+        (0..VOICE_COUNT)
+            .find_map(|index| {
+                let unused =
+                    unsafe { ogc_sys::ASND_StatusVoice(index as _) } == ogc_sys::SND_UNUSED as _;
+                if unused {
+                    Self::claim_index(index).ok()
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| OgcError::Audio("Voice::claim() found no free voice".into()))
+    }
+
+    /// Claims voice `index`, for instance one a library such as ``mp3player`` plays on.
+    /// Fails if another `Voice` owns it.
+    pub fn claim_index(index: u32) -> Result<Self> {
+        assert!(index < VOICE_COUNT, "Voice index {} is >= 16", index);
+        let bit = 1 << index;
+        if CLAIMED.fetch_or(bit, Ordering::AcqRel) & bit != 0 {
+            return Err(OgcError::Audio(format!(
+                "Voice::claim_index() failed, voice {} is already claimed",
+                index
+            )));
+        }
+        Ok(Self {
+            index,
+            buffers: [None, None],
+        })
+    }
+
+    /// Returns true if a `Voice` owns voice `index`.
+    pub fn is_claimed(index: u32) -> bool {
+        CLAIMED.load(Ordering::Acquire) & 1 << index != 0
+    }
+
+    /// Slot of the voice, in `0..16`.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Plays `sound_buffer` on this voice, see `Asnd::set_voice()`. The voice slot of
+    /// `options` is ignored.
+    pub fn play(&mut self, options: VoiceOptions, sound_buffer: SoundBuffer) -> Result<()> {
+        unsafe { Asnd::set_voice_unchecked(options.voice(self.index), sound_buffer.padded())? };
+        // The previous buffers are no longer read once the voice restarted.
+        self.buffers = [Some(sound_buffer), None];
+        Ok(())
+    }
+
+    /// Plays `sound_buffer` on this voice over and over, see `Asnd::set_infinite_voice()`.
+    pub fn play_looping(&mut self, options: VoiceOptions, sound_buffer: SoundBuffer) -> Result<()> {
+        unsafe {
+            Asnd::set_infinite_voice_unchecked(options.voice(self.index), sound_buffer.padded())?
+        };
+        self.buffers = [Some(sound_buffer), None];
+        Ok(())
+    }
+
+    /// Queues `sound_buffer` to play after the current one, see `Asnd::add_voice()`.
+    pub fn queue(&mut self, sound_buffer: SoundBuffer) -> Result<()> {
+        self.release_buffers();
+        let slot = self
+            .buffers
+            .iter_mut()
+            .find(|buffer| buffer.is_none())
+            .ok_or_else(|| {
+                OgcError::Audio("Voice::queue() failed, a buffer is already queued".into())
+            })?;
+
+        unsafe { Asnd::add_voice_unchecked(self.index, sound_buffer.padded())? };
+        *slot = Some(sound_buffer);
+        Ok(())
+    }
+
+    /// Lets go of the buffers the voice is done with, so their other clones can be
+    /// written to again. `Voice::queue` does this on its own.
+    pub fn release_buffers(&mut self) {
+        let index = self.index;
+        for buffer in self.buffers.iter_mut() {
+            let in_use = buffer.as_ref().map_or(false, |buffer| {
+                Asnd::test_pointer(index, buffer.samples.ptr) == 1
+            });
+            if !in_use {
+                *buffer = None;
+            }
+        }
+    }
+
+    /// Returns true if the voice can take another buffer with `Voice::queue`.
+    pub fn is_ready(&self) -> bool {
+        Asnd::test_voice_buffer_ready(self.index)
+    }
+
+    /// Pauses if true and resumes if false.
+    pub fn pause(&mut self, pause: bool) -> Result<()> {
+        Asnd::pause_voice(self.index, pause)
+    }
+
+    /// Stops playing and lets go of the buffers. The voice stays claimed.
+    pub fn stop(&mut self) -> Result<()> {
+        Asnd::stop_voice(self.index)?;
+        self.buffers = [None, None];
+        Ok(())
+    }
+
+    /// Frequency to play at, in Hz.
+    pub fn set_pitch(&mut self, pitch: u32) -> Result<()> {
+        Asnd::change_pitch_voice(self.index, pitch)
+    }
+
+    /// Volume of the left and right channel.
+    pub fn set_volume(&mut self, volume_left: u8, volume_right: u8) -> Result<()> {
+        Asnd::change_volume_voice(self.index, volume_left, volume_right)
+    }
+
+    /// Samples played since the voice started, at the 48000hz rate of the DSP.
+    pub fn position(&self) -> u32 {
+        Asnd::get_tick_counter_voice(self.index)
+    }
+
+    /// Time since the voice started playing.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis(Asnd::get_timer_voice(self.index) as u64)
+    }
+
+    pub fn status(&self) -> VoiceStatus {
+        match unsafe { ogc_sys::ASND_StatusVoice(self.index as _) } {
+            x if x == ogc_sys::SND_WORKING as _ => VoiceStatus::Working,
+            x if x == ogc_sys::SND_WAITING as _ => VoiceStatus::Waiting,
+            _ => VoiceStatus::Unused,
+        }
+    }
+
+    /// Returns true once the voice has played everything it was given, or was stopped.
+    pub fn is_finished(&self) -> bool {
+        self.status() == VoiceStatus::Unused
+    }
+}
+
+impl Drop for Voice {
+    fn drop(&mut self) {
+        // Stopped before the buffers are dropped with the fields.
+        let _ = Asnd::stop_voice(self.index);
+        VOICE_CALLBACKS[self.index as usize].store(0, Ordering::Release);
+        CLAIMED.fetch_and(!(1 << self.index), Ordering::AcqRel);
+    }
+}
@@ -2,10 +2,44 @@
 //!
 //! This module implements a safe wrapper around the audio functions found in ``audio.h``.
 
-use alloc::boxed::Box;
-use core::{convert::TryFrom, mem, ptr};
+use crate::{
+    asnd::Asnd, mp3player::Mp3Player, system::System, utils::replace_callback, OgcError, Result,
+};
+use alloc::{
+    alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout},
+    boxed::Box,
+    format,
+};
+use core::{
+    convert::TryFrom,
+    ptr,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
+type DmaCallback = Box<dyn FnMut() + Send>;
+type StreamCallback = Box<dyn FnMut(u32) + Send>;
+
+/// Addresses of the boxed callbacks the trampolines call, 0 when there is none.
+static DMA_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+static STREAM_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+/// Whether a `DmaStream` owns the DMA callback.
+static STREAMING: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn dma_trampoline() {
+    let callback = DMA_CALLBACK.load(Ordering::Acquire) as *mut DmaCallback;
+    if let Some(callback) = unsafe { callback.as_mut() } {
+        callback();
+    }
+}
+
+extern "C" fn stream_trampoline(sample_count: u32) {
+    let callback = STREAM_CALLBACK.load(Ordering::Acquire) as *mut StreamCallback;
+    if let Some(callback) = unsafe { callback.as_mut() } {
+        callback(sample_count);
+    }
+}
+
 /// Represents the audio service.
 /// No audio control can be done until an instance of this struct is created.
 /// This service can only be created once!
@@ -41,18 +75,21 @@ impl Audio {
     }
 
     /// Initialize an audio DMA transfer.
-    pub fn init_dma(data: &[u8]) {
-        unsafe {
-            // libogc has strict restrictions on data alignment and length.
-            // assert_eq!(
-            //     32,
-            //     mem::align_of_val(data),
-            //     "Data is not aligned correctly."
-            // );
-            // assert_eq!(0, data.len() % 32, "Data length is not a multiple of 32.");
-
-            ogc_sys::AUDIO_InitDMA(data.as_ptr() as u32, data.len() as u32);
+    ///
+    /// The data must be aligned and padded to 32 bytes. It is flushed from the data cache,
+    /// and must live as long as the DMA may read it.
+    pub fn init_dma(data: &'static [u8]) -> Result<()> {
+        if data.as_ptr() as usize % 32 != 0 || data.len() % 32 != 0 {
+            return Err(OgcError::Audio(format!(
+                "Audio::init_dma() needs data aligned and padded to 32 bytes, got {} bytes at {:p}",
+                data.len(),
+                data.as_ptr()
+            )));
         }
+
+        System::flush_data_cache(data);
+        unsafe { ogc_sys::AUDIO_InitDMA(data.as_ptr() as u32, data.len() as u32) };
+        Ok(())
     }
 
     /// Start the audio DMA operation.
@@ -73,36 +110,69 @@ impl Audio {
     }
 
     /// Register a user callback function for the ``audio`` streaming interface.
-    pub fn register_stream_callback<F>(callback: Box<F>)
+    ///
+    /// The callback receives the sample count of the stream trigger, and runs in interrupt
+    /// context, so it should be short and must not allocate.
+    pub fn register_stream_callback<F>(callback: F)
     where
-        F: Fn(u32) -> (),
+        F: FnMut(u32) + Send + 'static,
     {
-        // TODO: Check if this implementation can be changed.
-        let ptr = Box::into_raw(callback);
+        replace_callback::<StreamCallback>(&STREAM_CALLBACK, Some(Box::new(callback)));
+        unsafe {
+            ogc_sys::AUDIO_RegisterStreamCallback(Some(stream_trampoline as _));
+        }
+    }
 
+    /// Remove the callback of the ``audio`` streaming interface.
+    pub fn unregister_stream_callback() {
         unsafe {
-            let code: extern "C" fn(smp_cnt: u32) = mem::transmute(ptr);
-            // TODO: Do something with the returned callback.
-            let _ = ogc_sys::AUDIO_RegisterStreamCallback(Some(code));
+            ogc_sys::AUDIO_RegisterStreamCallback(None);
         }
+        replace_callback::<StreamCallback>(&STREAM_CALLBACK, None);
     }
 
     /// Register a user callback function for the audio DMA interface.
     ///
-    /// This callback will be called whenever the audio DMA requests new data.
-    /// Internally the DMA buffers are double buffered.
-    pub fn register_dma_callback<F>(callback: Box<F>)
+    /// This callback will be called whenever the audio DMA has started a transfer and
+    /// requests the next one, set with ``init_dma``. It runs in interrupt context, so it
+    /// should be short and must not allocate. Fails while a ``DmaStream`` is running.
+    pub fn register_dma_callback<F>(callback: F) -> Result<()>
     where
-        F: Fn() -> (),
+        F: FnMut() + Send + 'static,
     {
-        // TODO: Check if this implementation can be changed.
-        let ptr = Box::into_raw(callback);
+        if STREAMING.load(Ordering::Acquire) {
+            return Err(OgcError::Audio(
+                "Audio::register_dma_callback() failed, a DmaStream is running".into(),
+            ));
+        }
+        Self::set_dma_callback(Some(Box::new(callback)));
+        Ok(())
+    }
+
+    /// Remove the callback of the audio DMA interface. Fails while a ``DmaStream`` is running.
+    pub fn unregister_dma_callback() -> Result<()> {
+        if STREAMING.load(Ordering::Acquire) {
+            return Err(OgcError::Audio(
+                "Audio::unregister_dma_callback() failed, a DmaStream is running".into(),
+            ));
+        }
+        Self::set_dma_callback(None);
+        Ok(())
+    }
 
+    fn set_dma_callback(callback: Option<DmaCallback>) {
         unsafe {
-            let code: extern "C" fn() = mem::transmute(ptr);
-            // TODO: Do something with the returned callback.
-            let _ = ogc_sys::AUDIO_RegisterDMACallback(Some(code));
+            ogc_sys::AUDIO_RegisterDMACallback(callback.as_ref().map(|_| dma_trampoline as _));
         }
+        replace_callback(&DMA_CALLBACK, callback);
+    }
+
+    /// Stream 48 kHz stereo audio generated by ``fill``, see ``DmaStream``.
+    pub fn stream<F>(&self, frames: usize, fill: F) -> Result<DmaStream>
+    where
+        F: FnMut(&mut [i16]) + Send + 'static,
+    {
+        DmaStream::start(frames, fill)
     }
 
     /// Get the count of bytes, left to play, from the audio DMA interface.
@@ -201,3 +271,153 @@ impl Audio {
         unsafe { ogc_sys::AUDIO_SetStreamVolRight(volume) }
     }
 }
+
+/// Memory the audio DMA reads from: aligned and padded to 32 bytes.
+struct DmaBuffer {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+impl DmaBuffer {
+    fn new(frames: usize) -> Self {
+        let size = ((frames * DmaStream::FRAME_SIZE + 31) & !31).max(32);
+        let layout = Layout::from_size_align(size, 32).unwrap();
+        let ptr = unsafe { alloc_zeroed(layout) };
+        if ptr.is_null() {
+            handle_alloc_error(layout);
+        }
+        Self { ptr, layout }
+    }
+
+    fn samples(&mut self) -> &mut [i16] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr as *mut i16, self.layout.size() / 2) }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.ptr, self.layout.size()) }
+    }
+}
+
+impl Drop for DmaBuffer {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.ptr, self.layout) };
+    }
+}
+
+// The buffers are only touched by the DMA callback once the stream runs.
+unsafe impl Send for DmaBuffer {}
+
+/// Two buffers in turn: the DMA plays one while the other is filled.
+struct PingPong<F> {
+    buffers: [DmaBuffer; 2],
+    /// Buffer the DMA is set to play next.
+    queued: usize,
+    fill: F,
+}
+
+impl<F: FnMut(&mut [i16])> PingPong<F> {
+    /// Fills buffer `index`, flushes it from the data cache and sets the DMA to play it.
+    fn queue(&mut self, index: usize) {
+        let buffer = &mut self.buffers[index];
+        (self.fill)(buffer.samples());
+        System::flush_data_cache(buffer.bytes());
+        unsafe { ogc_sys::AUDIO_InitDMA(buffer.ptr as u32, buffer.layout.size() as u32) };
+        self.queued = index;
+    }
+}
+
+/// Audio streamed straight to the audio interface by DMA, produced by a callback.
+///
+/// The callback fills buffers of interleaved stereo 16-bit samples at 48 kHz, two of
+/// them in turn: while the DMA plays one, the callback fills the other. It runs in
+/// interrupt context whenever the DMA starts a buffer, so it must fill it before the
+/// one playing ends, and must not allocate.
+///
+/// The DMA cannot be shared, so this cannot run next to ``Asnd`` or ``Mp3Player``,
+/// whose mixing depends on the DMA callback. The stream stops when dropped.
+///
+/// # Example
+///
+/// ```rust
+/// let audio = Audio::init();
+/// let mut phase = 0.0f32;
+/// let stream = audio.stream(1024, move |samples| {
+///     for frame in samples.chunks_exact_mut(2) {
+///         let value = (libm::sinf(phase) * 8000.0) as i16;
+///         frame[0] = value;
+///         frame[1] = value;
+///         phase = (phase + 440.0 * core::f32::consts::TAU / 48000.0) % core::f32::consts::TAU;
+///     }
+/// })?;
+/// ```
+pub struct DmaStream {
+    frames: usize,
+}
+
+impl DmaStream {
+    /// Bytes in a stereo frame of 16-bit samples.
+    const FRAME_SIZE: usize = 4;
+
+    /// Starts streaming buffers of ``frames`` frames, rounded up to a multiple of 8.
+    /// Smaller buffers lower the latency, but leave the callback less time. Fails if a
+    /// stream is already running, or while ``Asnd`` or ``Mp3Player`` is initialized.
+    pub fn start<F>(frames: usize, fill: F) -> Result<Self>
+    where
+        F: FnMut(&mut [i16]) + Send + 'static,
+    {
+        if Asnd::is_initialized() || Mp3Player::is_initialized() {
+            return Err(OgcError::Audio(
+                "DmaStream::start() failed, the DMA is used by Asnd".into(),
+            ));
+        }
+        if STREAMING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(OgcError::Audio(
+                "DmaStream::start() failed, a stream is already running".into(),
+            ));
+        }
+
+        let mut ping_pong = PingPong {
+            buffers: [DmaBuffer::new(frames), DmaBuffer::new(frames)],
+            queued: 0,
+            fill,
+        };
+        let frames = ping_pong.buffers[0].layout.size() / Self::FRAME_SIZE;
+
+        Audio::set_dsp_samplerate(SampleRate::FortyEightKhz);
+        ping_pong.queue(0);
+        // Called once the DMA has started the queued buffer, so the other one is free.
+        Audio::set_dma_callback(Some(Box::new(move || {
+            let free = ping_pong.queued ^ 1;
+            ping_pong.queue(free);
+        })));
+        Audio::start_dma();
+
+        Ok(Self { frames })
+    }
+
+    /// Frames in each of the two buffers.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Pauses or resumes the DMA, which resumes with the buffer it was set to play next.
+    pub fn pause(&self, paused: bool) {
+        if paused {
+            Audio::stop_dma();
+        } else {
+            Audio::start_dma();
+        }
+    }
+}
+
+impl Drop for DmaStream {
+    fn drop(&mut self) {
+        // Stop the DMA before the callback, and the buffers it owns, are freed.
+        Audio::stop_dma();
+        Audio::set_dma_callback(None);
+        STREAMING.store(false, Ordering::Release);
+    }
+}
@@ -12,6 +12,10 @@ pub enum OgcError {
     Audio(String),
     Console(String),
     System(String),
+    Gx(String),
+    Fs(String),
+    Wpad(String),
+    Usb(String),
 }
 
 impl fmt::Debug for OgcError {
@@ -21,6 +25,10 @@ impl fmt::Debug for OgcError {
             OgcError::Audio(err) => write!(f, "[ OGC - Audio ]: {}", err),
             OgcError::Console(err) => write!(f, "[ OGC - Console ]: {}", err),
             OgcError::System(err) => write!(f, "[ OGC - System ]: {}", err),
+            OgcError::Gx(err) => write!(f, "[ OGC - Gx ]: {}", err),
+            OgcError::Fs(err) => write!(f, "[ OGC - Fs ]: {}", err),
+            OgcError::Wpad(err) => write!(f, "[ OGC - Wpad ]: {}", err),
+            OgcError::Usb(err) => write!(f, "[ OGC - Usb ]: {}", err),
         }
     }
 }
@@ -32,6 +40,10 @@ impl fmt::Display for OgcError {
             OgcError::Audio(err) => write!(f, "[ OGC - Audio ]: {}", err),
             OgcError::Console(err) => write!(f, "[ OGC - Console ]: {}", err),
             OgcError::System(err) => write!(f, "[ OGC - System ]: {}", err),
+            OgcError::Gx(err) => write!(f, "[ OGC - Gx ]: {}", err),
+            OgcError::Fs(err) => write!(f, "[ OGC - Fs ]: {}", err),
+            OgcError::Wpad(err) => write!(f, "[ OGC - Wpad ]: {}", err),
+            OgcError::Usb(err) => write!(f, "[ OGC - Usb ]: {}", err),
         }
     }
 }
@@ -0,0 +1,167 @@
+//! The ``fs`` module of ``ogc-rs``.
+//!
+//! This module implements a safe wrapper around the file functions found in ``stdio.h``.
+//!
+//! Note: The device holding the file has to be mounted before it can be accessed,
+//! for instance with `Fs::mount` for the SD card.
+
+use crate::{OgcError, Result};
+use alloc::{format, string::String, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+static MOUNTED: AtomicBool = AtomicBool::new(false);
+
+/// Represents the storage devices libfat can mount.
+pub struct Fs;
+
+impl Fs {
+    /// Mounts the SD card as `sd:/` and USB storage as `usb:/`.
+    /// Fails if neither could be mounted. Does nothing if already mounted.
+    /// See [fatInitDefault](https://github.com/devkitPro/libfat/blob/master/include/fat.h) for more.
+    pub fn mount() -> Result<()> {
+        if MOUNTED.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        if unsafe { ogc_sys::fatInitDefault() } {
+            MOUNTED.store(true, Ordering::Relaxed);
+            Ok(())
+        } else {
+            Err(OgcError::Fs("Fs::mount() found no device to mount".into()))
+        }
+    }
+
+    /// Whether `Fs::mount` succeeded.
+    pub fn is_mounted() -> bool {
+        MOUNTED.load(Ordering::Relaxed)
+    }
+}
+
+/// An open file on a mounted device, closed when dropped.
+///
+/// # Examples
+///
+/// Write a file to the SD card:
+///
+/// ```rust
+/// let mut file = File::create("sd:/hello.txt")?;
+/// file.write_all(b"Hello world!")?;
+/// ```
+pub struct File {
+    handle: *mut ogc_sys::FILE,
+}
+
+impl File {
+    /// Opens an existing file for reading.
+    pub fn open(path: &str) -> Result<Self> {
+        Self::with_mode(path, "rb\0")
+    }
+
+    /// Creates a file for writing, truncating it if it already exists.
+    pub fn create(path: &str) -> Result<Self> {
+        Self::with_mode(path, "wb\0")
+    }
+
+    fn with_mode(path: &str, mode: &str) -> Result<Self> {
+        let mut buffer = String::from(path);
+        buffer.push('\0');
+
+        let handle =
+            unsafe { ogc_sys::fopen(buffer.as_ptr() as *const _, mode.as_ptr() as *const _) };
+
+        if handle.is_null() {
+            Err(OgcError::Fs(format!(
+                "File::open() failed to open {}",
+                path
+            )))
+        } else {
+            Ok(Self { handle })
+        }
+    }
+
+    /// Reads up to `buffer.len()` bytes and returns how many were read. Returns 0 at the end of the file.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let read = unsafe {
+            ogc_sys::fread(
+                buffer.as_mut_ptr() as *mut _,
+                1,
+                buffer.len() as _,
+                self.handle,
+            )
+        };
+
+        if read < buffer.len() as _ && unsafe { ogc_sys::ferror(self.handle) } != 0 {
+            Err(OgcError::Fs("File::read() failed".into()))
+        } else {
+            Ok(read as usize)
+        }
+    }
+
+    /// Reads everything from the current position to the end of the file.
+    pub fn read_to_end(&mut self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut chunk = [0; 4096];
+
+        loop {
+            match self.read(&mut chunk)? {
+                0 => return Ok(data),
+                n => data.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+
+    /// Writes all of `data`.
+    pub fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        let written =
+            unsafe { ogc_sys::fwrite(data.as_ptr() as *const _, 1, data.len() as _, self.handle) };
+
+        if written as usize != data.len() {
+            Err(OgcError::Fs(format!(
+                "File::write_all() wrote {} of {} bytes",
+                written,
+                data.len()
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Moves the position to `offset` bytes from the start of the file.
+    pub fn seek(&mut self, offset: u32) -> Result<()> {
+        let r = unsafe { ogc_sys::fseek(self.handle, offset as _, ogc_sys::SEEK_SET as _) };
+
+        if r != 0 {
+            Err(OgcError::Fs(format!(
+                "File::seek() failed to seek to {}",
+                offset
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Current position, in bytes from the start of the file.
+    pub fn position(&self) -> u32 {
+        unsafe { ogc_sys::ftell(self.handle) as _ }
+    }
+
+    /// Size of the file, in bytes.
+    pub fn len(&mut self) -> Result<u32> {
+        let position = self.position();
+        let r = unsafe { ogc_sys::fseek(self.handle, 0, ogc_sys::SEEK_END as _) };
+        let len = self.position();
+        self.seek(position)?;
+
+        if r != 0 {
+            Err(OgcError::Fs("File::len() failed".into()))
+        } else {
+            Ok(len)
+        }
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        unsafe { ogc_sys::fclose(self.handle) };
+    }
+}
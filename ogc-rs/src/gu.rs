@@ -6,7 +6,7 @@
 pub struct Gu;
 
 use ogc_sys::guVector;
-use ogc_sys::{Mtx as Mtx34, Mtx44};
+pub use ogc_sys::{Mtx as Mtx34, Mtx44};
 
 impl Gu {
     /// Sets a 4x4 matrix for orthographic projection.
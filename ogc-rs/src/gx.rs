@@ -2,9 +2,24 @@
 //!
 //! This module implements a safe wrapper around the graphics functions found in ``gx.h``.
 
-use core::ffi::c_void;
+use crate::{OgcError, Result};
+use alloc::{format, vec::Vec};
+use core::{
+    ffi::c_void,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
 use ogc_sys::{Mtx as Mtx34, Mtx44};
 
+/// Maximum value of the 24-bit Z buffer, used when clearing the EFB.
+pub const MAX_Z24: u32 = ogc_sys::GX_MAX_Z24;
+
+static PRIMITIVE_COUNT: AtomicU32 = AtomicU32::new(0);
+static VERTEX_COUNT: AtomicU32 = AtomicU32::new(0);
+/// Whether commands go into a display list, whose primitives are counted when it is called.
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static RECORDED_PRIMITIVES: AtomicU32 = AtomicU32::new(0);
+static RECORDED_VERTICES: AtomicU32 = AtomicU32::new(0);
+
 /// Helper function for `Gx::init`
 pub fn gp_fifo(fifo_size: usize) -> *mut c_void {
     unsafe {
@@ -23,6 +38,567 @@ impl Color {
     }
 }
 
+/// Primitive type to be drawn by `Gx::begin`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Primitive {
+    Points = ogc_sys::GX_POINTS as u8,
+    Lines = ogc_sys::GX_LINES as u8,
+    LineStrip = ogc_sys::GX_LINESTRIP as u8,
+    Triangles = ogc_sys::GX_TRIANGLES as u8,
+    TriangleStrip = ogc_sys::GX_TRIANGLESTRIP as u8,
+    TriangleFan = ogc_sys::GX_TRIANGLEFAN as u8,
+    Quads = ogc_sys::GX_QUADS as u8,
+}
+
+/// Index into the Vertex Attribute Table (VAT).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum VtxFmt {
+    Fmt0 = ogc_sys::GX_VTXFMT0 as u8,
+    Fmt1 = ogc_sys::GX_VTXFMT1 as u8,
+    Fmt2 = ogc_sys::GX_VTXFMT2 as u8,
+    Fmt3 = ogc_sys::GX_VTXFMT3 as u8,
+    Fmt4 = ogc_sys::GX_VTXFMT4 as u8,
+    Fmt5 = ogc_sys::GX_VTXFMT5 as u8,
+    Fmt6 = ogc_sys::GX_VTXFMT6 as u8,
+    Fmt7 = ogc_sys::GX_VTXFMT7 as u8,
+}
+
+/// Vertex attribute, in the order the attributes are sent to the FIFO.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[repr(u8)]
+pub enum VtxAttr {
+    PosNrmMtxIdx = ogc_sys::GX_VA_PTNMTXIDX as u8,
+    Tex0MtxIdx = ogc_sys::GX_VA_TEX0MTXIDX as u8,
+    Tex1MtxIdx = ogc_sys::GX_VA_TEX1MTXIDX as u8,
+    Tex2MtxIdx = ogc_sys::GX_VA_TEX2MTXIDX as u8,
+    Tex3MtxIdx = ogc_sys::GX_VA_TEX3MTXIDX as u8,
+    Tex4MtxIdx = ogc_sys::GX_VA_TEX4MTXIDX as u8,
+    Tex5MtxIdx = ogc_sys::GX_VA_TEX5MTXIDX as u8,
+    Tex6MtxIdx = ogc_sys::GX_VA_TEX6MTXIDX as u8,
+    Tex7MtxIdx = ogc_sys::GX_VA_TEX7MTXIDX as u8,
+    Position = ogc_sys::GX_VA_POS as u8,
+    Normal = ogc_sys::GX_VA_NRM as u8,
+    Color0 = ogc_sys::GX_VA_CLR0 as u8,
+    Color1 = ogc_sys::GX_VA_CLR1 as u8,
+    Tex0 = ogc_sys::GX_VA_TEX0 as u8,
+    Tex1 = ogc_sys::GX_VA_TEX1 as u8,
+    Tex2 = ogc_sys::GX_VA_TEX2 as u8,
+    Tex3 = ogc_sys::GX_VA_TEX3 as u8,
+    Tex4 = ogc_sys::GX_VA_TEX4 as u8,
+    Tex5 = ogc_sys::GX_VA_TEX5 as u8,
+    Tex6 = ogc_sys::GX_VA_TEX6 as u8,
+    Tex7 = ogc_sys::GX_VA_TEX7 as u8,
+}
+
+/// How a vertex attribute is sent to the FIFO.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum AttrType {
+    None = ogc_sys::GX_NONE as u8,
+    Direct = ogc_sys::GX_DIRECT as u8,
+    Index8 = ogc_sys::GX_INDEX8 as u8,
+    Index16 = ogc_sys::GX_INDEX16 as u8,
+}
+
+/// Number of components of a position attribute.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PositionFormat {
+    Xy,
+    Xyz,
+}
+
+/// Number of components of a normal attribute.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NormalFormat {
+    Xyz,
+    Nbt,
+    Nbt3,
+}
+
+/// Number of components of a texture coordinate attribute.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TexFormat {
+    S,
+    St,
+}
+
+/// Storage type of a position, normal or texture coordinate component.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ComponentFormat {
+    U8 = ogc_sys::GX_U8,
+    S8 = ogc_sys::GX_S8,
+    U16 = ogc_sys::GX_U16,
+    S16 = ogc_sys::GX_S16,
+    F32 = ogc_sys::GX_F32,
+}
+
+/// Storage type of a color attribute. The alpha channel is implied by the format.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ColorFormat {
+    Rgb565 = ogc_sys::GX_RGB565,
+    Rgb8 = ogc_sys::GX_RGB8,
+    Rgbx8 = ogc_sys::GX_RGBX8,
+    Rgba4 = ogc_sys::GX_RGBA4,
+    Rgba6 = ogc_sys::GX_RGBA6,
+    Rgba8 = ogc_sys::GX_RGBA8,
+}
+
+impl ColorFormat {
+    fn component_type(self) -> u32 {
+        match self {
+            ColorFormat::Rgb565 | ColorFormat::Rgb8 | ColorFormat::Rgbx8 => ogc_sys::GX_CLR_RGB,
+            ColorFormat::Rgba4 | ColorFormat::Rgba6 | ColorFormat::Rgba8 => ogc_sys::GX_CLR_RGBA,
+        }
+    }
+}
+
+/// Full description of a single attribute in the Vertex Attribute Table (VAT).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AttrFormat {
+    Position(PositionFormat, ComponentFormat, u8),
+    Normal(NormalFormat, ComponentFormat),
+    Color(ColorFormat),
+    TexCoord(TexFormat, ComponentFormat, u8),
+}
+
+impl AttrFormat {
+    fn as_raw(self) -> (u32, u32, u32) {
+        match self {
+            AttrFormat::Position(format, component, frac) => {
+                let comp_type = match format {
+                    PositionFormat::Xy => ogc_sys::GX_POS_XY,
+                    PositionFormat::Xyz => ogc_sys::GX_POS_XYZ,
+                };
+                (comp_type, component as u32, frac as u32)
+            }
+            AttrFormat::Normal(format, component) => {
+                let comp_type = match format {
+                    NormalFormat::Xyz => ogc_sys::GX_NRM_XYZ,
+                    NormalFormat::Nbt => ogc_sys::GX_NRM_NBT,
+                    NormalFormat::Nbt3 => ogc_sys::GX_NRM_NBT3,
+                };
+                (comp_type, component as u32, 0)
+            }
+            AttrFormat::Color(format) => (format.component_type(), format as u32, 0),
+            AttrFormat::TexCoord(format, component, frac) => {
+                let comp_type = match format {
+                    TexFormat::S => ogc_sys::GX_TEX_S,
+                    TexFormat::St => ogc_sys::GX_TEX_ST,
+                };
+                (comp_type, component as u32, frac as u32)
+            }
+        }
+    }
+
+    fn matches(self, attr: VtxAttr) -> bool {
+        match self {
+            AttrFormat::Position(..) => attr == VtxAttr::Position,
+            AttrFormat::Normal(..) => attr == VtxAttr::Normal,
+            AttrFormat::Color(_) => matches!(attr, VtxAttr::Color0 | VtxAttr::Color1),
+            AttrFormat::TexCoord(..) => attr >= VtxAttr::Tex0,
+        }
+    }
+}
+
+/// Comparison function used by the Z buffer and the alpha compare.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum CompareFn {
+    Never = ogc_sys::GX_NEVER as u8,
+    Less = ogc_sys::GX_LESS as u8,
+    Equal = ogc_sys::GX_EQUAL as u8,
+    LessEqual = ogc_sys::GX_LEQUAL as u8,
+    Greater = ogc_sys::GX_GREATER as u8,
+    NotEqual = ogc_sys::GX_NEQUAL as u8,
+    GreaterEqual = ogc_sys::GX_GEQUAL as u8,
+    Always = ogc_sys::GX_ALWAYS as u8,
+}
+
+/// Operation combining the two alpha compare results.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum AlphaOp {
+    And = ogc_sys::GX_AOP_AND as u8,
+    Or = ogc_sys::GX_AOP_OR as u8,
+    Xor = ogc_sys::GX_AOP_XOR as u8,
+    Xnor = ogc_sys::GX_AOP_XNOR as u8,
+}
+
+/// Blending mode used when writing to the EFB.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum BlendMode {
+    None = ogc_sys::GX_BM_NONE as u8,
+    Blend = ogc_sys::GX_BM_BLEND as u8,
+    Logic = ogc_sys::GX_BM_LOGIC as u8,
+    Subtract = ogc_sys::GX_BM_SUBTRACT as u8,
+}
+
+/// Source and destination factor used by `BlendMode::Blend`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum BlendFactor {
+    Zero = ogc_sys::GX_BL_ZERO as u8,
+    One = ogc_sys::GX_BL_ONE as u8,
+    SrcColor = ogc_sys::GX_BL_SRCCLR as u8,
+    InvSrcColor = ogc_sys::GX_BL_INVSRCCLR as u8,
+    SrcAlpha = ogc_sys::GX_BL_SRCALPHA as u8,
+    InvSrcAlpha = ogc_sys::GX_BL_INVSRCALPHA as u8,
+    DstAlpha = ogc_sys::GX_BL_DSTALPHA as u8,
+    InvDstAlpha = ogc_sys::GX_BL_INVDSTALPHA as u8,
+}
+
+/// Logic operation used by `BlendMode::Logic`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum LogicOp {
+    Clear = ogc_sys::GX_LO_CLEAR as u8,
+    And = ogc_sys::GX_LO_AND as u8,
+    RevAnd = ogc_sys::GX_LO_REVAND as u8,
+    Copy = ogc_sys::GX_LO_COPY as u8,
+    InvAnd = ogc_sys::GX_LO_INVAND as u8,
+    Noop = ogc_sys::GX_LO_NOOP as u8,
+    Xor = ogc_sys::GX_LO_XOR as u8,
+    Or = ogc_sys::GX_LO_OR as u8,
+    Nor = ogc_sys::GX_LO_NOR as u8,
+    Equiv = ogc_sys::GX_LO_EQUIV as u8,
+    Inv = ogc_sys::GX_LO_INV as u8,
+    RevOr = ogc_sys::GX_LO_REVOR as u8,
+    InvCopy = ogc_sys::GX_LO_INVCOPY as u8,
+    InvOr = ogc_sys::GX_LO_INVOR as u8,
+    Nand = ogc_sys::GX_LO_NAND as u8,
+    Set = ogc_sys::GX_LO_SET as u8,
+}
+
+/// Which faces are culled by the rasterizer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum CullMode {
+    None = ogc_sys::GX_CULL_NONE as u8,
+    Front = ogc_sys::GX_CULL_FRONT as u8,
+    Back = ogc_sys::GX_CULL_BACK as u8,
+    All = ogc_sys::GX_CULL_ALL as u8,
+}
+
+/// Whether clipping is performed by the transform unit.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum ClipMode {
+    Enable = ogc_sys::GX_CLIP_ENABLE as u8,
+    Disable = ogc_sys::GX_CLIP_DISABLE as u8,
+}
+
+/// Pixel format of the EFB.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum PixelFormat {
+    Rgb8Z24 = ogc_sys::GX_PF_RGB8_Z24 as u8,
+    Rgba6Z24 = ogc_sys::GX_PF_RGBA6_Z24 as u8,
+    Rgb565Z16 = ogc_sys::GX_PF_RGB565_Z16 as u8,
+    Z24 = ogc_sys::GX_PF_Z24 as u8,
+    Y8 = ogc_sys::GX_PF_Y8 as u8,
+    U8 = ogc_sys::GX_PF_U8 as u8,
+    V8 = ogc_sys::GX_PF_V8 as u8,
+    Yuv420 = ogc_sys::GX_PF_YUV420 as u8,
+}
+
+/// Compression of the 16-bit Z buffer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum ZCompress {
+    Linear = ogc_sys::GX_ZC_LINEAR as u8,
+    Near = ogc_sys::GX_ZC_NEAR as u8,
+    Mid = ogc_sys::GX_ZC_MID as u8,
+    Far = ogc_sys::GX_ZC_FAR as u8,
+}
+
+/// Gamma correction applied during the EFB to XFB copy.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Gamma {
+    One = ogc_sys::GX_GM_1_0 as u8,
+    OnePointSeven = ogc_sys::GX_GM_1_7 as u8,
+    TwoPointTwo = ogc_sys::GX_GM_2_2 as u8,
+}
+
+/// Type of projection matrix loaded with `Gx::load_projection_mtx`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum ProjectionType {
+    Perspective = ogc_sys::GX_PERSPECTIVE as u8,
+    Orthographic = ogc_sys::GX_ORTHOGRAPHIC as u8,
+}
+
+/// Position/normal matrix slot in matrix memory.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum PosMtx {
+    Mtx0 = ogc_sys::GX_PNMTX0,
+    Mtx1 = ogc_sys::GX_PNMTX1,
+    Mtx2 = ogc_sys::GX_PNMTX2,
+    Mtx3 = ogc_sys::GX_PNMTX3,
+    Mtx4 = ogc_sys::GX_PNMTX4,
+    Mtx5 = ogc_sys::GX_PNMTX5,
+    Mtx6 = ogc_sys::GX_PNMTX6,
+    Mtx7 = ogc_sys::GX_PNMTX7,
+    Mtx8 = ogc_sys::GX_PNMTX8,
+    Mtx9 = ogc_sys::GX_PNMTX9,
+}
+
+/// Texture matrix used when generating texture coordinates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum TexMtx {
+    Identity = ogc_sys::GX_IDENTITY,
+    Mtx0 = ogc_sys::GX_TEXMTX0,
+    Mtx1 = ogc_sys::GX_TEXMTX1,
+    Mtx2 = ogc_sys::GX_TEXMTX2,
+    Mtx3 = ogc_sys::GX_TEXMTX3,
+    Mtx4 = ogc_sys::GX_TEXMTX4,
+    Mtx5 = ogc_sys::GX_TEXMTX5,
+    Mtx6 = ogc_sys::GX_TEXMTX6,
+    Mtx7 = ogc_sys::GX_TEXMTX7,
+    Mtx8 = ogc_sys::GX_TEXMTX8,
+    Mtx9 = ogc_sys::GX_TEXMTX9,
+}
+
+/// Function used to generate texture coordinates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum TexGenType {
+    Mtx3x4 = ogc_sys::GX_TG_MTX3x4,
+    Mtx2x4 = ogc_sys::GX_TG_MTX2x4,
+    Srtg = ogc_sys::GX_TG_SRTG,
+}
+
+/// Source of the generated texture coordinates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum TexGenSrc {
+    Position = ogc_sys::GX_TG_POS,
+    Normal = ogc_sys::GX_TG_NRM,
+    Binormal = ogc_sys::GX_TG_BINRM,
+    Tangent = ogc_sys::GX_TG_TANGENT,
+    Tex0 = ogc_sys::GX_TG_TEX0,
+    Tex1 = ogc_sys::GX_TG_TEX1,
+    Tex2 = ogc_sys::GX_TG_TEX2,
+    Tex3 = ogc_sys::GX_TG_TEX3,
+    Tex4 = ogc_sys::GX_TG_TEX4,
+    Tex5 = ogc_sys::GX_TG_TEX5,
+    Tex6 = ogc_sys::GX_TG_TEX6,
+    Tex7 = ogc_sys::GX_TG_TEX7,
+    Color0 = ogc_sys::GX_TG_COLOR0,
+    Color1 = ogc_sys::GX_TG_COLOR1,
+}
+
+/// TEV stage identifier.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum TevStage {
+    Stage0 = ogc_sys::GX_TEVSTAGE0 as u8,
+    Stage1 = ogc_sys::GX_TEVSTAGE1 as u8,
+    Stage2 = ogc_sys::GX_TEVSTAGE2 as u8,
+    Stage3 = ogc_sys::GX_TEVSTAGE3 as u8,
+    Stage4 = ogc_sys::GX_TEVSTAGE4 as u8,
+    Stage5 = ogc_sys::GX_TEVSTAGE5 as u8,
+    Stage6 = ogc_sys::GX_TEVSTAGE6 as u8,
+    Stage7 = ogc_sys::GX_TEVSTAGE7 as u8,
+    Stage8 = ogc_sys::GX_TEVSTAGE8 as u8,
+    Stage9 = ogc_sys::GX_TEVSTAGE9 as u8,
+    Stage10 = ogc_sys::GX_TEVSTAGE10 as u8,
+    Stage11 = ogc_sys::GX_TEVSTAGE11 as u8,
+    Stage12 = ogc_sys::GX_TEVSTAGE12 as u8,
+    Stage13 = ogc_sys::GX_TEVSTAGE13 as u8,
+    Stage14 = ogc_sys::GX_TEVSTAGE14 as u8,
+    Stage15 = ogc_sys::GX_TEVSTAGE15 as u8,
+}
+
+/// Predefined TEV combiner mode for `Gx::set_tev_op`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum TevMode {
+    Modulate = ogc_sys::GX_MODULATE as u8,
+    Decal = ogc_sys::GX_DECAL as u8,
+    Blend = ogc_sys::GX_BLEND as u8,
+    Replace = ogc_sys::GX_REPLACE as u8,
+    PassColor = ogc_sys::GX_PASSCLR as u8,
+}
+
+/// Texture coordinate slot.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum TexCoordId {
+    Coord0 = ogc_sys::GX_TEXCOORD0 as u8,
+    Coord1 = ogc_sys::GX_TEXCOORD1 as u8,
+    Coord2 = ogc_sys::GX_TEXCOORD2 as u8,
+    Coord3 = ogc_sys::GX_TEXCOORD3 as u8,
+    Coord4 = ogc_sys::GX_TEXCOORD4 as u8,
+    Coord5 = ogc_sys::GX_TEXCOORD5 as u8,
+    Coord6 = ogc_sys::GX_TEXCOORD6 as u8,
+    Coord7 = ogc_sys::GX_TEXCOORD7 as u8,
+    Null = ogc_sys::GX_TEXCOORDNULL as u8,
+}
+
+/// Texture map slot.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum TexMapId {
+    Map0 = ogc_sys::GX_TEXMAP0,
+    Map1 = ogc_sys::GX_TEXMAP1,
+    Map2 = ogc_sys::GX_TEXMAP2,
+    Map3 = ogc_sys::GX_TEXMAP3,
+    Map4 = ogc_sys::GX_TEXMAP4,
+    Map5 = ogc_sys::GX_TEXMAP5,
+    Map6 = ogc_sys::GX_TEXMAP6,
+    Map7 = ogc_sys::GX_TEXMAP7,
+    Null = ogc_sys::GX_TEXMAP_NULL,
+    Disable = ogc_sys::GX_TEX_DISABLE,
+}
+
+/// Rasterized color channel.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum ChannelId {
+    Color0 = ogc_sys::GX_COLOR0 as u8,
+    Color1 = ogc_sys::GX_COLOR1 as u8,
+    Alpha0 = ogc_sys::GX_ALPHA0 as u8,
+    Alpha1 = ogc_sys::GX_ALPHA1 as u8,
+    Color0Alpha0 = ogc_sys::GX_COLOR0A0 as u8,
+    Color1Alpha1 = ogc_sys::GX_COLOR1A1 as u8,
+    ColorZero = ogc_sys::GX_COLORZERO as u8,
+    AlphaBump = ogc_sys::GX_ALPHA_BUMP as u8,
+    AlphaBumpN = ogc_sys::GX_ALPHA_BUMPN as u8,
+    Null = ogc_sys::GX_COLORNULL as u8,
+}
+
+/// Description of a single enabled vertex attribute.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct VertexAttribute {
+    pub attr: VtxAttr,
+    pub attr_type: AttrType,
+    pub format: Option<AttrFormat>,
+}
+
+/// Builder for the current vertex descriptor and one entry of the Vertex Attribute Table.
+///
+/// # Examples
+///
+/// Describe vertices with a direct `f32` position and a direct RGBA8 color:
+///
+/// ```rust
+/// let format = VertexFormat::new(VtxFmt::Fmt0)
+///     .position(AttrType::Direct, PositionFormat::Xyz, ComponentFormat::F32, 0)
+///     .color(VtxAttr::Color0, AttrType::Direct, ColorFormat::Rgba8);
+/// format.apply();
+/// ```
+#[derive(Clone, Debug)]
+pub struct VertexFormat {
+    vtxfmt: VtxFmt,
+    attributes: Vec<VertexAttribute>,
+}
+
+impl VertexFormat {
+    /// Create an empty vertex format for the given VAT entry.
+    pub fn new(vtxfmt: VtxFmt) -> Self {
+        Self {
+            vtxfmt,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Enable an index into matrix memory, sent before all other attributes.
+    pub fn matrix_index(self, attr: VtxAttr) -> Self {
+        assert!(attr < VtxAttr::Position, "{:?} is not a matrix index", attr);
+        self.with(attr, AttrType::Direct, None)
+    }
+
+    /// Enable the position attribute.
+    pub fn position(
+        self,
+        attr_type: AttrType,
+        format: PositionFormat,
+        component: ComponentFormat,
+        frac: u8,
+    ) -> Self {
+        let format = AttrFormat::Position(format, component, frac);
+        self.with(VtxAttr::Position, attr_type, Some(format))
+    }
+
+    /// Enable the normal attribute.
+    pub fn normal(
+        self,
+        attr_type: AttrType,
+        format: NormalFormat,
+        component: ComponentFormat,
+    ) -> Self {
+        let format = AttrFormat::Normal(format, component);
+        self.with(VtxAttr::Normal, attr_type, Some(format))
+    }
+
+    /// Enable one of the two color attributes.
+    pub fn color(self, attr: VtxAttr, attr_type: AttrType, format: ColorFormat) -> Self {
+        let format = AttrFormat::Color(format);
+        assert!(format.matches(attr), "{:?} is not a color attribute", attr);
+        self.with(attr, attr_type, Some(format))
+    }
+
+    /// Enable one of the eight texture coordinate attributes.
+    pub fn tex_coord(
+        self,
+        attr: VtxAttr,
+        attr_type: AttrType,
+        format: TexFormat,
+        component: ComponentFormat,
+        frac: u8,
+    ) -> Self {
+        let format = AttrFormat::TexCoord(format, component, frac);
+        assert!(
+            format.matches(attr),
+            "{:?} is not a texture coordinate",
+            attr
+        );
+        self.with(attr, attr_type, Some(format))
+    }
+
+    fn with(mut self, attr: VtxAttr, attr_type: AttrType, format: Option<AttrFormat>) -> Self {
+        self.attributes.retain(|a| a.attr != attr);
+        if attr_type != AttrType::None {
+            self.attributes.push(VertexAttribute {
+                attr,
+                attr_type,
+                format,
+            });
+            self.attributes.sort_by_key(|a| a.attr);
+        }
+        self
+    }
+
+    /// VAT entry this format describes.
+    pub fn vtxfmt(&self) -> VtxFmt {
+        self.vtxfmt
+    }
+
+    /// Enabled attributes, in the order they must be sent to the FIFO.
+    pub fn attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    /// Clear the current vertex descriptor and load this format into it and the VAT.
+    pub fn apply(&self) {
+        Gx::clear_vtx_desc();
+        for attribute in self.attributes.iter() {
+            Gx::set_vtx_desc(attribute.attr, attribute.attr_type);
+            if let Some(format) = attribute.format {
+                Gx::set_vtx_attr_fmt(self.vtxfmt, attribute.attr, format);
+            }
+        }
+    }
+}
+
 /// Represents the GX service.
 pub struct Gx;
 
@@ -89,10 +665,12 @@ impl Gx {
     pub fn set_copy_filter(
         aa: u8,
         sample_pattern: &mut [[u8; 2]; 12],
-        vf: u8,
+        vf: bool,
         v_filter: &mut [u8; 7],
     ) {
-        unsafe { ogc_sys::GX_SetCopyFilter(aa, sample_pattern as *mut _, vf, v_filter as *mut _) }
+        unsafe {
+            ogc_sys::GX_SetCopyFilter(aa, sample_pattern as *mut _, vf as _, v_filter as *mut _)
+        }
     }
 
     /// Controls various rasterization and texturing parameters that relate to field-mode and double-strike rendering.
@@ -103,32 +681,39 @@ impl Gx {
 
     /// Sets the format of pixels in the Embedded Frame Buffer (EFB).
     /// See [GX_SetPixelFmt](https://libogc.devkitpro.org/gx_8h.html#a018d9b0359f9689ac41f44f0b2374ffb) for more.
-    pub fn set_pixel_fmt(pix_fmt: u8, z_fmt: u8) {
-        unsafe { ogc_sys::GX_SetPixelFmt(pix_fmt, z_fmt) }
+    pub fn set_pixel_fmt(pix_fmt: PixelFormat, z_fmt: ZCompress) {
+        unsafe { ogc_sys::GX_SetPixelFmt(pix_fmt as _, z_fmt as _) }
     }
 
     /// Enables or disables culling of geometry based on its orientation to the viewer.
     /// See [GX_SetCullMode](https://libogc.devkitpro.org/gx_8h.html#adb4b17c39b24073c3e961458ecf02e87) for more.
-    pub fn set_cull_mode(mode: u8) {
-        unsafe { ogc_sys::GX_SetCullMode(mode) }
+    pub fn set_cull_mode(mode: CullMode) {
+        unsafe { ogc_sys::GX_SetCullMode(mode as _) }
     }
 
     /// Copies the embedded framebuffer (EFB) to the external framebuffer(XFB) in main memory.
     /// See [GX_CopyDisp](https://libogc.devkitpro.org/gx_8h.html#a9ed0ae3f900abb6af2e930dff7a6bc28) for more.
-    pub fn copy_disp(dest: *mut c_void, clear: u8) {
-        unsafe { ogc_sys::GX_CopyDisp(dest, clear) }
+    pub fn copy_disp(dest: *mut c_void, clear: bool) {
+        unsafe { ogc_sys::GX_CopyDisp(dest, clear as _) }
     }
 
     /// Sets the gamma correction applied to pixels during EFB to XFB copy operation.
     /// See [GX_SetDispCopyGamma](https://libogc.devkitpro.org/gx_8h.html#aa8e5bc962cc786b2049345fa698d4efa) for more.
-    pub fn set_disp_copy_gamma(gamma: u8) {
-        unsafe { ogc_sys::GX_SetDispCopyGamma(gamma) }
+    pub fn set_disp_copy_gamma(gamma: Gamma) {
+        unsafe { ogc_sys::GX_SetDispCopyGamma(gamma as _) }
     }
 
     /// Sets the attribute format (vtxattr) for a single attribute in the Vertex Attribute Table (VAT).
     /// See [GX_SetVtxAttrFmt](https://libogc.devkitpro.org/gx_8h.html#a87437061debcc0457b6b6dc2eb021f23) for more.
-    pub fn set_vtx_attr_fmt(vtxfmt: u8, vtxattr: u32, comptype: u32, compsize: u32, frac: u32) {
-        unsafe { ogc_sys::GX_SetVtxAttrFmt(vtxfmt, vtxattr, comptype, compsize, frac) }
+    pub fn set_vtx_attr_fmt(vtxfmt: VtxFmt, vtxattr: VtxAttr, format: AttrFormat) {
+        assert!(
+            format.matches(vtxattr),
+            "{:?} cannot be used for {:?}",
+            format,
+            vtxattr
+        );
+        let (comptype, compsize, frac) = format.as_raw();
+        unsafe { ogc_sys::GX_SetVtxAttrFmt(vtxfmt as _, vtxattr as _, comptype, compsize, frac) }
     }
 
     /// Sets the number of color channels that are output to the TEV stages.
@@ -145,20 +730,32 @@ impl Gx {
 
     /// Simplified function to set various TEV parameters for this tevstage based on a predefined combiner mode.
     /// See [GX_SetTevOp](https://libogc.devkitpro.org/gx_8h.html#a68554713cdde7b45ae4d5ce156239cf8) for more.
-    pub fn set_tev_op(tevstage: u8, mode: u8) {
-        unsafe { ogc_sys::GX_SetTevOp(tevstage, mode) }
+    pub fn set_tev_op(tevstage: TevStage, mode: TevMode) {
+        unsafe { ogc_sys::GX_SetTevOp(tevstage as _, mode as _) }
     }
 
     /// Specifies the texture and rasterized color that will be available as inputs to this TEV tevstage.
     /// See [GX_SetTevOrder](https://libogc.devkitpro.org/gx_8h.html#ae64799e52298de39efc74bf989fc57f5) for more.
-    pub fn set_tev_order(tevstage: u8, texcoord: u8, texmap: u32, color: u8) {
-        unsafe { ogc_sys::GX_SetTevOrder(tevstage, texcoord, texmap, color) }
+    pub fn set_tev_order(
+        tevstage: TevStage,
+        texcoord: TexCoordId,
+        texmap: TexMapId,
+        color: ChannelId,
+    ) {
+        unsafe { ogc_sys::GX_SetTevOrder(tevstage as _, texcoord as _, texmap as _, color as _) }
     }
 
     /// Specifies how texture coordinates are generated.
     /// See [GX_SetTexCoordGen](https://libogc.devkitpro.org/gx_8h.html#a7d3139b693ace5587c3224e7df2d8245) for more.
-    pub fn set_tex_coord_gen(texcoord: u16, tgen_typ: u32, tgen_src: u32, mtxsrc: u32) {
-        unsafe { ogc_sys::GX_SetTexCoordGen(texcoord, tgen_typ, tgen_src, mtxsrc) }
+    pub fn set_tex_coord_gen(
+        texcoord: TexCoordId,
+        tgen_typ: TexGenType,
+        tgen_src: TexGenSrc,
+        mtxsrc: TexMtx,
+    ) {
+        unsafe {
+            ogc_sys::GX_SetTexCoordGen(texcoord as _, tgen_typ as _, tgen_src as _, mtxsrc as _)
+        }
     }
 
     /// Invalidates the current caches of the Texture Memory (TMEM).
@@ -169,14 +766,14 @@ impl Gx {
 
     /// Loads the state describing a texture into one of eight hardware register sets.
     /// See [GX_LoadTexObj](https://libogc.devkitpro.org/gx_8h.html#ad6388b0e4a0f2ffb5daa16a8851fa567) for more.
-    pub fn load_tex_obj(obj: &mut ogc_sys::GXTexObj, mapid: u8) {
-        unsafe { ogc_sys::GX_LoadTexObj(obj, mapid) }
+    pub fn load_tex_obj(obj: &mut ogc_sys::GXTexObj, mapid: TexMapId) {
+        unsafe { ogc_sys::GX_LoadTexObj(obj, mapid as _) }
     }
 
     /// Sets the projection matrix.
     /// See [GX_LoadProjectionMtx](https://libogc.devkitpro.org/gx_8h.html#a241a1301f006ed04b7895c051959f64e) for more.
-    pub fn load_projection_mtx(mt: &mut Mtx44, p_type: u8) {
-        unsafe { ogc_sys::GX_LoadProjectionMtx(mt as *mut _, p_type) }
+    pub fn load_projection_mtx(mt: &mut Mtx44, p_type: ProjectionType) {
+        unsafe { ogc_sys::GX_LoadProjectionMtx(mt as *mut _, p_type as _) }
     }
 
     /// Invalidates the vertex cache.
@@ -193,14 +790,14 @@ impl Gx {
 
     /// Sets the type of a single attribute (attr) in the current vertex descriptor.
     /// See [GX_SetVtxDesc](https://libogc.devkitpro.org/gx_8h.html#af41b45011ae731ae5697b26b2bf97e2f) for more.
-    pub fn set_vtx_desc(attr: u8, v_type: u8) {
-        unsafe { ogc_sys::GX_SetVtxDesc(attr, v_type) }
+    pub fn set_vtx_desc(attr: VtxAttr, v_type: AttrType) {
+        unsafe { ogc_sys::GX_SetVtxDesc(attr as _, v_type as _) }
     }
 
     /// Used to load a 3x4 modelview matrix mt into matrix memory at location pnidx.
     /// See [GX_LoadPosMtxImm](https://libogc.devkitpro.org/gx_8h.html#a90349e713128a1fa4fd6048dcab7b5e7) for more.
-    pub fn load_pos_mtx_imm(mt: &mut Mtx34, pnidx: u32) {
-        unsafe { ogc_sys::GX_LoadPosMtxImm(mt as *mut _, pnidx) }
+    pub fn load_pos_mtx_imm(mt: &mut Mtx34, pnidx: PosMtx) {
+        unsafe { ogc_sys::GX_LoadPosMtxImm(mt as *mut _, pnidx as _) }
     }
 
     /// Sends a DrawDone command to the GP and stalls until its subsequent execution.
@@ -211,50 +808,73 @@ impl Gx {
 
     /// Sets the Z-buffer compare mode.
     /// See [GX_SetZMode](https://libogc.devkitpro.org/gx_8h.html#a2af0d050f56ef45dd25d0db18909fa00) for more.
-    pub fn set_z_mode(enable: u8, func: u8, update_enable: u8) {
-        unsafe { ogc_sys::GX_SetZMode(enable, func, update_enable) }
+    pub fn set_z_mode(enable: bool, func: CompareFn, update_enable: bool) {
+        unsafe { ogc_sys::GX_SetZMode(enable as _, func as _, update_enable as _) }
     }
 
     /// Determines how the source image, generated by the graphics processor, is blended with the Embedded Frame Buffer (EFB).
     /// See [GX_SetBlendMode](https://libogc.devkitpro.org/gx_8h.html#a1d9c43b161f3c5a30b9fd8ea182c8eb6) for more.
-    pub fn set_blend_mode(b_type: u8, src_fact: u8, dst_fact: u8, op: u8) {
-        unsafe { ogc_sys::GX_SetBlendMode(b_type, src_fact, dst_fact, op) }
+    pub fn set_blend_mode(
+        b_type: BlendMode,
+        src_fact: BlendFactor,
+        dst_fact: BlendFactor,
+        op: LogicOp,
+    ) {
+        unsafe { ogc_sys::GX_SetBlendMode(b_type as _, src_fact as _, dst_fact as _, op as _) }
     }
 
     /// Enables or disables alpha-buffer updates of the Embedded Frame Buffer (EFB).
     /// See [GX_SetAlphaUpdate](https://libogc.devkitpro.org/gx_8h.html#ac238051bda896c8bb11802184882a2a0) for more.
-    pub fn set_alpha_update(enable: u8) {
-        unsafe { ogc_sys::GX_SetAlphaUpdate(enable) }
+    pub fn set_alpha_update(enable: bool) {
+        unsafe { ogc_sys::GX_SetAlphaUpdate(enable as _) }
     }
 
     /// Enables or disables color-buffer updates when rendering into the Embedded Frame Buffer (EFB).
     /// See [GX_SetColorUpdate](https://libogc.devkitpro.org/gx_8h.html#a3978e3b08198e52d7cea411e90ece3e5) for more.
-    pub fn set_color_update(enable: u8) {
-        unsafe { ogc_sys::GX_SetColorUpdate(enable) }
+    pub fn set_color_update(enable: bool) {
+        unsafe { ogc_sys::GX_SetColorUpdate(enable as _) }
     }
 
     /// Sets the array base pointer and stride for a single attribute.
     /// See [GX_SetArray](https://libogc.devkitpro.org/gx_8h.html#a5164fc6aa2a678d792af80d94bfa1ec2) for more.
-    pub fn set_array(attr: u32, ptr: *mut c_void, stride: u8) {
-        unsafe { ogc_sys::GX_SetArray(attr, ptr, stride) }
+    pub fn set_array(attr: VtxAttr, ptr: *mut c_void, stride: u8) {
+        unsafe { ogc_sys::GX_SetArray(attr as _, ptr, stride) }
     }
 
-    /// Begins drawing of a graphics primitive.
+    /// Begins drawing of a graphics primitive using a format previously loaded with `VertexFormat::apply`.
+    /// The returned builder ends the primitive when dropped.
     /// See [GX_Begin](https://libogc.devkitpro.org/gx_8h.html#ac1e1239130a33d9fae1352aee8d2cab9) for more.
-    pub fn begin(primitive: u8, vtxfmt: u8, vtxcnt: u16) {
-        unsafe { ogc_sys::GX_Begin(primitive, vtxfmt, vtxcnt) }
+    pub fn begin(primitive: Primitive, format: &VertexFormat, vtxcnt: u16) -> PrimitiveBuilder<'_> {
+        assert!(
+            !format.attributes().is_empty(),
+            "Vertex format {:?} has no attributes",
+            format.vtxfmt()
+        );
+        let (primitives, vertices) = if RECORDING.load(Ordering::Relaxed) {
+            (&RECORDED_PRIMITIVES, &RECORDED_VERTICES)
+        } else {
+            (&PRIMITIVE_COUNT, &VERTEX_COUNT)
+        };
+        primitives.fetch_add(1, Ordering::Relaxed);
+        vertices.fetch_add(vtxcnt as u32, Ordering::Relaxed);
+        unsafe { ogc_sys::GX_Begin(primitive as _, format.vtxfmt() as _, vtxcnt) }
+        PrimitiveBuilder {
+            format,
+            remaining: vtxcnt,
+            next: 0,
+        }
     }
 
     /// Sets the parameters for the alpha compare function which uses the alpha output from the last active TEV stage.
     /// See [Gx_SetAlphaCompare](https://libogc.devkitpro.org/gx_8h.html#a23ac269062a1b2c2efc8ad5aae24b26a) for more.
-    pub fn set_alpha_compare(comp0: u8, ref0: u8, aop: u8, comp1: u8, ref1: u8) {
-        unsafe { ogc_sys::GX_SetAlphaCompare(comp0, ref0, aop, comp1, ref1) }
+    pub fn set_alpha_compare(comp0: CompareFn, ref0: u8, aop: AlphaOp, comp1: CompareFn, ref1: u8) {
+        unsafe { ogc_sys::GX_SetAlphaCompare(comp0 as _, ref0, aop as _, comp1 as _, ref1) }
     }
 
     /// Sets the parameters for the alpha compare function which uses the alpha output from the last active TEV stage.
     /// See [GX_SetClipMode](https://libogc.devkitpro.org/gx_8h.html#a3d348d7af8ded25b57352e956f43d974) for more.
-    pub fn set_clip_mode(mode: u8) {
-        unsafe { ogc_sys::GX_SetClipMode(mode) }
+    pub fn set_clip_mode(mode: ClipMode) {
+        unsafe { ogc_sys::GX_SetClipMode(mode as _) }
     }
 
     /// Allows the CPU to write color directly to the Embedded Frame Buffer (EFB) at position x, y.
@@ -269,35 +889,393 @@ impl Gx {
         }
     }
 
-    pub fn position_3f32(x: f32, y: f32, z: f32) {
-        unsafe {
-            ogc_sys::GX_Position3f32(x, y, z);
+    pub fn flush() {
+        unsafe { ogc_sys::GX_Flush() }
+    }
+
+    /// Returns the number of primitives and vertices started with `Gx::begin` since the
+    /// last call, and resets both counters. Primitives recorded into a `DisplayList` are
+    /// counted whenever it is called instead.
+    pub fn take_draw_counts() -> (u32, u32) {
+        let primitives = PRIMITIVE_COUNT.swap(0, Ordering::Relaxed);
+        let vertices = VERTEX_COUNT.swap(0, Ordering::Relaxed);
+        (primitives, vertices)
+    }
+
+    /// Redirects all following GX commands into `list` instead of the FIFO.
+    /// Prefer `DisplayList::record`, which pairs this call with `Gx::end_disp_list`.
+    /// See [GX_BeginDispList](https://libogc.devkitpro.org/gx_8h.html#a0b7122421171545256ccb2992dccc546) for more.
+    pub fn begin_disp_list(list: *mut c_void, size: u32) {
+        assert_eq!(
+            0,
+            list as usize % 32,
+            "Display list must be 32-byte aligned"
+        );
+        assert_eq!(0, size % 32, "Display list size must be a multiple of 32");
+        RECORDING.store(true, Ordering::Relaxed);
+        RECORDED_PRIMITIVES.store(0, Ordering::Relaxed);
+        RECORDED_VERTICES.store(0, Ordering::Relaxed);
+        unsafe { ogc_sys::GX_BeginDispList(list, size) }
+    }
+
+    /// Ends recording of a display list and returns its size in bytes, or 0 if it overflowed.
+    /// See [GX_EndDispList](https://libogc.devkitpro.org/gx_8h.html#ad7103a02cdffe078062879185a094d5f) for more.
+    pub fn end_disp_list() -> u32 {
+        RECORDING.store(false, Ordering::Relaxed);
+        unsafe { ogc_sys::GX_EndDispList() }
+    }
+
+    /// Makes the graphics processor execute a previously recorded display list.
+    /// See [GX_CallDispList](https://libogc.devkitpro.org/gx_8h.html#a20cac24818fa79957c4c8616ecc87cbe) for more.
+    pub fn call_disp_list(list: *const c_void, nbytes: u32) {
+        assert_eq!(
+            0,
+            list as usize % 32,
+            "Display list must be 32-byte aligned"
+        );
+        assert_eq!(0, nbytes % 32, "Display list size must be a multiple of 32");
+        unsafe { ogc_sys::GX_CallDispList(list as *mut _, nbytes) }
+    }
+}
+
+/// A recorded stream of GX commands that can be replayed with a single call.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut list = DisplayList::new(4096);
+/// list.record(|| {
+///     let mut point = Gx::begin(Primitive::Points, &format, 1);
+///     point.position_3f32(10.0, 10.0, 0.0);
+///     point.color_1u32(0xFFFFFFFF);
+/// })?;
+/// list.call();
+/// ```
+pub struct DisplayList {
+    buffer: *mut c_void,
+    capacity: u32,
+    size: u32,
+    /// Primitives and vertices recorded, counted by `Gx::take_draw_counts` on every call.
+    primitives: u32,
+    vertices: u32,
+}
+
+impl DisplayList {
+    /// Allocates a 32-byte aligned buffer able to hold `capacity` bytes of commands.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = (capacity + 31) & !31;
+        let buffer = unsafe { libc::memalign(32, capacity) };
+        assert!(!buffer.is_null(), "Failed to allocate display list");
+
+        Self {
+            buffer,
+            capacity: capacity as u32,
+            size: 0,
+            primitives: 0,
+            vertices: 0,
         }
     }
 
-    pub fn color_1u32(clr: u32) {
-        unsafe {
-            ogc_sys::GX_Color1u32(clr);
+    /// Records every GX command issued by `commands`, replacing the previous contents.
+    /// Fails if the commands do not fit in the buffer.
+    pub fn record<F>(&mut self, commands: F) -> Result<()>
+    where
+        F: FnOnce(),
+    {
+        unsafe { ogc_sys::DCInvalidateRange(self.buffer, self.capacity) };
+        let recording = Recording::begin(self.buffer, self.capacity);
+        commands();
+        self.size = recording.end();
+        self.primitives = RECORDED_PRIMITIVES.load(Ordering::Relaxed);
+        self.vertices = RECORDED_VERTICES.load(Ordering::Relaxed);
+
+        if self.size == 0 {
+            Err(OgcError::Gx(format!(
+                "DisplayList::record() overflowed {} bytes",
+                self.capacity
+            )))
+        } else {
+            Ok(())
         }
     }
 
-    pub fn flush() {
-        unsafe { ogc_sys::GX_Flush() }
+    /// Executes the recorded commands. Does nothing if nothing was recorded.
+    pub fn call(&self) {
+        if self.size > 0 {
+            PRIMITIVE_COUNT.fetch_add(self.primitives, Ordering::Relaxed);
+            VERTEX_COUNT.fetch_add(self.vertices, Ordering::Relaxed);
+            Gx::call_disp_list(self.buffer, self.size);
+        }
     }
 
-    pub fn end() {
-        unsafe { ogc_sys::GX_End() }
+    /// Size of the recorded commands, in bytes.
+    pub fn size(&self) -> u32 {
+        self.size
     }
 
-    pub fn position1x8(index: u8) {
+    /// Size of the underlying buffer, in bytes.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+}
+
+impl Drop for DisplayList {
+    /// Waits for the graphics processor to finish before freeing the buffer,
+    /// since a list passed to `call` is read long after the call returns.
+    fn drop(&mut self) {
+        if self.size > 0 {
+            Gx::draw_done();
+        }
+        unsafe { libc::free(self.buffer) }
+    }
+}
+
+/// Display list being recorded by `DisplayList::record`.
+/// Ends the list when dropped, so a panic in the commands does not leave GX recording.
+struct Recording(());
+
+impl Recording {
+    fn begin(list: *mut c_void, size: u32) -> Self {
+        Gx::begin_disp_list(list, size);
+        Self(())
+    }
+
+    /// Ends the list and returns its size in bytes, or 0 if it overflowed.
+    fn end(self) -> u32 {
+        core::mem::forget(self);
+        Gx::end_disp_list()
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        Gx::end_disp_list();
+    }
+}
+
+/// Writes vertices of a primitive started with `Gx::begin` to the FIFO.
+///
+/// In debug builds every write is checked against the vertex format passed to `Gx::begin`:
+/// attributes must be sent in descriptor order with a matching type, and exactly the declared
+/// number of vertices must be written before the builder is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut quad = Gx::begin(Primitive::Quads, &format, 4);
+/// for (x, y) in [(0.0, 0.0), (50.0, 0.0), (50.0, 50.0), (0.0, 50.0)] {
+///     quad.position_3f32(x, y, 0.0);
+///     quad.color_1u32(0xFFFFFFFF);
+/// }
+/// ```
+#[must_use = "the primitive is ended as soon as the builder is dropped"]
+pub struct PrimitiveBuilder<'a> {
+    format: &'a VertexFormat,
+    remaining: u16,
+    next: usize,
+}
+
+impl PrimitiveBuilder<'_> {
+    /// Number of vertices that still have to be written.
+    pub fn remaining(&self) -> u16 {
+        self.remaining
+    }
+
+    fn expect<F>(&mut self, name: &str, valid: F)
+    where
+        F: Fn(&VertexAttribute) -> bool,
+    {
+        if cfg!(debug_assertions) {
+            assert!(
+                self.remaining > 0,
+                "{} written past the declared vertex count",
+                name
+            );
+            let attributes = self.format.attributes();
+            let expected = &attributes[self.next];
+            assert!(
+                valid(expected),
+                "{} written where {:?} was expected",
+                name,
+                expected
+            );
+            self.next += 1;
+            if self.next == attributes.len() {
+                self.next = 0;
+                self.remaining -= 1;
+            }
+        }
+    }
+
+    pub fn matrix_index1x8(&mut self, index: u8) {
+        self.expect("Matrix index", |a| a.attr < VtxAttr::Position);
+        unsafe { ogc_sys::GX_MatrixIndex1x8(index) }
+    }
+
+    pub fn position_3f32(&mut self, x: f32, y: f32, z: f32) {
+        self.expect("Position3f32", |a| {
+            a.attr_type == AttrType::Direct
+                && matches!(
+                    a.format,
+                    Some(AttrFormat::Position(
+                        PositionFormat::Xyz,
+                        ComponentFormat::F32,
+                        _
+                    ))
+                )
+        });
+        unsafe { ogc_sys::GX_Position3f32(x, y, z) }
+    }
+
+    pub fn position_2f32(&mut self, x: f32, y: f32) {
+        self.expect("Position2f32", |a| {
+            a.attr_type == AttrType::Direct
+                && matches!(
+                    a.format,
+                    Some(AttrFormat::Position(
+                        PositionFormat::Xy,
+                        ComponentFormat::F32,
+                        _
+                    ))
+                )
+        });
+        unsafe { ogc_sys::GX_Position2f32(x, y) }
+    }
+
+    pub fn position_3s16(&mut self, x: i16, y: i16, z: i16) {
+        self.expect("Position3s16", |a| {
+            a.attr_type == AttrType::Direct
+                && matches!(
+                    a.format,
+                    Some(AttrFormat::Position(
+                        PositionFormat::Xyz,
+                        ComponentFormat::S16,
+                        _
+                    ))
+                )
+        });
+        unsafe { ogc_sys::GX_Position3s16(x, y, z) }
+    }
+
+    pub fn position_2s16(&mut self, x: i16, y: i16) {
+        self.expect("Position2s16", |a| {
+            a.attr_type == AttrType::Direct
+                && matches!(
+                    a.format,
+                    Some(AttrFormat::Position(
+                        PositionFormat::Xy,
+                        ComponentFormat::S16,
+                        _
+                    ))
+                )
+        });
+        unsafe { ogc_sys::GX_Position2s16(x, y) }
+    }
+
+    pub fn position1x8(&mut self, index: u8) {
+        self.expect("Position1x8", |a| {
+            a.attr == VtxAttr::Position && a.attr_type == AttrType::Index8
+        });
         unsafe { ogc_sys::GX_Position1x8(index) }
     }
 
-    pub fn color1x8(index: u8) {
+    pub fn position1x16(&mut self, index: u16) {
+        self.expect("Position1x16", |a| {
+            a.attr == VtxAttr::Position && a.attr_type == AttrType::Index16
+        });
+        unsafe { ogc_sys::GX_Position1x16(index) }
+    }
+
+    pub fn normal_3f32(&mut self, nx: f32, ny: f32, nz: f32) {
+        self.expect("Normal3f32", |a| {
+            a.attr_type == AttrType::Direct
+                && a.format == Some(AttrFormat::Normal(NormalFormat::Xyz, ComponentFormat::F32))
+        });
+        unsafe { ogc_sys::GX_Normal3f32(nx, ny, nz) }
+    }
+
+    pub fn color_1u32(&mut self, clr: u32) {
+        self.expect("Color1u32", |a| {
+            a.attr_type == AttrType::Direct
+                && matches!(
+                    a.format,
+                    Some(AttrFormat::Color(ColorFormat::Rgba8 | ColorFormat::Rgbx8))
+                )
+        });
+        unsafe { ogc_sys::GX_Color1u32(clr) }
+    }
+
+    pub fn color_4u8(&mut self, r: u8, g: u8, b: u8, a: u8) {
+        self.expect("Color4u8", |attr| {
+            attr.attr_type == AttrType::Direct
+                && matches!(
+                    attr.format,
+                    Some(AttrFormat::Color(ColorFormat::Rgba8 | ColorFormat::Rgbx8))
+                )
+        });
+        unsafe { ogc_sys::GX_Color4u8(r, g, b, a) }
+    }
+
+    pub fn color1x8(&mut self, index: u8) {
+        self.expect("Color1x8", |a| {
+            matches!(a.attr, VtxAttr::Color0 | VtxAttr::Color1) && a.attr_type == AttrType::Index8
+        });
         unsafe { ogc_sys::GX_Color1x8(index) }
     }
 
-    pub fn tex_coord_2f32(s: f32, t: f32) {
+    pub fn color1x16(&mut self, index: u16) {
+        self.expect("Color1x16", |a| {
+            matches!(a.attr, VtxAttr::Color0 | VtxAttr::Color1) && a.attr_type == AttrType::Index16
+        });
+        unsafe { ogc_sys::GX_Color1x16(index) }
+    }
+
+    pub fn tex_coord_2f32(&mut self, s: f32, t: f32) {
+        self.expect("TexCoord2f32", |a| {
+            a.attr_type == AttrType::Direct
+                && matches!(
+                    a.format,
+                    Some(AttrFormat::TexCoord(TexFormat::St, ComponentFormat::F32, _))
+                )
+        });
         unsafe { ogc_sys::GX_TexCoord2f32(s, t) }
     }
+
+    pub fn tex_coord_2s16(&mut self, s: i16, t: i16) {
+        self.expect("TexCoord2s16", |a| {
+            a.attr_type == AttrType::Direct
+                && matches!(
+                    a.format,
+                    Some(AttrFormat::TexCoord(TexFormat::St, ComponentFormat::S16, _))
+                )
+        });
+        unsafe { ogc_sys::GX_TexCoord2s16(s, t) }
+    }
+
+    pub fn tex_coord1x8(&mut self, index: u8) {
+        self.expect("TexCoord1x8", |a| {
+            a.attr >= VtxAttr::Tex0 && a.attr_type == AttrType::Index8
+        });
+        unsafe { ogc_sys::GX_TexCoord1x8(index) }
+    }
+
+    pub fn tex_coord1x16(&mut self, index: u16) {
+        self.expect("TexCoord1x16", |a| {
+            a.attr >= VtxAttr::Tex0 && a.attr_type == AttrType::Index16
+        });
+        unsafe { ogc_sys::GX_TexCoord1x16(index) }
+    }
+}
+
+impl Drop for PrimitiveBuilder<'_> {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) {
+            assert!(
+                self.remaining == 0 && self.next == 0,
+                "Primitive ended with {} vertices left to write",
+                self.remaining
+            );
+        }
+        unsafe { ogc_sys::GX_End() }
+    }
 }
@@ -0,0 +1,106 @@
+//! The ``io`` module of ``ogc-rs``.
+//!
+//! This module provides minimal byte source traits, standing in for ``std::io`` which is
+//! not available on the Wii. The decoders of ``mad`` and ``vorbis`` read through them.
+
+use crate::{fs::File, OgcError, Result};
+use alloc::format;
+
+/// A source of bytes.
+pub trait Read {
+    /// Reads up to `buffer.len()` bytes and returns how many were read. Returns 0 at the end.
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize>;
+}
+
+/// A source of bytes with a known length and a movable position.
+pub trait Seek {
+    /// Moves the position to `offset` bytes from the start.
+    fn seek(&mut self, offset: u32) -> Result<()>;
+
+    /// Current position, in bytes from the start.
+    fn position(&self) -> u32;
+
+    /// Length, in bytes.
+    fn len(&mut self) -> Result<u32>;
+}
+
+impl Read for File {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        File::read(self, buffer)
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, offset: u32) -> Result<()> {
+        File::seek(self, offset)
+    }
+
+    fn position(&self) -> u32 {
+        File::position(self)
+    }
+
+    fn len(&mut self) -> Result<u32> {
+        File::len(self)
+    }
+}
+
+/// Reads from bytes in memory, such as a `Vec<u8>` or an `include_bytes!`.
+///
+/// # Examples
+///
+/// ```rust
+/// const SONG: &[u8] = include_bytes!("song.mp3");
+/// let mut decoder = Mp3Decoder::new(Cursor::new(SONG));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Cursor<T> {
+    data: T,
+    position: usize,
+}
+
+impl<T: AsRef<[u8]>> Cursor<T> {
+    pub fn new(data: T) -> Self {
+        Self { data, position: 0 }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.data
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+}
+
+impl<T: AsRef<[u8]>> Read for Cursor<T> {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let data = self.data.as_ref();
+        let start = self.position.min(data.len());
+        let read = buffer.len().min(data.len() - start);
+        buffer[..read].copy_from_slice(&data[start..start + read]);
+        self.position = start + read;
+        Ok(read)
+    }
+}
+
+impl<T: AsRef<[u8]>> Seek for Cursor<T> {
+    fn seek(&mut self, offset: u32) -> Result<()> {
+        if offset as usize > self.data.as_ref().len() {
+            Err(OgcError::Fs(format!(
+                "Cursor::seek() failed to seek to {}",
+                offset
+            )))
+        } else {
+            self.position = offset as usize;
+            Ok(())
+        }
+    }
+
+    fn position(&self) -> u32 {
+        self.position as u32
+    }
+
+    fn len(&mut self) -> Result<u32> {
+        Ok(self.data.as_ref().len() as u32)
+    }
+}
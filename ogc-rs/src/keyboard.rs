@@ -0,0 +1,243 @@
+//! The ``keyboard`` module of ``ogc-rs``.
+//!
+//! This module implements a safe wrapper around the USB keyboard functions found in ``wiikeyboard/keyboard.h``.
+//!
+//! Note: In order to use this library, you need to have the following linker flags
+//! in your `powerpc-unknown-eabi.json` ("post-link-args" goes after "pre-link-args"):
+//!
+//! ```json
+//! "post-link-args": {
+//!     "gcc": [
+//!         "-lwiikeyboard",
+//!         "-logc"
+//!     ]
+//! },
+//! ```
+
+use crate::{bitflags, OgcError, Result};
+use alloc::format;
+use core::mem;
+
+/// Represents the keyboard service. No key events are reported until an instance of
+/// this struct is created. This service can only be created once!
+///
+/// Keyboards are plugged into the USB ports and can be connected and removed at any time.
+/// libogc translates key presses with the keyboard layout set in the console settings.
+///
+/// # Minimal Example
+///
+/// ```rust
+/// let _keyboard = Keyboard::init().unwrap();
+///
+/// while let Some(event) = Keyboard::event() {
+///     if let Some(c) = event.char() {
+///         print!("{}", c);
+///     }
+/// }
+/// ```
+pub struct Keyboard;
+
+/// Kind of a keyboard event.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum KeyEventKind {
+    Connected,
+    Disconnected,
+    Pressed,
+    Released,
+}
+
+/// A physical key, as the USB HID usage id it reports. Independent of the keyboard layout,
+/// so `Key::W` is the key in that position on a US keyboard, which is `Z` on a French one.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Key(pub u8);
+
+impl Key {
+    pub const A: Key = Key(0x04);
+    pub const B: Key = Key(0x05);
+    pub const C: Key = Key(0x06);
+    pub const D: Key = Key(0x07);
+    pub const E: Key = Key(0x08);
+    pub const F: Key = Key(0x09);
+    pub const G: Key = Key(0x0A);
+    pub const H: Key = Key(0x0B);
+    pub const I: Key = Key(0x0C);
+    pub const J: Key = Key(0x0D);
+    pub const K: Key = Key(0x0E);
+    pub const L: Key = Key(0x0F);
+    pub const M: Key = Key(0x10);
+    pub const N: Key = Key(0x11);
+    pub const O: Key = Key(0x12);
+    pub const P: Key = Key(0x13);
+    pub const Q: Key = Key(0x14);
+    pub const R: Key = Key(0x15);
+    pub const S: Key = Key(0x16);
+    pub const T: Key = Key(0x17);
+    pub const U: Key = Key(0x18);
+    pub const V: Key = Key(0x19);
+    pub const W: Key = Key(0x1A);
+    pub const X: Key = Key(0x1B);
+    pub const Y: Key = Key(0x1C);
+    pub const Z: Key = Key(0x1D);
+    pub const NUM_1: Key = Key(0x1E);
+    pub const NUM_2: Key = Key(0x1F);
+    pub const NUM_3: Key = Key(0x20);
+    pub const NUM_4: Key = Key(0x21);
+    pub const NUM_5: Key = Key(0x22);
+    pub const NUM_6: Key = Key(0x23);
+    pub const NUM_7: Key = Key(0x24);
+    pub const NUM_8: Key = Key(0x25);
+    pub const NUM_9: Key = Key(0x26);
+    pub const NUM_0: Key = Key(0x27);
+    pub const ENTER: Key = Key(0x28);
+    pub const ESCAPE: Key = Key(0x29);
+    pub const BACKSPACE: Key = Key(0x2A);
+    pub const TAB: Key = Key(0x2B);
+    pub const SPACE: Key = Key(0x2C);
+    pub const F1: Key = Key(0x3A);
+    pub const F2: Key = Key(0x3B);
+    pub const F3: Key = Key(0x3C);
+    pub const F4: Key = Key(0x3D);
+    pub const F5: Key = Key(0x3E);
+    pub const F6: Key = Key(0x3F);
+    pub const F7: Key = Key(0x40);
+    pub const F8: Key = Key(0x41);
+    pub const F9: Key = Key(0x42);
+    pub const F10: Key = Key(0x43);
+    pub const F11: Key = Key(0x44);
+    pub const F12: Key = Key(0x45);
+    pub const INSERT: Key = Key(0x49);
+    pub const HOME: Key = Key(0x4A);
+    pub const PAGE_UP: Key = Key(0x4B);
+    pub const DELETE: Key = Key(0x4C);
+    pub const END: Key = Key(0x4D);
+    pub const PAGE_DOWN: Key = Key(0x4E);
+    pub const RIGHT: Key = Key(0x4F);
+    pub const LEFT: Key = Key(0x50);
+    pub const DOWN: Key = Key(0x51);
+    pub const UP: Key = Key(0x52);
+    pub const LEFT_CTRL: Key = Key(0xE0);
+    pub const LEFT_SHIFT: Key = Key(0xE1);
+    pub const LEFT_ALT: Key = Key(0xE2);
+    pub const RIGHT_CTRL: Key = Key(0xE4);
+    pub const RIGHT_SHIFT: Key = Key(0xE5);
+    pub const RIGHT_ALT: Key = Key(0xE6);
+}
+
+bitflags! {
+    /// Modifier keys and locks active during a keyboard event.
+    pub struct Modifiers: u16 {
+        const SHIFT_L    = ogc_sys::MOD_SHIFT_L as u16;
+        const SHIFT_R    = ogc_sys::MOD_SHIFT_R as u16;
+        const SHIFT_LOCK = ogc_sys::MOD_SHIFTLOCK as u16;
+        const CAPS_LOCK  = ogc_sys::MOD_CAPSLOCK as u16;
+        const CONTROL_L  = ogc_sys::MOD_CONTROL_L as u16;
+        const CONTROL_R  = ogc_sys::MOD_CONTROL_R as u16;
+        const META_L     = ogc_sys::MOD_META_L as u16;
+        const META_R     = ogc_sys::MOD_META_R as u16;
+        const MODE_SHIFT = ogc_sys::MOD_MODESHIFT as u16;
+        const NUM_LOCK   = ogc_sys::MOD_NUMLOCK as u16;
+    }
+}
+
+impl Modifiers {
+    /// Returns true if either shift key is held.
+    pub fn shift(&self) -> bool {
+        self.intersects(Modifiers::SHIFT_L | Modifiers::SHIFT_R)
+    }
+
+    /// Returns true if either control key is held.
+    pub fn control(&self) -> bool {
+        self.intersects(Modifiers::CONTROL_L | Modifiers::CONTROL_R)
+    }
+
+    /// Returns true if either alt key is held.
+    pub fn alt(&self) -> bool {
+        self.intersects(Modifiers::META_L | Modifiers::META_R)
+    }
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Modifiers::empty()
+    }
+}
+
+/// An event of the keyboard service, see `Keyboard::event`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct KeyboardEvent {
+    pub kind: KeyEventKind,
+    pub key: Key,
+    pub modifiers: Modifiers,
+    /// Keysym the key produces with the current layout and modifiers. Printable ones
+    /// are Unicode code points, see `KeyboardEvent::char`.
+    pub symbol: u16,
+}
+
+impl KeyboardEvent {
+    /// Character typed by this event, if it is a press of a printable key.
+    /// Dead keys, control characters and function keys yield nothing.
+    pub fn char(&self) -> Option<char> {
+        let symbol = self.symbol as u32;
+        let printable = match symbol {
+            0x0300..=0x036F => false,
+            0xE000..=0xF7FF => false,
+            _ => symbol >= 0x20 && symbol != 0x7F,
+        };
+
+        if self.kind == KeyEventKind::Pressed && printable {
+            core::char::from_u32(symbol)
+        } else {
+            None
+        }
+    }
+}
+
+impl Keyboard {
+    /// Initializes the keyboard service, which starts polling the USB ports for keyboards.
+    pub fn init() -> Result<Self> {
+        let err = unsafe { ogc_sys::KEYBOARD_Init(None) };
+        if err < 0 {
+            Err(OgcError::Usb(format!(
+                "Keyboard::init() failed with error {}",
+                err
+            )))
+        } else {
+            Ok(Self)
+        }
+    }
+
+    /// Stops the keyboard service.
+    pub fn deinit() {
+        unsafe { ogc_sys::KEYBOARD_Deinit() };
+    }
+
+    /// Takes the oldest pending event, if there is one.
+    pub fn event() -> Option<KeyboardEvent> {
+        let event = unsafe {
+            let mut event: ogc_sys::keyboard_event = mem::zeroed();
+            if ogc_sys::KEYBOARD_GetEvent(&mut event) <= 0 {
+                return None;
+            }
+            event
+        };
+
+        let kind = match event.type_ {
+            ogc_sys::KEYBOARD_CONNECTED => KeyEventKind::Connected,
+            ogc_sys::KEYBOARD_DISCONNECTED => KeyEventKind::Disconnected,
+            ogc_sys::KEYBOARD_PRESSED => KeyEventKind::Pressed,
+            _ => KeyEventKind::Released,
+        };
+
+        Some(KeyboardEvent {
+            kind,
+            key: Key(event.keycode),
+            modifiers: Modifiers::from_bits_truncate(event.modifiers),
+            symbol: event.symbol,
+        })
+    }
+
+    /// Discards every pending event.
+    pub fn flush_events() {
+        unsafe { ogc_sys::KEYBOARD_FlushEvents() };
+    }
+}
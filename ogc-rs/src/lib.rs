@@ -10,6 +10,8 @@
 //! * ``system``: Provides OS functions for the Wii.
 //! * ``console``: Provides console functions for the Wii.
 //! * ``input``: Provides an interface for reading input from devices on the Wii.
+//! * ``keyboard`` and ``mouse``: Provide events of USB keyboards and mice.
+//! * ``mad`` and ``vorbis``: Provide frame by frame MP3 and Ogg Vorbis decoding.
 //! * ``video``: Provides functions for video output on the Wii.
 //! * ``gx``: Provides an opengl-like interface for rendering on the Wii.
 //!
@@ -44,7 +46,16 @@ pub mod gu;
 pub mod pad;
 
 // WPad Implementation
-// mod wpad;
+pub mod wpad;
+
+// USB Keyboard Implementation
+pub mod keyboard;
+
+// USB Mouse Implementation
+pub mod mouse;
+
+// Filesystem Implementation
+pub mod fs;
 
 // Console Implementation
 pub mod console;
@@ -71,6 +82,16 @@ pub mod asnd;
 // Mp3player Implementation
 pub mod mp3player;
 
+// Byte Sources
+pub mod io;
+
+// Mad Implementation
+pub mod mad;
+
+// Vorbis Implementation
+#[cfg(feature = "vorbis")]
+pub mod vorbis;
+
 #[cfg(feature = "ffi")]
 pub extern crate ogc_sys as ffi;
 
@@ -86,13 +107,20 @@ pub mod prelude {
     pub use crate::audio::*;
     pub use crate::console::*;
     pub use crate::debug::*;
+    pub use crate::fs::*;
     pub use crate::gu::*;
     pub use crate::gx::*;
+    pub use crate::io::*;
+    pub use crate::keyboard::*;
+    pub use crate::mad::*;
+    pub use crate::mouse::*;
     pub use crate::mp3player::*;
     pub use crate::pad::*;
     pub use crate::system::*;
     pub use crate::video::*;
-    // pub use crate::wpad::*;
+    #[cfg(feature = "vorbis")]
+    pub use crate::vorbis::*;
+    pub use crate::wpad::*;
     pub use crate::{print, println};
 
     // Global Allocator
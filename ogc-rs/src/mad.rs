@@ -0,0 +1,214 @@
+//! The ``mad`` module of ``ogc-rs``.
+//!
+//! This module implements a safe wrapper around the MP3 decoder found in ``mad.h``, for
+//! decoding MP3s frame by frame instead of playing them whole with ``mp3player``.
+//!
+//! Note: In order to use this library, you need to have the following linker flags
+//! in your `powerpc-unknown-eabi.json` ("post-link-args" goes after "pre-link-args"):
+//!
+//! ```json
+//! "post-link-args": {
+//!     "gcc": [
+//!         "-lmad"
+//!     ]
+//! },
+//! ```
+
+use crate::{io::Read, OgcError, Result};
+use alloc::{
+    alloc::{alloc_zeroed, handle_alloc_error, Layout},
+    boxed::Box,
+    format, vec,
+    vec::Vec,
+};
+
+const INPUT_SIZE: usize = 16 * 1024;
+const MAD_F_FRACBITS: i32 = 28;
+const MAD_F_ONE: i32 = 1 << MAD_F_FRACBITS;
+
+/// The mad structs are several kilobytes large, so they are allocated zeroed on
+/// the heap instead of being moved there from the stack.
+fn boxed_zeroed<T>() -> Box<T> {
+    let layout = Layout::new::<T>();
+    unsafe {
+        let ptr = alloc_zeroed(layout) as *mut T;
+        if ptr.is_null() {
+            handle_alloc_error(layout);
+        }
+        Box::from_raw(ptr)
+    }
+}
+
+/// Converts a sample from mad's fixed point to 16-bit, rounding and clipping it.
+fn to_i16(sample: i32) -> i16 {
+    let sample = sample.saturating_add(1 << (MAD_F_FRACBITS - 16));
+    let sample = sample.max(-MAD_F_ONE).min(MAD_F_ONE - 1);
+    (sample >> (MAD_F_FRACBITS + 1 - 16)) as i16
+}
+
+/// Properties of a decoded MP3 frame, see `Mp3Decoder::next_frame`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Mp3Frame {
+    pub channels: u16,
+    /// Sample rate, in Hz.
+    pub rate: u32,
+    /// Bit rate of the frame, in bits per second.
+    pub bitrate: u32,
+    /// Number of samples per channel.
+    pub frames: usize,
+}
+
+/// Decodes an MP3 frame by frame, reading the file as it goes.
+///
+/// # Minimal Example
+///
+/// ```rust
+/// let file = File::open("sd:/song.mp3")?;
+/// let mut decoder = Mp3Decoder::new(file);
+/// let mut samples = Vec::new();
+///
+/// while let Some(frame) = decoder.next_frame(&mut samples)? {
+///     // `samples` holds `frame.frames * frame.channels` interleaved samples
+///     samples.clear();
+/// }
+/// ```
+pub struct Mp3Decoder<R> {
+    reader: R,
+    stream: Box<ogc_sys::mad_stream>,
+    frame: Box<ogc_sys::mad_frame>,
+    synth: Box<ogc_sys::mad_synth>,
+    input: Vec<u8>,
+    end_of_input: bool,
+}
+
+impl<R: Read> Mp3Decoder<R> {
+    pub fn new(reader: R) -> Self {
+        let mut decoder = Self {
+            reader,
+            stream: boxed_zeroed(),
+            frame: boxed_zeroed(),
+            synth: boxed_zeroed(),
+            input: vec![0; INPUT_SIZE + ogc_sys::MAD_BUFFER_GUARD as usize],
+            end_of_input: false,
+        };
+
+        unsafe {
+            ogc_sys::mad_stream_init(&mut *decoder.stream);
+            ogc_sys::mad_frame_init(&mut *decoder.frame);
+            ogc_sys::mad_synth_init(&mut *decoder.synth);
+        }
+        decoder
+    }
+
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// The reader, for instance to seek it. Call `Mp3Decoder::reset` afterwards.
+    pub fn reader_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Drops the buffered input, so decoding starts over at the position of the reader.
+    pub fn reset(&mut self) {
+        unsafe {
+            ogc_sys::mad_frame_finish(&mut *self.frame);
+            ogc_sys::mad_stream_finish(&mut *self.stream);
+            ogc_sys::mad_stream_init(&mut *self.stream);
+            ogc_sys::mad_frame_init(&mut *self.frame);
+            ogc_sys::mad_synth_init(&mut *self.synth);
+        }
+        self.end_of_input = false;
+    }
+
+    /// Moves the unconsumed input to the front of the buffer and fills the rest from the reader.
+    /// Returns false once the reader is exhausted.
+    fn refill(&mut self) -> Result<bool> {
+        if self.end_of_input {
+            return Ok(false);
+        }
+
+        let remaining = if self.stream.next_frame.is_null() {
+            0
+        } else {
+            let start = self.stream.next_frame as usize - self.input.as_ptr() as usize;
+            let end = self.stream.bufend as usize - self.input.as_ptr() as usize;
+            self.input.copy_within(start..end, 0);
+            end - start
+        };
+
+        let mut length = remaining;
+        while length < INPUT_SIZE {
+            match self.reader.read(&mut self.input[length..INPUT_SIZE])? {
+                0 => break,
+                read => length += read,
+            }
+        }
+
+        if length < INPUT_SIZE {
+            // mad needs zeroed guard bytes after the last frame to decode it.
+            let guard = ogc_sys::MAD_BUFFER_GUARD as usize;
+            self.input[length..length + guard].fill(0);
+            length += guard;
+            self.end_of_input = true;
+        }
+
+        unsafe {
+            ogc_sys::mad_stream_buffer(&mut *self.stream, self.input.as_ptr(), length as _);
+        }
+        self.stream.error = 0;
+        Ok(true)
+    }
+
+    /// Decodes the next frame and appends its samples to `samples`, interleaved if stereo.
+    /// Returns `None` at the end of the file.
+    pub fn next_frame(&mut self, samples: &mut Vec<i16>) -> Result<Option<Mp3Frame>> {
+        loop {
+            if self.stream.buffer.is_null() || self.stream.error == ogc_sys::MAD_ERROR_BUFLEN {
+                if !self.refill()? {
+                    return Ok(None);
+                }
+            }
+
+            if unsafe { ogc_sys::mad_frame_decode(&mut *self.frame, &mut *self.stream) } != 0 {
+                let error = self.stream.error;
+                if error == ogc_sys::MAD_ERROR_BUFLEN || error & 0xFF00 != 0 {
+                    // Needs more input, or skips a damaged frame.
+                    continue;
+                }
+                return Err(OgcError::Audio(format!(
+                    "Mp3Decoder::next_frame() failed with error {:#06x}",
+                    error
+                )));
+            }
+
+            unsafe { ogc_sys::mad_synth_frame(&mut *self.synth, &*self.frame) };
+
+            let pcm = &self.synth.pcm;
+            let frames = pcm.length as usize;
+            let channels = pcm.channels as usize;
+            samples.reserve(frames * channels);
+            for i in 0..frames {
+                for channel in 0..channels {
+                    samples.push(to_i16(pcm.samples[channel][i]));
+                }
+            }
+
+            return Ok(Some(Mp3Frame {
+                channels: pcm.channels as u16,
+                rate: pcm.samplerate as u32,
+                bitrate: self.frame.header.bitrate as u32,
+                frames,
+            }));
+        }
+    }
+}
+
+impl<R> Drop for Mp3Decoder<R> {
+    fn drop(&mut self) {
+        unsafe {
+            ogc_sys::mad_frame_finish(&mut *self.frame);
+            ogc_sys::mad_stream_finish(&mut *self.stream);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! The ``mouse`` module of ``ogc-rs``.
+//!
+//! This module implements a safe wrapper around the USB mouse functions found in ``ogc/usbmouse.h``.
+
+use crate::{bitflags, OgcError, Result};
+use alloc::format;
+use core::mem;
+
+/// Represents the mouse service. No mouse events are reported until an instance of
+/// this struct is created. This service can only be created once!
+///
+/// # Minimal Example
+///
+/// ```rust
+/// let _mouse = Mouse::init().unwrap();
+///
+/// let (mut x, mut y) = (320, 264);
+/// while let Some(event) = Mouse::event() {
+///     x += event.dx;
+///     y += event.dy;
+/// }
+/// ```
+pub struct Mouse;
+
+bitflags! {
+    /// Buttons of a USB mouse.
+    pub struct MouseButtons: u8 {
+        const LEFT   = 0x01;
+        const RIGHT  = 0x02;
+        const MIDDLE = 0x04;
+    }
+}
+
+impl Default for MouseButtons {
+    fn default() -> Self {
+        MouseButtons::empty()
+    }
+}
+
+/// A report of the mouse, see `Mouse::event`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct MouseEvent {
+    /// Buttons held down.
+    pub buttons: MouseButtons,
+    /// Motion since the previous report, in mouse counts. Positive is right and down.
+    pub dx: i32,
+    pub dy: i32,
+    /// Wheel motion since the previous report. Positive is away from the user.
+    pub wheel: i32,
+}
+
+impl Mouse {
+    /// Initializes the mouse service, which starts polling the USB ports for a mouse.
+    pub fn init() -> Result<Self> {
+        let err = unsafe { ogc_sys::MOUSE_Init() };
+        if err < 0 {
+            Err(OgcError::Usb(format!(
+                "Mouse::init() failed with error {}",
+                err
+            )))
+        } else {
+            Ok(Self)
+        }
+    }
+
+    /// Stops the mouse service.
+    pub fn deinit() {
+        unsafe { ogc_sys::MOUSE_Deinit() };
+    }
+
+    pub fn is_connected() -> bool {
+        unsafe { ogc_sys::MOUSE_IsConnected() }
+    }
+
+    /// Takes the oldest pending report, if there is one.
+    pub fn event() -> Option<MouseEvent> {
+        let event = unsafe {
+            let mut event: ogc_sys::mouse_event = mem::zeroed();
+            if ogc_sys::MOUSE_GetEvent(&mut event) <= 0 {
+                return None;
+            }
+            event
+        };
+
+        Some(MouseEvent {
+            buttons: MouseButtons::from_bits_truncate(event.button),
+            dx: event.rx as i32,
+            dy: event.ry as i32,
+            wheel: event.rz as i32,
+        })
+    }
+
+    /// Discards every pending report.
+    pub fn flush_events() {
+        unsafe { ogc_sys::MOUSE_FlushEvents() };
+    }
+}
@@ -15,6 +15,11 @@
 //! },
 //! ```
 
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether `Mp3Player::init` was called. The player cannot be shut down.
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
 /// Represents the mp3player service.
 /// This service can only be created once!
 ///
@@ -31,9 +36,17 @@ pub struct Mp3Player;
 impl Mp3Player {
     pub fn init() {
         unsafe { ogc_sys::MP3Player_Init() };
+        INITIALIZED.store(true, Ordering::Release);
+    }
+
+    /// Returns true once `Mp3Player::init` was called, after which it plays through asnd.
+    pub fn is_initialized() -> bool {
+        INITIALIZED.load(Ordering::Acquire)
     }
 
-    pub fn play_buffer(sound_buffer: &[u8]) {
+    /// Plays an MP3 from memory. The decoder keeps reading `sound_buffer` while the
+    /// music plays, hence `'static`, such as an `include_bytes!` or a leaked `Vec`.
+    pub fn play_buffer(sound_buffer: &'static [u8]) {
         unsafe {
             ogc_sys::MP3Player_PlayBuffer(
                 sound_buffer.as_ptr() as *const _,
@@ -2,15 +2,31 @@
 //!
 //! This module implements a safe wrapper around the gamecube controller functions found in ``pad.h``.
 
+use crate::{bitflags, utils::replace_callback};
 use alloc::boxed::Box;
-use core::mem;
+use core::{
+    mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+type SamplingCallback = Box<dyn FnMut() + Send>;
+
+/// Address of the boxed callback the trampoline calls, 0 when there is none.
+static SAMPLING_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn sampling_trampoline() {
+    let callback = SAMPLING_CALLBACK.load(Ordering::Acquire) as *mut SamplingCallback;
+    if let Some(callback) = unsafe { callback.as_mut() } {
+        callback();
+    }
+}
 
 /// Represents the pad service. No gamecube controllers can be read until an instance of
 /// this struct is created. This service can only be created once!
 pub struct Pad;
 
 /// The controller to be read for the `pad` service.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Controller {
     One = 0,
     Two = 1,
@@ -18,37 +34,179 @@ pub enum Controller {
     Four = 3,
 }
 
+impl Controller {
+    /// Every controller, in port order.
+    pub const ALL: [Controller; 4] = [
+        Controller::One,
+        Controller::Two,
+        Controller::Three,
+        Controller::Four,
+    ];
+}
+
 impl PartialEq<Controller> for u16 {
     fn eq(&self, other: &Controller) -> bool {
         *self == *other as u16
     }
 }
 
-macro_rules! enumerate {
-    ($name:ident { $($variant:ident = $value:expr),* $(,)* }) => {
-        #[allow(non_snake_case)]
-        #[allow(non_upper_case_globals)]
-        pub mod $name {
-            $(pub const $variant: u16 = $value;)*
+/// Largest deflection of the control stick along an axis, once clamped by `Pad::scan_pads`.
+pub const STICK_MAX: i8 = 72;
+
+/// Largest deflection of the C-stick along an axis, once clamped by `Pad::scan_pads`.
+pub const SUB_STICK_MAX: i8 = 59;
+
+/// Largest value of the analog triggers, once clamped by `Pad::scan_pads`.
+pub const TRIGGER_MAX: u8 = 150;
+
+bitflags! {
+    /// Buttons of a gamecube controller.
+    ///
+    /// Test with `contains` for all of a set of buttons and `any` for at least one of them,
+    /// instead of comparing for equality, since several buttons can change in the same frame.
+    pub struct Buttons: u16 {
+        const LEFT  = ogc_sys::PAD_BUTTON_LEFT as u16;
+        const RIGHT = ogc_sys::PAD_BUTTON_RIGHT as u16;
+        const DOWN  = ogc_sys::PAD_BUTTON_DOWN as u16;
+        const UP    = ogc_sys::PAD_BUTTON_UP as u16;
+        const Z     = ogc_sys::PAD_TRIGGER_Z as u16;
+        const R     = ogc_sys::PAD_TRIGGER_R as u16;
+        const L     = ogc_sys::PAD_TRIGGER_L as u16;
+        const A     = ogc_sys::PAD_BUTTON_A as u16;
+        const B     = ogc_sys::PAD_BUTTON_B as u16;
+        const X     = ogc_sys::PAD_BUTTON_X as u16;
+        const Y     = ogc_sys::PAD_BUTTON_Y as u16;
+        /// Also known as `PAD_BUTTON_MENU`, which shares its bit.
+        const START = ogc_sys::PAD_BUTTON_START as u16;
+    }
+}
+
+impl Buttons {
+    const EACH: [Buttons; 12] = [
+        Buttons::LEFT,
+        Buttons::RIGHT,
+        Buttons::DOWN,
+        Buttons::UP,
+        Buttons::Z,
+        Buttons::R,
+        Buttons::L,
+        Buttons::A,
+        Buttons::B,
+        Buttons::X,
+        Buttons::Y,
+        Buttons::START,
+    ];
+
+    /// Returns true if at least one of `other` is set.
+    pub fn any(&self, other: Buttons) -> bool {
+        self.intersects(other)
+    }
+
+    /// Iterates over every single button that is set.
+    pub fn iter(&self) -> impl Iterator<Item = Buttons> {
+        let buttons = *self;
+        Self::EACH
+            .iter()
+            .copied()
+            .filter(move |b| buttons.contains(*b))
+    }
+}
+
+impl Default for Buttons {
+    fn default() -> Self {
+        Buttons::empty()
+    }
+}
+
+/// Command for the rumble motor, see `Pad::control_motor`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum MotorCommand {
+    /// Cuts the power and lets the motor spin down.
+    Stop = ogc_sys::PAD_MOTOR_STOP,
+    Rumble = ogc_sys::PAD_MOTOR_RUMBLE,
+    /// Brakes the motor, stopping it immediately.
+    StopHard = ogc_sys::PAD_MOTOR_STOP_HARD,
+}
+
+/// Reason a controller could not be read, see `PadState::error`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PadError {
+    NoController,
+    NotReady,
+    Transfer,
+}
+
+/// Snapshot of a gamecube controller, as returned by `Pad::read`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PadState {
+    /// Buttons held down.
+    pub buttons: Buttons,
+    pub stick_x: i8,
+    pub stick_y: i8,
+    pub sub_stick_x: i8,
+    pub sub_stick_y: i8,
+    pub trigger_l: u8,
+    pub trigger_r: u8,
+    /// Pressure on the A button, only reported by some controllers.
+    pub analog_a: u8,
+    /// Pressure on the B button, only reported by some controllers.
+    pub analog_b: u8,
+    /// Set if the controller could not be read, in which case every other field is zero.
+    pub error: Option<PadError>,
+}
+
+impl From<PadState> for ogc_sys::PADStatus {
+    fn from(state: PadState) -> Self {
+        let err = match state.error {
+            None => ogc_sys::PAD_ERR_NONE as i32,
+            Some(PadError::NoController) => ogc_sys::PAD_ERR_NO_CONTROLLER as i32,
+            Some(PadError::NotReady) => ogc_sys::PAD_ERR_NOT_READY as i32,
+            Some(PadError::Transfer) => ogc_sys::PAD_ERR_TRANSFER as i32,
+        };
+
+        Self {
+            button: state.buttons.bits(),
+            stickX: state.stick_x,
+            stickY: state.stick_y,
+            substickX: state.sub_stick_x,
+            substickY: state.sub_stick_y,
+            triggerL: state.trigger_l,
+            triggerR: state.trigger_r,
+            analogA: state.analog_a,
+            analogB: state.analog_b,
+            err: err as _,
         }
-    };
+    }
 }
 
-enumerate!(Button {
-    Left = 0x0001,
-    Right = 0x0002,
-    Down = 0x0004,
-    Up = 0x0008,
-    Z = 0x0010,
-    R = 0x0020,
-    L = 0x0040,
-    A = 0x0100,
-    B = 0x0200,
-    X = 0x0400,
-    Y = 0x0800,
-    Menu = 0x1000,
-    Start = 0x1000,
-});
+impl From<ogc_sys::PADStatus> for PadState {
+    fn from(status: ogc_sys::PADStatus) -> Self {
+        let err = status.err as i32;
+        let error = if err == ogc_sys::PAD_ERR_NONE as i32 {
+            None
+        } else if err == ogc_sys::PAD_ERR_NO_CONTROLLER as i32 {
+            Some(PadError::NoController)
+        } else if err == ogc_sys::PAD_ERR_NOT_READY as i32 {
+            Some(PadError::NotReady)
+        } else {
+            Some(PadError::Transfer)
+        };
+
+        Self {
+            buttons: Buttons::from_bits_truncate(status.button),
+            stick_x: status.stickX,
+            stick_y: status.stickY,
+            sub_stick_x: status.substickX,
+            sub_stick_y: status.substickY,
+            trigger_l: status.triggerL,
+            trigger_r: status.triggerR,
+            analog_a: status.analogA,
+            analog_b: status.analogB,
+            error,
+        }
+    }
+}
 
 impl Pad {
     /// Initialization of the pad service.
@@ -61,16 +219,16 @@ impl Pad {
         unsafe { ogc_sys::PAD_ScanPads() }
     }
 
-    pub fn buttons_down(controller: Controller) -> u16 {
-        unsafe { ogc_sys::PAD_ButtonsDown(controller as _) }
+    pub fn buttons_down(controller: Controller) -> Buttons {
+        Buttons::from_bits_truncate(unsafe { ogc_sys::PAD_ButtonsDown(controller as _) })
     }
 
-    pub fn buttons_held(controller: Controller) -> u16 {
-        unsafe { ogc_sys::PAD_ButtonsHeld(controller as _) }
+    pub fn buttons_held(controller: Controller) -> Buttons {
+        Buttons::from_bits_truncate(unsafe { ogc_sys::PAD_ButtonsHeld(controller as _) })
     }
 
-    pub fn buttons_up(controller: Controller) -> u16 {
-        unsafe { ogc_sys::PAD_ButtonsUp(controller as _) }
+    pub fn buttons_up(controller: Controller) -> Buttons {
+        Buttons::from_bits_truncate(unsafe { ogc_sys::PAD_ButtonsUp(controller as _) })
     }
 
     pub fn stick_x(controller: Controller) -> i8 {
@@ -97,24 +255,65 @@ impl Pad {
         unsafe { ogc_sys::PAD_TriggerR(controller as _) }
     }
 
-    /// Registers a sampling callback function.
-    pub fn set_sampling_callback<F>(callback: Box<F>)
+    /// Reads the current state of every controller, independently of `Pad::scan_pads`.
+    /// Unlike the other functions, this includes the analog A and B buttons.
+    /// The sticks and triggers are unclamped, see `Pad::clamp`.
+    pub fn read() -> [PadState; 4] {
+        let mut status: [ogc_sys::PADStatus; 4] = unsafe { mem::zeroed() };
+        unsafe { ogc_sys::PAD_Read(status.as_mut_ptr()) };
+        [
+            status[0].into(),
+            status[1].into(),
+            status[2].into(),
+            status[3].into(),
+        ]
+    }
+
+    /// Clamps the sticks and triggers of `states` to the octagonal gate of the controller,
+    /// the same way `Pad::scan_pads` does. Afterwards the sticks lie within `STICK_MAX` and
+    /// `SUB_STICK_MAX` and the triggers within `TRIGGER_MAX`.
+    pub fn clamp(states: &mut [PadState; 4]) {
+        let mut status: [ogc_sys::PADStatus; 4] = [
+            states[0].into(),
+            states[1].into(),
+            states[2].into(),
+            states[3].into(),
+        ];
+        unsafe { ogc_sys::PAD_Clamp(status.as_mut_ptr()) };
+        for (state, status) in states.iter_mut().zip(status.iter()) {
+            *state = (*status).into();
+        }
+    }
+
+    /// Takes the current position of the sticks and triggers of `controllers` as their new
+    /// origin. The sticks should be at rest when this is called.
+    /// Returns false if the controllers could not be recalibrated.
+    pub fn recalibrate(controllers: &[Controller]) -> bool {
+        let mask = controllers
+            .iter()
+            .fold(0, |mask, &c| mask | ogc_sys::PAD_CHAN0_BIT >> c as u32);
+        unsafe { ogc_sys::PAD_Recalibrate(mask) != 0 }
+    }
+
+    /// Registers a callback called every time the controllers are sampled.
+    ///
+    /// The callback runs in interrupt context, so it should be short and must not allocate.
+    pub fn set_sampling_callback<F>(callback: F)
     where
-        F: Fn(u32) -> (),
+        F: FnMut() + Send + 'static,
     {
-        let ptr = Box::into_raw(callback);
+        replace_callback::<SamplingCallback>(&SAMPLING_CALLBACK, Some(Box::new(callback)));
+        unsafe { ogc_sys::PAD_SetSamplingCallback(Some(sampling_trampoline as _)) };
+    }
 
-        unsafe {
-            let code: extern "C" fn() = mem::transmute(ptr);
-            ogc_sys::PAD_SetSamplingCallback(Some(code));
-        }
+    /// Removes the callback set with `set_sampling_callback`.
+    pub fn remove_sampling_callback() {
+        unsafe { ogc_sys::PAD_SetSamplingCallback(None) };
+        replace_callback::<SamplingCallback>(&SAMPLING_CALLBACK, None);
     }
 
-    fn clamp() {}
-    fn control_motor() {}
-    fn read() {}
-    fn recalibrate() {}
-    fn reset() {}
-    fn set_spec() {}
-    fn sync() {}
+    /// Starts or stops the rumble motor of the controller.
+    pub fn control_motor(controller: Controller, command: MotorCommand) {
+        unsafe { ogc_sys::PAD_ControlMotor(controller as _, command as _) };
+    }
 }
@@ -8,6 +8,9 @@ use alloc::boxed::Box;
 use core::{ffi::c_void, mem, time::Duration};
 use num_enum::IntoPrimitive;
 
+/// Time base ticks per millisecond.
+const TB_TIMER_CLOCK: u64 = 243_000_000 / 4000;
+
 /// Represents the system service.
 /// The initialization of this service is done in the crt0 startup code.
 pub struct System;
@@ -400,4 +403,26 @@ impl System {
     pub fn system_time() -> u64 {
         unsafe { ogc_sys::SYS_Time() }
     }
+
+    /// Get the current value of the time base, in ticks. Suitable for measuring short intervals.
+    pub fn get_time() -> u64 {
+        unsafe { ogc_sys::gettime() }
+    }
+
+    /// Convert a number of time base ticks into a `Duration`.
+    pub fn ticks_to_duration(ticks: u64) -> Duration {
+        Duration::from_nanos(ticks * 1_000_000 / TB_TIMER_CLOCK)
+    }
+
+    /// Writes the data cache lines covering `data` back to main memory, so hardware
+    /// reading memory directly, such as the DSP, sees what the CPU wrote.
+    pub fn flush_data_cache(data: &[u8]) {
+        unsafe { ogc_sys::DCFlushRange(data.as_ptr() as *mut _, data.len() as _) };
+    }
+
+    /// Discards the data cache lines covering `len` bytes at `data`, so the CPU reads
+    /// what hardware writing memory directly, such as the GPU, left there.
+    pub fn invalidate_data_cache(data: *mut c_void, len: usize) {
+        unsafe { ogc_sys::DCInvalidateRange(data, len as _) };
+    }
 }
@@ -1,7 +1,10 @@
 //! Utility Functions to convert between types.
 
-use alloc::{string::String, vec::Vec};
-use core::slice;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
+    slice,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 /// Converts a raw *mut u8 into a String.
 pub fn raw_to_string(raw: *mut u8) -> String {
@@ -21,6 +24,19 @@ pub fn raw_to_strings(raw: *mut *mut u8) -> Vec<String> {
         .collect()
 }
 
+/// Stores `callback` in `slot`, the address of a boxed callback that an `extern "C"`
+/// trampoline calls, and frees the one it replaces.
+///
+/// The callbacks run in interrupt context, which cannot be entered while this runs on
+/// the single core, so the replaced callback is never freed while it runs.
+pub(crate) fn replace_callback<C>(slot: &AtomicUsize, callback: Option<C>) {
+    let new = callback.map_or(0, |callback| Box::into_raw(Box::new(callback)) as usize);
+    let old = slot.swap(new, Ordering::AcqRel);
+    if old != 0 {
+        unsafe { drop(Box::from_raw(old as *mut C)) };
+    }
+}
+
 /// OS memory casting macros.
 mod memory_casting {
     /// Cast a cached address to a uncached address.
@@ -0,0 +1,203 @@
+//! The ``vorbis`` module of ``ogc-rs``.
+//!
+//! This module implements a safe wrapper around the Ogg Vorbis decoder found in
+//! ``tremor/ivorbisfile.h``. It needs the ``vorbis`` feature and the ``ppc-libvorbisidec``
+//! package of devkitPro, which ``ogc-sys`` links against.
+
+use crate::{
+    io::{Read, Seek},
+    OgcError, Result,
+};
+use alloc::{boxed::Box, format};
+use core::{ffi::c_void, mem, ptr, slice};
+
+const SEEK_SET: i32 = 0;
+const SEEK_CUR: i32 = 1;
+const SEEK_END: i32 = 2;
+
+unsafe extern "C" fn read_callback<R: Read + Seek>(
+    buffer: *mut c_void,
+    size: usize,
+    count: usize,
+    source: *mut c_void,
+) -> usize {
+    let reader = &mut *(source as *mut R);
+    let buffer = slice::from_raw_parts_mut(buffer as *mut u8, size * count);
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => read += n,
+        }
+    }
+    read / size.max(1)
+}
+
+unsafe extern "C" fn seek_callback<R: Read + Seek>(
+    source: *mut c_void,
+    offset: i64,
+    whence: i32,
+) -> i32 {
+    let reader = &mut *(source as *mut R);
+    let base = match whence {
+        SEEK_SET => 0,
+        SEEK_CUR => reader.position() as i64,
+        SEEK_END => match reader.len() {
+            Ok(len) => len as i64,
+            Err(_) => return -1,
+        },
+        _ => return -1,
+    };
+
+    match reader.seek((base + offset).max(0) as u32) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn tell_callback<R: Read + Seek>(source: *mut c_void) -> i32 {
+    let reader = &*(source as *mut R);
+    reader.position() as i32
+}
+
+/// Decodes an Ogg Vorbis stream, reading the file as it goes.
+///
+/// # Minimal Example
+///
+/// ```rust
+/// let file = File::open("sd:/song.ogg")?;
+/// let mut decoder = VorbisDecoder::new(file)?;
+/// let mut samples = [0; 4096];
+///
+/// loop {
+///     let read = decoder.read(&mut samples)?;
+///     if read == 0 {
+///         break;
+///     }
+///     // `samples[..read]` holds interleaved samples of `decoder.channels()` channels
+/// }
+/// ```
+pub struct VorbisDecoder<R> {
+    file: Box<ogc_sys::OggVorbis_File>,
+    // Boxed, so the pointer handed to the callbacks stays valid.
+    reader: Box<R>,
+    channels: u16,
+    rate: u32,
+}
+
+impl<R: Read + Seek> VorbisDecoder<R> {
+    /// Reads the headers of the stream.
+    pub fn new(reader: R) -> Result<Self> {
+        let mut reader = Box::new(reader);
+        let mut file: Box<ogc_sys::OggVorbis_File> = Box::new(unsafe { mem::zeroed() });
+
+        let err = unsafe {
+            // The callbacks use `usize` where the headers use `size_t`, which is the same type on the Wii.
+            let callbacks = ogc_sys::ov_callbacks {
+                read_func: Some(mem::transmute(
+                    read_callback::<R> as unsafe extern "C" fn(_, _, _, _) -> _,
+                )),
+                seek_func: Some(mem::transmute(
+                    seek_callback::<R> as unsafe extern "C" fn(_, _, _) -> _,
+                )),
+                close_func: None,
+                tell_func: Some(mem::transmute(
+                    tell_callback::<R> as unsafe extern "C" fn(_) -> _,
+                )),
+            };
+
+            ogc_sys::ov_open_callbacks(
+                &mut *reader as *mut R as *mut c_void,
+                &mut *file,
+                ptr::null_mut(),
+                0,
+                callbacks,
+            )
+        };
+
+        if err < 0 {
+            return Err(OgcError::Audio(format!(
+                "VorbisDecoder::new() failed with error {}",
+                err
+            )));
+        }
+
+        let info = unsafe { &*ogc_sys::ov_info(&mut *file, -1) };
+        Ok(Self {
+            file,
+            reader,
+            channels: info.channels as u16,
+            rate: info.rate as u32,
+        })
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Sample rate, in Hz.
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// Number of samples per channel, if the stream is seekable.
+    pub fn total_frames(&mut self) -> Option<u64> {
+        let total = unsafe { ogc_sys::ov_pcm_total(&mut *self.file, -1) };
+        if total < 0 {
+            None
+        } else {
+            Some(total as u64)
+        }
+    }
+
+    /// Decodes into `samples`, interleaved if stereo, and returns how many samples were
+    /// written. Returns 0 at the end of the stream.
+    pub fn read(&mut self, samples: &mut [i16]) -> Result<usize> {
+        let mut bitstream = 0;
+        loop {
+            let read = unsafe {
+                ogc_sys::ov_read(
+                    &mut *self.file,
+                    samples.as_mut_ptr() as *mut _,
+                    (samples.len() * 2) as _,
+                    &mut bitstream,
+                )
+            };
+
+            match read {
+                // A hole in the data, decoding continues after it.
+                x if x == ogc_sys::OV_HOLE as _ => continue,
+                x if x < 0 => {
+                    return Err(OgcError::Audio(format!(
+                        "VorbisDecoder::read() failed with error {}",
+                        read
+                    )))
+                }
+                x => return Ok(x as usize / 2),
+            }
+        }
+    }
+
+    /// Moves to sample `frame` of every channel.
+    pub fn seek(&mut self, frame: u64) -> Result<()> {
+        let err = unsafe { ogc_sys::ov_pcm_seek(&mut *self.file, frame as _) };
+        if err < 0 {
+            Err(OgcError::Audio(format!(
+                "VorbisDecoder::seek() failed with error {}",
+                err
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<R> Drop for VorbisDecoder<R> {
+    fn drop(&mut self) {
+        unsafe { ogc_sys::ov_clear(&mut *self.file) };
+    }
+}
@@ -9,20 +9,46 @@
 //! "post-link-args": {
 //!     "gcc": [
 //!         "-lwiiuse",
+//!         "-lbte",
 //!         "-logc"
 //!     ]
 //! },
 //! ```
 
-use alloc::boxed::Box;
-use core::mem;
+use crate::{bitflags, utils::replace_callback, OgcError, Result};
+use alloc::{boxed::Box, format};
+use core::{
+    mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+type ChannelCallback = Box<dyn FnMut(i32) + Send>;
+
+/// Addresses of the boxed callbacks the trampolines call, 0 when there is none.
+static BATTERY_DEAD_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+static POWER_BUTTON_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+
+fn call_channel_callback(slot: &AtomicUsize, chan: i32) {
+    let callback = slot.load(Ordering::Acquire) as *mut ChannelCallback;
+    if let Some(callback) = unsafe { callback.as_mut() } {
+        callback(chan);
+    }
+}
+
+extern "C" fn battery_dead_trampoline(chan: i32) {
+    call_channel_callback(&BATTERY_DEAD_CALLBACK, chan);
+}
+
+extern "C" fn power_button_trampoline(chan: i32) {
+    call_channel_callback(&POWER_BUTTON_CALLBACK, chan);
+}
 
 /// Represents the wpad service. No wii remotes can be read until an instance of
 /// this struct is created. This service can only be created once!
 pub struct Wpad;
 
 /// The remote to be read for the `wpad` service.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Remote {
     One = 0,
     Two = 1,
@@ -30,36 +56,236 @@ pub enum Remote {
     Four = 3,
 }
 
+impl Remote {
+    /// Every remote, in channel order.
+    pub const ALL: [Remote; 4] = [Remote::One, Remote::Two, Remote::Three, Remote::Four];
+}
+
 impl PartialEq<Remote> for u32 {
     fn eq(&self, other: &Remote) -> bool {
         *self == *other as u32
     }
 }
 
-/// The button to be checked for the `wpad` service.
-#[derive(Copy, Clone)]
-pub enum WpadButton {
-    One = 1,
-    Two = 2,
-    B = 4,
-    A = 8,
-    Minus = 16,
-    Home = 128,
-    Left = 256,
-    Right = 512,
-    Down = 1024,
-    Up = 2048,
-    Plus = 4096,
-    Z = 65536,
-    C = 131072,
+bitflags! {
+    /// Buttons of a wii remote and its expansion, as reported by `Wpad::buttons_held`.
+    ///
+    /// The upper 16 bits are shared: the `NUNCHUK_*` and `CLASSIC_*` buttons overlap,
+    /// so check `Wpad::expansion` before testing them.
+    pub struct WpadButtons: u32 {
+        const TWO   = ogc_sys::WPAD_BUTTON_2;
+        const ONE   = ogc_sys::WPAD_BUTTON_1;
+        const B     = ogc_sys::WPAD_BUTTON_B;
+        const A     = ogc_sys::WPAD_BUTTON_A;
+        const MINUS = ogc_sys::WPAD_BUTTON_MINUS;
+        const HOME  = ogc_sys::WPAD_BUTTON_HOME;
+        const LEFT  = ogc_sys::WPAD_BUTTON_LEFT;
+        const RIGHT = ogc_sys::WPAD_BUTTON_RIGHT;
+        const DOWN  = ogc_sys::WPAD_BUTTON_DOWN;
+        const UP    = ogc_sys::WPAD_BUTTON_UP;
+        const PLUS  = ogc_sys::WPAD_BUTTON_PLUS;
+
+        const NUNCHUK_Z = ogc_sys::WPAD_NUNCHUK_BUTTON_Z;
+        const NUNCHUK_C = ogc_sys::WPAD_NUNCHUK_BUTTON_C;
+
+        const CLASSIC_UP     = ogc_sys::WPAD_CLASSIC_BUTTON_UP;
+        const CLASSIC_LEFT   = ogc_sys::WPAD_CLASSIC_BUTTON_LEFT;
+        const CLASSIC_ZR     = ogc_sys::WPAD_CLASSIC_BUTTON_ZR;
+        const CLASSIC_X      = ogc_sys::WPAD_CLASSIC_BUTTON_X;
+        const CLASSIC_A      = ogc_sys::WPAD_CLASSIC_BUTTON_A;
+        const CLASSIC_Y      = ogc_sys::WPAD_CLASSIC_BUTTON_Y;
+        const CLASSIC_B      = ogc_sys::WPAD_CLASSIC_BUTTON_B;
+        const CLASSIC_ZL     = ogc_sys::WPAD_CLASSIC_BUTTON_ZL;
+        const CLASSIC_R      = ogc_sys::WPAD_CLASSIC_BUTTON_FULL_R;
+        const CLASSIC_PLUS   = ogc_sys::WPAD_CLASSIC_BUTTON_PLUS;
+        const CLASSIC_HOME   = ogc_sys::WPAD_CLASSIC_BUTTON_HOME;
+        const CLASSIC_MINUS  = ogc_sys::WPAD_CLASSIC_BUTTON_MINUS;
+        const CLASSIC_L      = ogc_sys::WPAD_CLASSIC_BUTTON_FULL_L;
+        const CLASSIC_DOWN   = ogc_sys::WPAD_CLASSIC_BUTTON_DOWN;
+        const CLASSIC_RIGHT  = ogc_sys::WPAD_CLASSIC_BUTTON_RIGHT;
+    }
+}
+
+impl WpadButtons {
+    const EACH: [WpadButtons; 28] = [
+        WpadButtons::TWO,
+        WpadButtons::ONE,
+        WpadButtons::B,
+        WpadButtons::A,
+        WpadButtons::MINUS,
+        WpadButtons::HOME,
+        WpadButtons::LEFT,
+        WpadButtons::RIGHT,
+        WpadButtons::DOWN,
+        WpadButtons::UP,
+        WpadButtons::PLUS,
+        WpadButtons::NUNCHUK_Z,
+        WpadButtons::NUNCHUK_C,
+        WpadButtons::CLASSIC_UP,
+        WpadButtons::CLASSIC_LEFT,
+        WpadButtons::CLASSIC_ZR,
+        WpadButtons::CLASSIC_X,
+        WpadButtons::CLASSIC_A,
+        WpadButtons::CLASSIC_Y,
+        WpadButtons::CLASSIC_B,
+        WpadButtons::CLASSIC_ZL,
+        WpadButtons::CLASSIC_R,
+        WpadButtons::CLASSIC_PLUS,
+        WpadButtons::CLASSIC_HOME,
+        WpadButtons::CLASSIC_MINUS,
+        WpadButtons::CLASSIC_L,
+        WpadButtons::CLASSIC_DOWN,
+        WpadButtons::CLASSIC_RIGHT,
+    ];
+
+    /// Buttons on the remote itself.
+    pub const REMOTE: WpadButtons = WpadButtons::from_bits_truncate(0xFFFF);
+
+    /// Buttons of the Nunchuk or the Classic Controller.
+    pub const EXPANSION: WpadButtons = WpadButtons::from_bits_truncate(0xFFFF_0000);
+
+    /// Returns true if at least one of `other` is set.
+    pub fn any(&self, other: WpadButtons) -> bool {
+        self.intersects(other)
+    }
+
+    /// Iterates over every single button that is set. Shared bits yield both the
+    /// Nunchuk and the Classic Controller button.
+    pub fn iter(&self) -> impl Iterator<Item = WpadButtons> {
+        let buttons = *self;
+        Self::EACH
+            .iter()
+            .copied()
+            .filter(move |b| buttons.contains(*b))
+    }
 }
 
-impl PartialEq<WpadButton> for u32 {
-    fn eq(&self, other: &WpadButton) -> bool {
-        *self == *other as u32
+impl Default for WpadButtons {
+    fn default() -> Self {
+        WpadButtons::empty()
+    }
+}
+
+/// Data reported by the remotes, see `Wpad::set_data_format`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(i32)]
+pub enum DataFormat {
+    Buttons = ogc_sys::WPAD_FMT_BTNS as i32,
+    ButtonsAccel = ogc_sys::WPAD_FMT_BTNS_ACC as i32,
+    ButtonsAccelIr = ogc_sys::WPAD_FMT_BTNS_ACC_IR as i32,
+}
+
+/// A three dimensional vector, as reported by the accelerometers.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// Infrared pointer data, in the resolution set with `Wpad::set_vres`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Ir {
+    /// True if the pointer is on screen.
+    pub valid: bool,
+    /// Pointer position.
+    pub x: f32,
+    pub y: f32,
+    /// True if the smoothed position is valid, which may be the case even when off screen.
+    pub smooth_valid: bool,
+    /// Smoothed pointer position.
+    pub sx: f32,
+    pub sy: f32,
+    /// Distance between the sensor bar dots, in pixels of the IR camera.
+    pub distance: f32,
+    /// Distance from the sensor bar, in meters.
+    pub z: f32,
+    /// Roll of the remote computed from the sensor bar, in degrees.
+    pub angle: f32,
+    /// Number of sensor bar dots seen by the camera.
+    pub num_dots: u8,
+}
+
+/// Orientation of a remote or Nunchuk, in degrees.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Orientation {
+    pub roll: f32,
+    pub pitch: f32,
+    /// Only available while the IR pointer is valid.
+    pub yaw: f32,
+}
+
+/// An analog stick of an expansion controller.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Joystick {
+    /// Position relative to the calibrated center, in `-1.0..=1.0`.
+    pub x: f32,
+    pub y: f32,
+    /// Angle in degrees, clockwise from up.
+    pub angle: f32,
+    /// Distance from the center, in `0.0..=1.0`.
+    pub magnitude: f32,
+}
+
+impl From<ogc_sys::joystick_t> for Joystick {
+    fn from(js: ogc_sys::joystick_t) -> Self {
+        fn axis(pos: u8, min: u8, center: u8, max: u8) -> f32 {
+            let (pos, min, center, max) = (pos as f32, min as f32, center as f32, max as f32);
+            let range = if pos >= center {
+                max - center
+            } else {
+                center - min
+            };
+            if range <= 0.0 {
+                0.0
+            } else {
+                ((pos - center) / range).max(-1.0).min(1.0)
+            }
+        }
+
+        Self {
+            x: axis(js.pos.x, js.min.x, js.center.x, js.max.x),
+            y: axis(js.pos.y, js.min.y, js.center.y, js.max.y),
+            angle: js.ang,
+            magnitude: js.mag,
+        }
     }
 }
 
+/// State of a Nunchuk attached to a remote.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Nunchuk {
+    pub stick: Joystick,
+    pub accel: Vec3<u8>,
+    pub orientation: Orientation,
+    pub g_force: Vec3<f32>,
+    /// Held buttons, `WpadButtons::NUNCHUK_Z` and `WpadButtons::NUNCHUK_C`.
+    pub buttons: WpadButtons,
+}
+
+/// State of a Classic Controller attached to a remote.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Classic {
+    pub left_stick: Joystick,
+    pub right_stick: Joystick,
+    /// Analog shoulder buttons, in `0.0..=1.0`.
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+    /// Held buttons, the `WpadButtons::CLASSIC_*` ones.
+    pub buttons: WpadButtons,
+}
+
+/// Controller attached to the expansion port of a remote.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Expansion {
+    None,
+    Nunchuk(Nunchuk),
+    Classic(Classic),
+    GuitarHero3,
+    BalanceBoard,
+    MotionPlus,
+}
+
 impl Wpad {
     /// Initialization of the wpad service.
     pub fn init() -> Self {
@@ -72,68 +298,237 @@ impl Wpad {
         unsafe { ogc_sys::WPAD_ScanPads() }
     }
 
-    pub fn buttons_down(remote: Remote) -> u32 {
-        unsafe { ogc_sys::WPAD_ButtonsDown(remote as _) }
+    pub fn buttons_down(remote: Remote) -> WpadButtons {
+        WpadButtons::from_bits_truncate(unsafe { ogc_sys::WPAD_ButtonsDown(remote as _) })
     }
 
-    pub fn buttons_held(remote: Remote) -> u32 {
-        unsafe { ogc_sys::WPAD_ButtonsHeld(remote as _) }
+    pub fn buttons_held(remote: Remote) -> WpadButtons {
+        WpadButtons::from_bits_truncate(unsafe { ogc_sys::WPAD_ButtonsHeld(remote as _) })
     }
 
-    pub fn buttons_up(remote: Remote) -> u32 {
-        unsafe { ogc_sys::WPAD_ButtonsHeld(remote as _) }
+    pub fn buttons_up(remote: Remote) -> WpadButtons {
+        WpadButtons::from_bits_truncate(unsafe { ogc_sys::WPAD_ButtonsUp(remote as _) })
     }
 
-    /// Registers a battery dead callback function.
-    pub fn set_battery_dead_callback<F>(callback: Box<F>)
+    /// Returns true if the remote is connected and ready to be read.
+    pub fn is_connected(remote: Remote) -> bool {
+        let mut kind = 0;
+        unsafe { ogc_sys::WPAD_Probe(remote as _, &mut kind) == ogc_sys::WPAD_ERR_NONE as _ }
+    }
+
+    /// Selects which data the remote reports. IR data needs `DataFormat::ButtonsAccelIr`.
+    pub fn set_data_format(remote: Remote, format: DataFormat) -> Result<()> {
+        let err = unsafe { ogc_sys::WPAD_SetDataFormat(remote as _, format as _) };
+        Self::check(err, "Wpad::set_data_format()")
+    }
+
+    /// Sets the resolution the IR pointer position is mapped to.
+    pub fn set_vres(remote: Remote, width: u32, height: u32) -> Result<()> {
+        let err = unsafe { ogc_sys::WPAD_SetVRes(remote as _, width, height) };
+        Self::check(err, "Wpad::set_vres()")
+    }
+
+    /// Infrared pointer data of the remote.
+    pub fn ir(remote: Remote) -> Ir {
+        let ir = unsafe {
+            let mut ir: ogc_sys::ir_t = mem::zeroed();
+            ogc_sys::WPAD_IR(remote as _, &mut ir);
+            ir
+        };
+
+        Ir {
+            valid: ir.valid != 0,
+            x: ir.x,
+            y: ir.y,
+            smooth_valid: ir.smooth_valid != 0,
+            sx: ir.sx,
+            sy: ir.sy,
+            distance: ir.distance,
+            z: ir.z,
+            angle: ir.angle,
+            num_dots: ir.num_dots,
+        }
+    }
+
+    /// Raw accelerometer values of the remote.
+    pub fn accel(remote: Remote) -> Vec3<u16> {
+        let accel = unsafe {
+            let mut accel: ogc_sys::vec3w_t = mem::zeroed();
+            ogc_sys::WPAD_Accel(remote as _, &mut accel);
+            accel
+        };
+
+        Vec3 {
+            x: accel.x,
+            y: accel.y,
+            z: accel.z,
+        }
+    }
+
+    /// Orientation of the remote computed from the accelerometers.
+    pub fn orientation(remote: Remote) -> Orientation {
+        let orient = unsafe {
+            let mut orient: ogc_sys::orient_t = mem::zeroed();
+            ogc_sys::WPAD_Orientation(remote as _, &mut orient);
+            orient
+        };
+
+        orient.into()
+    }
+
+    /// Acceleration of the remote, in g.
+    pub fn g_force(remote: Remote) -> Vec3<f32> {
+        let gforce = unsafe {
+            let mut gforce: ogc_sys::gforce_t = mem::zeroed();
+            ogc_sys::WPAD_GForce(remote as _, &mut gforce);
+            gforce
+        };
+
+        gforce.into()
+    }
+
+    /// Controller attached to the expansion port of the remote.
+    pub fn expansion(remote: Remote) -> Expansion {
+        let exp = unsafe {
+            let mut exp: ogc_sys::expansion_t = mem::zeroed();
+            ogc_sys::WPAD_Expansion(remote as _, &mut exp);
+            exp
+        };
+
+        match exp.type_ as u32 {
+            ogc_sys::EXP_NUNCHUK => {
+                let nunchuk = unsafe { exp.__bindgen_anon_1.nunchuk.as_ref() };
+                Expansion::Nunchuk(Nunchuk {
+                    stick: nunchuk.js.into(),
+                    accel: Vec3 {
+                        x: nunchuk.accel.x,
+                        y: nunchuk.accel.y,
+                        z: nunchuk.accel.z,
+                    },
+                    orientation: nunchuk.orient.into(),
+                    g_force: nunchuk.gforce.into(),
+                    buttons: WpadButtons::from_bits_truncate((nunchuk.btns_held as u32) << 16),
+                })
+            }
+            ogc_sys::EXP_CLASSIC => {
+                let classic = unsafe { exp.__bindgen_anon_1.classic.as_ref() };
+                Expansion::Classic(Classic {
+                    left_stick: classic.ljs.into(),
+                    right_stick: classic.rjs.into(),
+                    left_trigger: classic.l_shoulder,
+                    right_trigger: classic.r_shoulder,
+                    buttons: WpadButtons::from_bits_truncate((classic.btns_held as u32) << 16),
+                })
+            }
+            ogc_sys::EXP_GUITAR_HERO_3 => Expansion::GuitarHero3,
+            ogc_sys::EXP_WII_BOARD => Expansion::BalanceBoard,
+            ogc_sys::EXP_MOTION_PLUS => Expansion::MotionPlus,
+            _ => Expansion::None,
+        }
+    }
+
+    /// Battery level of the remote, from 0 (empty) to 4 (full).
+    pub fn battery_level(remote: Remote) -> u8 {
+        let level = unsafe { ogc_sys::WPAD_BatteryLevel(remote as _) };
+        // libogc reports the raw level, which is roughly 0xC0 for fresh batteries.
+        (level as u32 * 5 / 0xC1).min(4) as u8
+    }
+
+    /// Turns the rumble motor of the remote on or off.
+    pub fn rumble(remote: Remote, enable: bool) -> Result<()> {
+        let err = unsafe { ogc_sys::WPAD_Rumble(remote as _, enable as _) };
+        Self::check(err, "Wpad::rumble()")
+    }
+
+    /// Enables or disables a Wii MotionPlus attached to the remote.
+    pub fn set_motion_plus(remote: Remote, enable: bool) {
+        unsafe { ogc_sys::WPAD_SetMotionPlus(remote as _, enable as _) };
+    }
+
+    /// Seconds of inactivity after which remotes are disconnected.
+    pub fn set_idle_timeout(seconds: u32) {
+        unsafe { ogc_sys::WPAD_SetIdleTimeout(seconds) };
+    }
+
+    /// Flushes pending commands to the remote.
+    pub fn flush(remote: Remote) -> Result<()> {
+        let err = unsafe { ogc_sys::WPAD_Flush(remote as _) };
+        Self::check(err, "Wpad::flush()")
+    }
+
+    /// Disconnects the remote.
+    pub fn disconnect(remote: Remote) -> Result<()> {
+        let err = unsafe { ogc_sys::WPAD_Disconnect(remote as _) };
+        Self::check(err, "Wpad::disconnect()")
+    }
+
+    /// Disconnects all remotes and shuts the wpad service down.
+    pub fn shutdown() {
+        unsafe { ogc_sys::WPAD_Shutdown() };
+    }
+
+    fn check(err: i32, function: &str) -> Result<()> {
+        if err < ogc_sys::WPAD_ERR_NONE as _ {
+            Err(OgcError::Wpad(format!(
+                "{} failed with error {}",
+                function, err
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Registers a callback called with the channel of a remote whose batteries ran out.
+    ///
+    /// The callback runs in interrupt context, so it should be short and must not allocate.
+    pub fn set_battery_dead_callback<F>(callback: F)
     where
-        F: Fn(i32) -> (),
+        F: FnMut(i32) + Send + 'static,
     {
-        let ptr = Box::into_raw(callback);
+        replace_callback::<ChannelCallback>(&BATTERY_DEAD_CALLBACK, Some(Box::new(callback)));
+        unsafe { ogc_sys::WPAD_SetBatteryDeadCallback(Some(battery_dead_trampoline as _)) };
+    }
 
-        unsafe {
-            let code: extern "C" fn(i32) = mem::transmute(ptr);
-            ogc_sys::WPAD_SetBatteryDeadCallback(Some(code));
-        }
+    /// Removes the callback set with `set_battery_dead_callback`.
+    pub fn remove_battery_dead_callback() {
+        unsafe { ogc_sys::WPAD_SetBatteryDeadCallback(None) };
+        replace_callback::<ChannelCallback>(&BATTERY_DEAD_CALLBACK, None);
     }
 
-    /// Registers a power button callback function.
-    pub fn set_power_button_callback<F>(callback: Box<F>)
+    /// Registers a callback called with the channel of a remote whose power button was pressed.
+    ///
+    /// The callback runs in interrupt context, so it should be short and must not allocate.
+    pub fn set_power_button_callback<F>(callback: F)
     where
-        F: Fn(i32) -> (),
+        F: FnMut(i32) + Send + 'static,
     {
-        let ptr = Box::into_raw(callback);
+        replace_callback::<ChannelCallback>(&POWER_BUTTON_CALLBACK, Some(Box::new(callback)));
+        unsafe { ogc_sys::WPAD_SetPowerButtonCallback(Some(power_button_trampoline as _)) };
+    }
 
-        unsafe {
-            let code: extern "C" fn(i32) = mem::transmute(ptr);
-            ogc_sys::WPAD_SetPowerButtonCallback(Some(code));
+    /// Removes the callback set with `set_power_button_callback`.
+    pub fn remove_power_button_callback() {
+        unsafe { ogc_sys::WPAD_SetPowerButtonCallback(None) };
+        replace_callback::<ChannelCallback>(&POWER_BUTTON_CALLBACK, None);
+    }
+}
+
+impl From<ogc_sys::orient_t> for Orientation {
+    fn from(orient: ogc_sys::orient_t) -> Self {
+        Self {
+            roll: orient.roll,
+            pitch: orient.pitch,
+            yaw: orient.yaw,
         }
     }
+}
 
-    fn accel() {}
-    fn battery_level() {}
-    fn control_speaker() {}
-    fn data() {}
-    fn disconnect() {}
-    fn dropped_events() {}
-    fn encode_data() {}
-    fn expansion() {}
-    fn flush() {}
-    fn g_force() {}
-    fn get_status() {}
-    fn ir() {}
-    fn is_speaker_enabled() {}
-    fn orientation() {}
-    fn probe() {}
-    fn read_event() {}
-    fn read_pending() {}
-    fn rumble() {}
-    fn send_stream_data() {}
-    fn set_data_format() {}
-    fn set_event_bufs() {}
-    fn set_idle_thresholds() {}
-    fn set_idle_timeout() {}
-    fn set_motion_plus() {}
-    fn set_vres() {}
-    fn shutdown() {}
+impl From<ogc_sys::gforce_t> for Vec3<f32> {
+    fn from(gforce: ogc_sys::gforce_t) -> Self {
+        Self {
+            x: gforce.x,
+            y: gforce.y,
+            z: gforce.z,
+        }
+    }
 }
@@ -0,0 +1,320 @@
+use ogc::prelude::*;
+
+/// Position of an analog stick, with both axes in `-1.0..=1.0` and a length of at most `1.0`.
+/// Positive `y` points up.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StickVector {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl StickVector {
+    pub const ZERO: StickVector = StickVector { x: 0.0, y: 0.0 };
+
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Control stick of a gamecube controller, as clamped by `Pad::scan_pads`.
+    #[cfg(target_os = "rvl-ios")]
+    pub fn pad_stick(controller: Controller) -> Self {
+        Self::from_raw(
+            Pad::stick_x(controller),
+            Pad::stick_y(controller),
+            STICK_MAX,
+        )
+    }
+
+    /// C-stick of a gamecube controller, as clamped by `Pad::scan_pads`.
+    #[cfg(target_os = "rvl-ios")]
+    pub fn pad_sub_stick(controller: Controller) -> Self {
+        Self::from_raw(
+            Pad::sub_stick_x(controller),
+            Pad::sub_stick_y(controller),
+            SUB_STICK_MAX,
+        )
+    }
+
+    /// Normalizes a raw stick position, where `max` is the deflection along an axis.
+    pub fn from_raw(x: i8, y: i8, max: i8) -> Self {
+        let max = max as f32;
+        Self::new(x as f32 / max, y as f32 / max).clamp_length()
+    }
+
+    /// Distance from the center.
+    pub fn length(&self) -> f32 {
+        libm::sqrtf(self.x * self.x + self.y * self.y)
+    }
+
+    /// Angle in radians, counter-clockwise from the positive x axis.
+    pub fn angle(&self) -> f32 {
+        libm::atan2f(self.y, self.x)
+    }
+
+    fn scale(self, factor: f32) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+
+    fn clamp_length(self) -> Self {
+        let length = self.length();
+        if length > 1.0 {
+            self.scale(1.0 / length)
+        } else {
+            self
+        }
+    }
+}
+
+/// Region around the center of a stick that reads as zero, as a fraction of the full range.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DeadZone {
+    None,
+    /// Applied to each axis separately. Keeps cardinal directions straight, but
+    /// diagonals snap towards the axes.
+    Axial(f32),
+    /// Applied to the distance from the center. Keeps the direction intact.
+    Radial(f32),
+}
+
+/// Mapping from the deflection of a stick, past its dead zone, to the reported value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResponseCurve {
+    Linear,
+    /// Finer control near the center.
+    Quadratic,
+    /// Even finer control near the center.
+    Cubic,
+    /// Raises the deflection to the given power.
+    Power(f32),
+}
+
+impl ResponseCurve {
+    /// Maps `value` in `0.0..=1.0`.
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => value,
+            ResponseCurve::Quadratic => value * value,
+            ResponseCurve::Cubic => value * value * value,
+            ResponseCurve::Power(exponent) => libm::powf(value, exponent),
+        }
+    }
+}
+
+/// Processing applied to an analog stick.
+///
+/// # Example
+///
+/// ```rust
+/// let config = StickConfig::new()
+///     .dead_zone(DeadZone::Radial(0.2))
+///     .curve(ResponseCurve::Quadratic);
+/// let stick = config.apply(StickVector::pad_stick(Controller::One));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StickConfig {
+    dead_zone: DeadZone,
+    saturation: f32,
+    curve: ResponseCurve,
+}
+
+impl StickConfig {
+    /// A radial dead zone of 0.15, full saturation and a linear response.
+    pub fn new() -> Self {
+        Self {
+            dead_zone: DeadZone::Radial(0.15),
+            saturation: 1.0,
+            curve: ResponseCurve::Linear,
+        }
+    }
+
+    pub fn dead_zone(self, dead_zone: DeadZone) -> Self {
+        Self { dead_zone, ..self }
+    }
+
+    /// Deflection from which the stick reads as fully pushed, for sticks that do not reach their edge.
+    pub fn saturation(self, saturation: f32) -> Self {
+        Self { saturation, ..self }
+    }
+
+    pub fn curve(self, curve: ResponseCurve) -> Self {
+        Self { curve, ..self }
+    }
+
+    /// Applies the dead zone, saturation and response curve to `stick`.
+    pub fn apply(&self, stick: StickVector) -> StickVector {
+        let stick = stick.clamp_length();
+
+        match self.dead_zone {
+            DeadZone::Axial(dead_zone) => {
+                let axis = |value: f32| {
+                    let magnitude = rescale(libm::fabsf(value), dead_zone, self.saturation);
+                    libm::copysignf(self.curve.apply(magnitude), value)
+                };
+                StickVector::new(axis(stick.x), axis(stick.y)).clamp_length()
+            }
+            DeadZone::Radial(_) | DeadZone::None => {
+                let dead_zone = match self.dead_zone {
+                    DeadZone::Radial(dead_zone) => dead_zone,
+                    _ => 0.0,
+                };
+                let length = stick.length();
+                if length <= 0.0 {
+                    return StickVector::ZERO;
+                }
+                let magnitude = self
+                    .curve
+                    .apply(rescale(length, dead_zone, self.saturation));
+                stick.scale(magnitude / length)
+            }
+        }
+    }
+}
+
+impl Default for StickConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Processing applied to an analog trigger.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TriggerConfig {
+    threshold: f32,
+    saturation: f32,
+}
+
+impl TriggerConfig {
+    /// A threshold of 0.1 and full saturation.
+    pub fn new() -> Self {
+        Self {
+            threshold: 0.1,
+            saturation: 1.0,
+        }
+    }
+
+    /// Pull below which the trigger reads as released.
+    pub fn threshold(self, threshold: f32) -> Self {
+        Self { threshold, ..self }
+    }
+
+    /// Pull from which the trigger reads as fully pressed.
+    pub fn saturation(self, saturation: f32) -> Self {
+        Self { saturation, ..self }
+    }
+
+    /// Normalizes a trigger of a gamecube controller, as clamped by `Pad::scan_pads`.
+    pub fn apply_raw(&self, value: u8) -> f32 {
+        self.apply(value as f32 / TRIGGER_MAX as f32)
+    }
+
+    /// Applies the threshold and saturation to `value` in `0.0..=1.0`.
+    pub fn apply(&self, value: f32) -> f32 {
+        rescale(value, self.threshold, self.saturation)
+    }
+
+    /// Returns true if `value` in `0.0..=1.0` is past the threshold.
+    pub fn is_pressed(&self, value: f32) -> bool {
+        value > self.threshold
+    }
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Maps `value` from `low..=high` onto `0.0..=1.0`.
+fn rescale(value: f32, low: f32, high: f32) -> f32 {
+    if value <= low {
+        0.0
+    } else if value >= high || high <= low {
+        1.0
+    } else {
+        (value - low) / (high - low)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(expected: StickVector, actual: StickVector) {
+        assert!(
+            libm::fabsf(expected.x - actual.x) < 1e-5 && libm::fabsf(expected.y - actual.y) < 1e-5,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn zero_dead_zone_passes_the_stick_through() {
+        for dead_zone in [DeadZone::None, DeadZone::Radial(0.0), DeadZone::Axial(0.0)] {
+            let config = StickConfig::new().dead_zone(dead_zone);
+            assert_eq!(StickVector::ZERO, config.apply(StickVector::ZERO));
+            assert_near(
+                StickVector::new(0.3, -0.4),
+                config.apply(StickVector::new(0.3, -0.4)),
+            );
+        }
+    }
+
+    #[test]
+    fn dead_zone_reads_as_zero_and_rescales_the_rest() {
+        let radial = StickConfig::new().dead_zone(DeadZone::Radial(0.2));
+        assert_eq!(StickVector::ZERO, radial.apply(StickVector::new(0.1, 0.1)));
+        assert_near(
+            StickVector::new(0.0, 0.5),
+            radial.apply(StickVector::new(0.0, 0.6)),
+        );
+
+        let axial = StickConfig::new().dead_zone(DeadZone::Axial(0.2));
+        assert_near(
+            StickVector::new(0.5, 0.0),
+            axial.apply(StickVector::new(0.6, 0.1)),
+        );
+    }
+
+    #[test]
+    fn saturation_at_or_below_dead_zone_acts_as_a_switch() {
+        for saturation in [0.3, 0.1] {
+            let radial = StickConfig::new()
+                .dead_zone(DeadZone::Radial(0.3))
+                .saturation(saturation);
+            assert_eq!(StickVector::ZERO, radial.apply(StickVector::new(0.0, 0.3)));
+            assert_near(
+                StickVector::new(0.0, -1.0),
+                radial.apply(StickVector::new(0.0, -0.31)),
+            );
+
+            let axial = StickConfig::new()
+                .dead_zone(DeadZone::Axial(0.3))
+                .saturation(saturation);
+            assert_near(
+                StickVector::new(-1.0, 0.0),
+                axial.apply(StickVector::new(-0.5, 0.2)),
+            );
+        }
+    }
+
+    #[test]
+    fn output_stays_within_the_unit_circle() {
+        let config = StickConfig::new()
+            .dead_zone(DeadZone::Axial(0.1))
+            .saturation(0.5);
+        let stick = config.apply(StickVector::from_raw(STICK_MAX, STICK_MAX, STICK_MAX));
+        assert!(stick.length() <= 1.0 + 1e-5);
+        assert!(StickVector::from_raw(-STICK_MAX, STICK_MAX, STICK_MAX).length() <= 1.0 + 1e-5);
+    }
+
+    #[test]
+    fn trigger_threshold_and_saturation() {
+        let config = TriggerConfig::new().threshold(0.2).saturation(0.6);
+        assert_eq!(0.0, config.apply(0.2));
+        assert!(libm::fabsf(0.5 - config.apply(0.4)) < 1e-5);
+        assert_eq!(1.0, config.apply_raw(TRIGGER_MAX));
+        assert!(!config.is_pressed(0.2));
+        assert!(config.is_pressed(0.21));
+    }
+}
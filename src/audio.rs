@@ -0,0 +1,533 @@
+extern crate alloc;
+
+use crate::{
+    global::Global,
+    mixer::{Bus, EffectChain, Mixer},
+    wav::Wav,
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+use ogc::{prelude::*, OgcError, Result};
+
+/// `Mp3Player` plays on this voice, so it stays claimed for the music.
+const MUSIC_VOICE: u32 = 0;
+const VOICES: usize = VOICE_COUNT as usize;
+/// Highest pitch `Asnd` can play a voice at, in Hz.
+const MAX_PITCH: f32 = 144_000.0;
+
+/// PCM samples ready to be played by `Audio`. Cloning is cheap, the samples are shared.
+#[derive(Clone)]
+pub struct Sound {
+    buffer: SoundBuffer,
+    format: VoiceFormat,
+    rate: u32,
+}
+
+impl Sound {
+    /// Copies `data`, PCM samples in `format` at `rate` Hz, into memory the DSP can read.
+    pub fn new(data: &[u8], format: VoiceFormat, rate: u32) -> Self {
+        let silence = match format {
+            VoiceFormat::Mono8BitU | VoiceFormat::Stereo8BitU => 0x80,
+            _ => 0,
+        };
+
+        Self {
+            buffer: SoundBuffer::with_padding(data, silence),
+            format,
+            rate,
+        }
+    }
+
+    /// Loads a WAV file at its own sample rate, see `Wav`.
+    pub fn from_wav(bytes: &[u8]) -> Result<Self> {
+        Ok(Wav::parse(bytes)?.to_sound())
+    }
+
+    pub fn format(&self) -> VoiceFormat {
+        self.format
+    }
+
+    /// Sample rate, in Hz.
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// Size of the samples, in bytes.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Playing time at the original pitch.
+    pub fn duration(&self) -> Duration {
+        let frame = self.format.frame_size();
+        Duration::from_secs_f32((self.len() / frame) as f32 / self.rate.max(1) as f32)
+    }
+
+    /// The sound processed by `effects` ahead of time, as 16-bit samples lengthened by
+    /// the tail of the effects, see `EffectChain::tail`.
+    pub fn with_effects(&self, effects: &mut EffectChain) -> Self {
+        self.processed(effects, true)
+    }
+
+    pub(crate) fn processed(&self, effects: &mut EffectChain, tail: bool) -> Self {
+        let channels = self.format.channels();
+        let mut samples = self.samples();
+        if tail {
+            let frames = effects.tail().as_secs_f32() * self.rate as f32;
+            samples.resize(samples.len() + frames as usize * channels, 0);
+        }
+        effects.process(&mut samples, channels, self.rate);
+
+        let bytes: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| sample.to_be_bytes())
+            .collect();
+        let format = if channels == 1 {
+            VoiceFormat::Mono16Bit
+        } else {
+            VoiceFormat::Stereo16Bit
+        };
+        Self::new(&bytes, format, self.rate)
+    }
+
+    /// Returns true if `other` is a clone of this sound.
+    pub(crate) fn is_same(&self, other: &Sound) -> bool {
+        self.buffer.ptr_eq(&other.buffer) && self.format == other.format && self.rate == other.rate
+    }
+
+    /// The samples as signed 16-bit values, interleaved if stereo.
+    fn samples(&self) -> Vec<i16> {
+        let data = self.buffer.as_slice();
+        match self.format {
+            VoiceFormat::Mono8Bit | VoiceFormat::Stereo8Bit => {
+                data.iter().map(|&byte| (byte as i8 as i16) << 8).collect()
+            }
+            VoiceFormat::Mono8BitU | VoiceFormat::Stereo8BitU => {
+                data.iter().map(|&byte| (byte as i16 - 0x80) << 8).collect()
+            }
+            VoiceFormat::Mono16BitLe | VoiceFormat::Stereo16BitLe => data
+                .chunks_exact(2)
+                .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+                .collect(),
+            _ => data
+                .chunks_exact(2)
+                .map(|bytes| i16::from_be_bytes([bytes[0], bytes[1]]))
+                .collect(),
+        }
+    }
+}
+
+/// How `Audio::play_with` plays a sound.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlayOptions {
+    volume: f32,
+    pan: f32,
+    pitch: f32,
+    looping: bool,
+    bus: Bus,
+}
+
+impl PlayOptions {
+    /// Full volume, centered, at the original pitch, once, on `Bus::Effects`.
+    pub fn new() -> Self {
+        Self {
+            volume: 1.0,
+            pan: 0.0,
+            pitch: 1.0,
+            looping: false,
+            bus: Bus::Effects,
+        }
+    }
+
+    /// Volume, in `0.0..=1.0`.
+    pub fn volume(self, volume: f32) -> Self {
+        Self { volume, ..self }
+    }
+
+    /// Balance, from `-1.0` for left only to `1.0` for right only.
+    pub fn pan(self, pan: f32) -> Self {
+        Self { pan, ..self }
+    }
+
+    /// Playback speed relative to the sample rate of the sound. `2.0` is an octave up.
+    pub fn pitch(self, pitch: f32) -> Self {
+        Self { pitch, ..self }
+    }
+
+    /// Plays the sound over and over, until stopped.
+    pub fn looping(self, looping: bool) -> Self {
+        Self { looping, ..self }
+    }
+
+    /// Bus the sound is mixed on, for its effects, volume and ducking, see `Mixer`.
+    pub fn bus(self, bus: Bus) -> Self {
+        Self { bus, ..self }
+    }
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A volume ramp, advanced once per frame.
+pub(crate) struct Fade {
+    from: f32,
+    to: f32,
+    started: u64,
+    duration: Duration,
+    /// Stop the sound once the fade is done.
+    pub(crate) stop: bool,
+}
+
+impl Fade {
+    pub(crate) fn new(from: f32, to: f32, duration: Duration, stop: bool) -> Self {
+        Self {
+            from,
+            to,
+            started: System::get_time(),
+            duration,
+            stop,
+        }
+    }
+
+    /// Volume at time base `now`, and whether the fade is done.
+    pub(crate) fn volume(&self, now: u64) -> (f32, bool) {
+        let elapsed = System::ticks_to_duration(now.saturating_sub(self.started));
+        if elapsed >= self.duration {
+            (self.to, true)
+        } else {
+            let t = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+            (self.from + (self.to - self.from) * t, false)
+        }
+    }
+}
+
+struct Slot {
+    voice: Option<Voice>,
+    sound: Option<Sound>,
+    generation: u32,
+    volume: f32,
+    pan: f32,
+    /// Attenuation by distance, set by `Emitter`.
+    gain: f32,
+    bus: Bus,
+    fade: Option<Fade>,
+}
+
+impl Slot {
+    const FREE: Slot = Slot {
+        voice: None,
+        sound: None,
+        generation: 0,
+        volume: 1.0,
+        pan: 0.0,
+        gain: 1.0,
+        bus: Bus::Effects,
+        fade: None,
+    };
+
+    /// Stops the sound and gives the voice back.
+    fn release(&mut self) {
+        self.voice = None;
+        self.sound = None;
+    }
+}
+
+static SLOTS: Global<[Slot; VOICES]> = Global::new([Slot::FREE; VOICES]);
+static MP3_VOICE: Global<Option<Voice>> = Global::new(None);
+static EFFECTS_VOLUME: Global<f32> = Global::new(1.0);
+static MUSIC: Global<Option<&'static [u8]>> = Global::new(None);
+static MUSIC_LOOPING: Global<bool> = Global::new(false);
+static MUSIC_VOLUME: Global<f32> = Global::new(1.0);
+
+/// A sound effect started by `Audio::play`. Does nothing once the sound has ended.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SoundHandle {
+    voice: u32,
+    generation: u32,
+}
+
+impl SoundHandle {
+    /// Calls `f` with the slot of the sound, unless it has ended.
+    fn with_slot<R>(&self, f: impl FnOnce(&mut Slot) -> R) -> Option<R> {
+        SLOTS.with_mut(|slots| {
+            let slot = &mut slots[self.voice as usize];
+            if slot.generation == self.generation && slot.voice.is_some() {
+                Some(f(slot))
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.with_slot(|_| ()).is_some()
+    }
+
+    /// Stops the sound immediately.
+    pub fn stop(&self) {
+        self.with_slot(Slot::release);
+    }
+
+    /// Fades the sound out over `duration`, then stops it.
+    pub fn fade_out(&self, duration: Duration) {
+        self.fade(0.0, duration, true);
+    }
+
+    /// Changes the volume to `volume` gradually over `duration`.
+    pub fn fade_to(&self, volume: f32, duration: Duration) {
+        self.fade(volume, duration, false);
+    }
+
+    /// Volume, in `0.0..=1.0`. Cancels a fade.
+    pub fn set_volume(&self, volume: f32) {
+        self.with_slot(|slot| {
+            slot.volume = volume;
+            slot.fade = None;
+            apply_volume(slot);
+        });
+    }
+
+    /// Balance, from `-1.0` for left only to `1.0` for right only.
+    pub fn set_pan(&self, pan: f32) {
+        self.with_slot(|slot| {
+            slot.pan = pan;
+            apply_volume(slot);
+        });
+    }
+
+    /// Playback speed relative to the sample rate of the sound.
+    pub fn set_pitch(&self, pitch: f32) {
+        self.with_slot(|slot| {
+            if let (Some(voice), Some(sound)) = (slot.voice.as_mut(), slot.sound.as_ref()) {
+                let _ = voice.set_pitch(voice_pitch(sound, pitch));
+            }
+        });
+    }
+
+    /// Attenuation and balance of a positioned sound, on top of its volume.
+    pub(crate) fn set_spatial(&self, gain: f32, pan: f32) {
+        self.with_slot(|slot| {
+            slot.gain = gain;
+            slot.pan = pan;
+            apply_volume(slot);
+        });
+    }
+
+    fn fade(&self, to: f32, duration: Duration, stop: bool) {
+        self.with_slot(|slot| slot.fade = Some(Fade::new(slot.volume, to, duration, stop)));
+    }
+}
+
+fn voice_pitch(sound: &Sound, pitch: f32) -> u32 {
+    (sound.rate as f32 * pitch).max(1.0).min(MAX_PITCH) as u32
+}
+
+fn apply_volume(slot: &mut Slot) {
+    let (left, right) = volumes(slot.volume * slot.gain, slot.pan, slot.bus);
+    if let Some(voice) = slot.voice.as_mut() {
+        let _ = voice.set_volume(left, right);
+    }
+}
+
+fn volumes(volume: f32, pan: f32, bus: Bus) -> (u8, u8) {
+    let volume = volume * EFFECTS_VOLUME.get() * Mixer::gain(bus);
+    let volume = volume.max(0.0).min(1.0) * 255.0;
+    let pan = pan.max(-1.0).min(1.0);
+    let left = volume * (1.0 - pan).min(1.0);
+    let right = volume * (1.0 + pan).min(1.0);
+    (left as u8, right as u8)
+}
+
+/// Sound effects mixed by `Asnd`, and music on a channel of its own.
+///
+/// Up to 15 sound effects play at the same time, each on its own voice, next to
+/// the music played by `Mp3Player`. Tracks streamed by `Music` take one voice each.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// const MUSIC: &[u8] = include_bytes!("music.mp3");
+/// const JUMP: &[u8] = include_bytes!("jump.pcm");
+///
+/// struct Game {
+///     jump: Sound,
+/// }
+///
+/// impl State for Game {
+///     fn init() {
+///         Audio::play_music(MUSIC, true);
+///     }
+///
+///     fn update(&mut self) {
+///         if InputFrame::pad_down(Controller::One).contains(Buttons::A) {
+///             let _ = Audio::play_with(&self.jump, PlayOptions::new().pitch(1.2));
+///         }
+///     }
+/// }
+///
+/// let game = Game {
+///     jump: Sound::new(JUMP, VoiceFormat::Mono16Bit, 22050),
+/// };
+/// ```
+pub struct Audio;
+
+impl Audio {
+    /// Keeps the voice of `Mp3Player` away from the sound effects and streamed music.
+    pub(crate) fn init() {
+        MP3_VOICE.replace(Voice::claim_index(MUSIC_VOICE).ok());
+    }
+
+    /// Plays `sound` once, at full volume.
+    pub fn play(sound: &Sound) -> Result<SoundHandle> {
+        Self::play_with(sound, PlayOptions::new())
+    }
+
+    /// Plays `sound` on the first free voice. Fails if every voice is busy.
+    pub fn play_with(sound: &Sound, options: PlayOptions) -> Result<SoundHandle> {
+        Self::play_spatial(sound, options, 1.0)
+    }
+
+    /// `Audio::play_with`, attenuated by `gain` from the start, see `Emitter`.
+    pub(crate) fn play_spatial(
+        sound: &Sound,
+        options: PlayOptions,
+        gain: f32,
+    ) -> Result<SoundHandle> {
+        let mut voice = Voice::claim()
+            .map_err(|_| OgcError::Audio("Audio::play() found no free voice".into()))?;
+        let processed = Mixer::prepared(options.bus, sound, options.looping);
+        let sound = processed.as_ref().unwrap_or(sound);
+        let (left, right) = volumes(options.volume * gain, options.pan, options.bus);
+        let voice_options = VoiceOptions::new()
+            .format(sound.format)
+            .pitch(voice_pitch(sound, options.pitch))
+            .volume_left(left)
+            .volume_right(right);
+
+        let buffer = sound.buffer.clone();
+        if options.looping {
+            voice.play_looping(voice_options, buffer)?;
+        } else {
+            voice.play(voice_options, buffer)?;
+        }
+
+        let index = voice.index();
+        let generation = SLOTS.with_mut(|slots| {
+            let slot = &mut slots[index as usize];
+            slot.voice = Some(voice);
+            slot.sound = Some(sound.clone());
+            slot.generation = slot.generation.wrapping_add(1);
+            slot.volume = options.volume;
+            slot.pan = options.pan;
+            slot.gain = gain;
+            slot.bus = options.bus;
+            slot.fade = None;
+            slot.generation
+        });
+
+        Ok(SoundHandle {
+            voice: index,
+            generation,
+        })
+    }
+
+    /// Stops every sound effect. The music keeps playing.
+    pub fn stop_all() {
+        SLOTS.with_mut(|slots| slots.iter_mut().for_each(Slot::release));
+    }
+
+    /// Volume every sound effect is scaled by, in `0.0..=1.0`.
+    pub fn set_effects_volume(volume: f32) {
+        EFFECTS_VOLUME.set(volume);
+        SLOTS.with_mut(|slots| slots.iter_mut().for_each(apply_volume));
+    }
+
+    /// Plays an MP3 as music, replacing the current one. With `looping`, it restarts
+    /// whenever it ends, until stopped.
+    pub fn play_music(mp3: &'static [u8], looping: bool) {
+        MUSIC.set(Some(mp3));
+        MUSIC_LOOPING.set(looping);
+        Mp3Player::stop();
+        Mp3Player::play_buffer(mp3);
+    }
+
+    pub fn stop_music() {
+        MUSIC.set(None);
+        Mp3Player::stop();
+    }
+
+    pub fn is_music_playing() -> bool {
+        Mp3Player::is_playing()
+    }
+
+    /// Volume of the music, in `0.0..=1.0`.
+    pub fn set_music_volume(volume: f32) {
+        MUSIC_VOLUME.set(volume);
+        Self::apply_music_volume();
+    }
+
+    fn apply_music_volume() {
+        let volume = MUSIC_VOLUME.get() * Mixer::gain(Bus::Music);
+        Mp3Player::volume((volume.max(0.0).min(1.0) * 255.0) as u8);
+    }
+
+    /// Whether a sound plays on `bus`.
+    pub(crate) fn is_bus_playing(bus: Bus) -> bool {
+        SLOTS.with(|slots| {
+            slots
+                .iter()
+                .any(|slot| slot.voice.is_some() && slot.bus == bus)
+        })
+    }
+
+    /// Follows changes to the volume of the buses.
+    pub(crate) fn apply_mixer() {
+        SLOTS.with_mut(|slots| slots.iter_mut().for_each(apply_volume));
+        Self::apply_music_volume();
+    }
+
+    /// Advances fades, releases the voices of finished sounds and loops the music.
+    pub(crate) fn update() {
+        let now = System::get_time();
+
+        SLOTS.with_mut(|slots| {
+            for slot in slots.iter_mut() {
+                match slot.voice.as_ref() {
+                    Some(voice) if !voice.is_finished() => {}
+                    _ => {
+                        slot.release();
+                        continue;
+                    }
+                }
+
+                if let Some(fade) = slot.fade.as_ref() {
+                    let (volume, done) = fade.volume(now);
+                    slot.volume = volume;
+                    if done {
+                        if fade.stop {
+                            slot.release();
+                            continue;
+                        }
+                        slot.fade = None;
+                    }
+                    apply_volume(slot);
+                }
+            }
+        });
+
+        if let (Some(mp3), true) = (MUSIC.get(), MUSIC_LOOPING.get()) {
+            if !Mp3Player::is_playing() {
+                Mp3Player::play_buffer(mp3);
+            }
+        }
+    }
+}
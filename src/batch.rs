@@ -0,0 +1,79 @@
+use crate::display::Display;
+use embedded_graphics::prelude::Point;
+use ogc::prelude::*;
+
+/// Static geometry recorded once into a display list and replayed every frame.
+///
+/// Only drawing that goes through the graphics processor is recorded, such as
+/// `Display::fill_solid` and `Display::clear`. Individual pixels are written
+/// directly to the framebuffer and are lost.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Game {
+///     level: Option<StaticBatch>,
+/// }
+///
+/// impl State for Game {
+///     fn setup(&mut self, display: &mut Display) {
+///         let level = StaticBatch::new(display, 16 * 1024, |display| {
+///             let floor = Rectangle::new(Point::new(0, 400), Size::new(640, 128));
+///             display.fill_solid(&floor, Rgb::GREEN)
+///         });
+///         self.level = level.ok();
+///     }
+///
+///     fn draw(&self, display: &mut Display) -> Result<(), DrawError> {
+///         if let Some(level) = &self.level {
+///             level.draw_at(display, Point::new(-10, 0));
+///         }
+///
+///         Ok(())
+///     }
+/// }
+/// ```
+pub struct StaticBatch {
+    list: DisplayList,
+}
+
+impl StaticBatch {
+    /// Records everything `draw` submits into a buffer of `capacity` bytes.
+    pub fn new<F>(display: &mut Display, capacity: usize, draw: F) -> ogc::Result<Self>
+    where
+        F: FnOnce(&mut Display) -> Result<(), crate::DrawError>,
+    {
+        let mut list = DisplayList::new(capacity);
+        list.record(|| draw(display).expect("Error occured while recording"))?;
+
+        Ok(Self { list })
+    }
+
+    /// Replays the batch with `transform` applied.
+    pub fn draw(&self, display: &mut Display, transform: &mut Mtx34) {
+        display.set_transform(transform);
+        self.list.call();
+        display.reset_transform();
+    }
+
+    /// Replays the batch moved by `offset`.
+    pub fn draw_at(&self, display: &mut Display, offset: Point) {
+        let mut transform: Mtx34 = [[0.0; 4]; 3];
+        Gu::mtx_identity(&mut transform);
+        Gu::mtx_trans_apply(
+            &mut transform.clone(),
+            &mut transform,
+            offset.x as _,
+            offset.y as _,
+            0.0,
+        );
+        self.draw(display, &mut transform);
+    }
+
+    /// Size of the recorded commands, in bytes.
+    pub fn size(&self) -> u32 {
+        self.list.size()
+    }
+}
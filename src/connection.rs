@@ -0,0 +1,99 @@
+use crate::{engine::State, frame::InputFrame};
+use ogc::prelude::*;
+
+/// A controller slot.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Device {
+    /// A GameCube controller port.
+    Pad(Controller),
+    /// A Wii Remote channel.
+    Remote(Remote),
+}
+
+/// A controller being plugged in or removed, see `State::connection`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConnectionEvent {
+    Connected(Device),
+    Disconnected(Device),
+}
+
+impl ConnectionEvent {
+    pub fn device(&self) -> Device {
+        match *self {
+            ConnectionEvent::Connected(device) | ConnectionEvent::Disconnected(device) => device,
+        }
+    }
+}
+
+/// Connection status of every controller slot, as recorded in the `InputFrame`s, so
+/// that replays report the controllers of the recording.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Game {
+///     paused: bool,
+/// }
+///
+/// impl State for Game {
+///     fn connection(&mut self, event: ConnectionEvent) {
+///         if event == ConnectionEvent::Disconnected(Device::Pad(Controller::One)) {
+///             self.paused = true;
+///         }
+///     }
+///
+///     fn update(&mut self) {
+///         if self.paused && Connections::is_connected(Device::Pad(Controller::One)) {
+///             // Show "press start to resume"
+///         }
+///     }
+/// }
+/// ```
+pub struct Connections;
+
+impl Connections {
+    /// Returns true if a controller is plugged into `device`.
+    pub fn is_connected(device: Device) -> bool {
+        connected(&InputFrame::current(), device)
+    }
+
+    /// Number of connected GameCube controllers and Wii Remotes.
+    pub fn count() -> u32 {
+        let frame = InputFrame::current();
+        let pads = frame.pads.iter().filter(|pad| pad.connected).count();
+        let remotes = frame
+            .remotes
+            .iter()
+            .filter(|remote| remote.connected)
+            .count();
+        (pads + remotes) as u32
+    }
+
+    /// Compares the slots of the current frame against the previous one and reports every
+    /// change to `state`. On the first frame, every controller that is already plugged in
+    /// is reported as connected.
+    pub(crate) fn update<T: State>(state: &mut T) {
+        let (current, previous) = (InputFrame::current(), InputFrame::previous());
+        let devices = Controller::ALL
+            .iter()
+            .map(|&pad| Device::Pad(pad))
+            .chain(Remote::ALL.iter().map(|&remote| Device::Remote(remote)));
+
+        for device in devices {
+            match (connected(&previous, device), connected(&current, device)) {
+                (false, true) => state.connection(ConnectionEvent::Connected(device)),
+                (true, false) => state.connection(ConnectionEvent::Disconnected(device)),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn connected(frame: &InputFrame, device: Device) -> bool {
+    match device {
+        Device::Pad(pad) => frame.pad(pad).connected,
+        Device::Remote(remote) => frame.remote(remote).connected,
+    }
+}
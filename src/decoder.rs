@@ -0,0 +1,17 @@
+use ogc::Result;
+
+/// Decodes music into 16-bit PCM for `Music`.
+pub trait Decoder {
+    /// Number of channels, 1 or 2.
+    fn channels(&self) -> u16;
+
+    /// Sample rate, in Hz.
+    fn rate(&self) -> u32;
+
+    /// Decodes into `samples`, interleaved if stereo, and returns how many samples were
+    /// written. Returns 0 at the end of the track.
+    fn decode(&mut self, samples: &mut [i16]) -> Result<usize>;
+
+    /// Moves to sample `frame` of every channel.
+    fn seek(&mut self, frame: u64) -> Result<()>;
+}
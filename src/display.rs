@@ -1,5 +1,5 @@
 extern crate alloc;
-use core::{convert::TryInto, ffi::c_void};
+use core::{convert::TryInto, ffi::c_void, ptr, slice};
 
 use embedded_graphics::{
     draw_target::DrawTarget,
@@ -9,41 +9,49 @@ use embedded_graphics::{
     Pixel,
 };
 
-use ogc::{
-    ffi::{
-        Mtx as Mtx34, Mtx44, GX_ALWAYS, GX_AOP_AND, GX_BL_INVSRCALPHA, GX_BL_SRCALPHA, GX_BM_BLEND,
-        GX_CLIP_ENABLE, GX_CLR_RGBA, GX_COLOR0A0, GX_CULL_NONE, GX_DIRECT, GX_F32, GX_GM_1_0,
-        GX_GREATER, GX_LEQUAL, GX_LO_CLEAR, GX_MAX_Z24, GX_NONE, GX_ORTHOGRAPHIC, GX_PASSCLR,
-        GX_PF_RGB8_Z24, GX_PNMTX0, GX_POS_XYZ, GX_QUADS, GX_RGBA8, GX_TEVSTAGE0, GX_TEXCOORD0,
-        GX_TEXMAP0, GX_TEX_ST, GX_TRIANGLES, GX_TRUE, GX_VA_CLR0, GX_VA_POS, GX_VA_TEX0,
-        GX_VTXFMT0, GX_ZC_LINEAR,
-    },
-    prelude::*,
-};
+use ogc::{prelude::*, OgcError};
+
+use crate::screenshot::{self, ImageFormat};
 
 /// Underlying implementation for manipulating the screen via `ogc-rs`.
-pub struct Display;
+pub struct Display {
+    format: VertexFormat,
+    framebuffer: *mut c_void,
+    framebuffer_size: (u16, u16),
+}
 
 impl Display {
     pub fn new(fifo_size: usize) -> Self {
         let buffer = gp_fifo(fifo_size);
         Gx::init(buffer, fifo_size as u32);
-        Self
+        let format = VertexFormat::new(VtxFmt::Fmt0)
+            .position(
+                AttrType::Direct,
+                PositionFormat::Xyz,
+                ComponentFormat::F32,
+                0,
+            )
+            .color(VtxAttr::Color0, AttrType::Direct, ColorFormat::Rgba8);
+        Self {
+            format,
+            framebuffer: ptr::null_mut(),
+            framebuffer_size: (0, 0),
+        }
     }
 
-    pub fn flush(&self, framebuffer: *mut c_void) {
+    pub fn flush(&mut self, framebuffer: *mut c_void) {
         Gx::draw_done();
-        Gx::set_z_mode(GX_TRUE as _, GX_LEQUAL as _, GX_TRUE as _);
-        Gx::copy_disp(framebuffer, GX_TRUE as _);
+        Gx::set_z_mode(true, CompareFn::LessEqual, true);
+        Gx::copy_disp(framebuffer, true);
+        self.framebuffer = framebuffer;
     }
 
-    pub fn setup(&self, rc: &mut RenderConfig) {
-        let mut ident: Mtx34 = [[0.0; 4]; 3];
+    pub fn setup(&mut self, rc: &mut RenderConfig) {
         let mut perspective: Mtx44 = [[0.0; 4]; 4];
 
         let color = Color::new(0, 0, 0, 0);
-        Gx::set_copy_clear(color, GX_MAX_Z24);
-        Gx::set_pixel_fmt(GX_PF_RGB8_Z24 as _, GX_ZC_LINEAR as _);
+        Gx::set_copy_clear(color, MAX_Z24);
+        Gx::set_pixel_fmt(PixelFormat::Rgb8Z24, ZCompress::Linear);
 
         let fb_width = rc.framebuffer_width;
         let emb_height = rc.embed_framebuffer_height;
@@ -58,55 +66,40 @@ impl Display {
 
         Gx::set_disp_copy_src(0, 0, fb_width, emb_height);
         Gx::set_disp_copy_dst(fb_width, ext_fb_height as _);
+        self.framebuffer_size = (fb_width, ext_fb_height as _);
 
         Gx::set_copy_filter(
             rc.anti_aliasing,
             &mut rc.sample_pattern,
-            GX_TRUE as _,
+            true,
             &mut rc.v_filter,
         );
 
         Gx::set_field_mode(rc.field_rendering, half_aspect_ratio as _);
-        Gx::set_disp_copy_gamma(GX_GM_1_0 as _);
+        Gx::set_disp_copy_gamma(Gamma::One);
 
-        Gx::clear_vtx_desc();
         Gx::inv_vtx_cache();
         Gx::invalidate_tex_all();
 
-        Gx::set_vtx_desc(GX_VA_TEX0 as _, GX_NONE as _);
-        Gx::set_vtx_desc(GX_VA_POS as _, GX_DIRECT as _);
-        Gx::set_vtx_desc(GX_VA_CLR0 as _, GX_DIRECT as _);
-
+        self.format.apply();
         Gx::set_vtx_attr_fmt(
-            GX_VTXFMT0 as _,
-            GX_VA_POS as _,
-            GX_POS_XYZ as _,
-            GX_F32 as _,
-            0,
+            VtxFmt::Fmt0,
+            VtxAttr::Tex0,
+            AttrFormat::TexCoord(TexFormat::St, ComponentFormat::F32, 0),
         );
-        Gx::set_vtx_attr_fmt(GX_VTXFMT0 as _, GX_VA_TEX0, GX_TEX_ST as _, GX_F32 as _, 0);
-        Gx::set_vtx_attr_fmt(
-            GX_VTXFMT0 as _,
-            GX_VA_CLR0,
-            GX_CLR_RGBA as _,
-            GX_RGBA8 as _,
-            0,
-        );
-        Gx::set_z_mode(GX_TRUE as _, GX_LEQUAL as _, GX_TRUE as _);
+        Gx::set_z_mode(true, CompareFn::LessEqual, true);
 
         Gx::set_num_chans(1);
         Gx::set_num_tex_gens(1);
-        Gx::set_tev_op(GX_TEVSTAGE0 as _, GX_PASSCLR as _);
+        Gx::set_tev_op(TevStage::Stage0, TevMode::PassColor);
         Gx::set_tev_order(
-            GX_TEVSTAGE0 as _,
-            GX_TEXCOORD0 as _,
-            GX_TEXMAP0 as _,
-            GX_COLOR0A0 as _,
+            TevStage::Stage0,
+            TexCoordId::Coord0,
+            TexMapId::Map0,
+            ChannelId::Color0Alpha0,
         );
 
-        Gu::mtx_identity(&mut ident);
-        Gu::mtx_trans_apply(&mut ident.clone(), &mut ident, 0.0, 0.0, -100.0);
-        Gx::load_pos_mtx_imm(&mut ident, GX_PNMTX0 as _);
+        Gx::load_pos_mtx_imm(&mut view_matrix(), PosMtx::Mtx0);
 
         Gu::ortho(
             &mut perspective,
@@ -117,24 +110,56 @@ impl Display {
             0.0,
             1000.0,
         );
-        Gx::load_projection_mtx(&mut perspective, GX_ORTHOGRAPHIC as _);
+        Gx::load_projection_mtx(&mut perspective, ProjectionType::Orthographic);
 
         Gx::set_viewport(0.0, 0.0, fb_width as _, emb_height as _, 0.0, 1.0);
         Gx::set_blend_mode(
-            GX_BM_BLEND as _,
-            GX_BL_SRCALPHA as _,
-            GX_BL_INVSRCALPHA as _,
-            GX_LO_CLEAR as _,
+            BlendMode::Blend,
+            BlendFactor::SrcAlpha,
+            BlendFactor::InvSrcAlpha,
+            LogicOp::Clear,
         );
 
-        Gx::set_alpha_update(GX_TRUE as _);
-        Gx::set_alpha_compare(GX_GREATER as _, 0, GX_AOP_AND as _, GX_ALWAYS as _, 0);
-        Gx::set_color_update(GX_TRUE as _);
-        Gx::set_cull_mode(GX_CULL_NONE as _);
-        Gx::set_clip_mode(GX_CLIP_ENABLE as _);
+        Gx::set_alpha_update(true);
+        Gx::set_alpha_compare(CompareFn::Greater, 0, AlphaOp::And, CompareFn::Always, 0);
+        Gx::set_color_update(true);
+        Gx::set_cull_mode(CullMode::None);
+        Gx::set_clip_mode(ClipMode::Enable);
 
         Gx::set_scissor(0, 0, fb_width as _, emb_height as _);
     }
+
+    /// Applies `transform` to everything drawn until `reset_transform` is called.
+    pub fn set_transform(&mut self, transform: &mut Mtx34) {
+        let mut modelview: Mtx34 = [[0.0; 4]; 3];
+        Gu::mtx_concat(&mut view_matrix(), transform, &mut modelview);
+        Gx::load_pos_mtx_imm(&mut modelview, PosMtx::Mtx0);
+    }
+
+    /// Restores the default transform set up by `setup`.
+    pub fn reset_transform(&mut self) {
+        Gx::load_pos_mtx_imm(&mut view_matrix(), PosMtx::Mtx0);
+    }
+
+    /// Saves the last frame copied to the external framebuffer as an image at `path`.
+    /// Fails before the first frame was flushed.
+    pub fn screenshot(&self, path: &str, format: ImageFormat) -> ogc::Result<()> {
+        if self.framebuffer.is_null() {
+            return Err(OgcError::Gx(
+                "Display::screenshot() found no flushed frame".into(),
+            ));
+        }
+
+        let (width, height) = self.framebuffer_size;
+        let words = width as usize / 2 * height as usize;
+        // The copy to the external framebuffer bypasses the CPU caches
+        System::invalidate_data_cache(self.framebuffer, words * 4);
+        let xfb = unsafe { slice::from_raw_parts(self.framebuffer as *const u32, words) };
+
+        let rgb = screenshot::yuyv_to_rgb(xfb);
+        let image = format.encode(width as _, height as _, &rgb);
+        File::create(path)?.write_all(&image)
+    }
 }
 
 impl DrawTarget for Display {
@@ -161,16 +186,15 @@ impl DrawTarget for Display {
         let (top_x, top_y) = (area.top_left.x as f32, area.top_left.y as f32);
         let (bottom_x, bottom_y) = (bottom.x as f32, bottom.y as f32);
 
-        Gx::begin(GX_QUADS as _, GX_VTXFMT0 as _, 4);
-        Gx::position_3f32(top_x, top_y, 0.0);
-        Gx::color_1u32(color);
-        Gx::position_3f32(bottom_x, top_y, 0.0);
-        Gx::color_1u32(color);
-        Gx::position_3f32(bottom_x, bottom_y, 0.0);
-        Gx::color_1u32(color);
-        Gx::position_3f32(top_x, bottom_y, 0.0);
-        Gx::color_1u32(color);
-        Gx::end();
+        let mut quad = Gx::begin(Primitive::Quads, &self.format, 4);
+        quad.position_3f32(top_x, top_y, 0.0);
+        quad.color_1u32(color);
+        quad.position_3f32(bottom_x, top_y, 0.0);
+        quad.color_1u32(color);
+        quad.position_3f32(bottom_x, bottom_y, 0.0);
+        quad.color_1u32(color);
+        quad.position_3f32(top_x, bottom_y, 0.0);
+        quad.color_1u32(color);
 
         Ok(())
     }
@@ -183,27 +207,31 @@ impl OriginDimensions for Display {
 }
 
 impl Display {
-    #[allow(dead_code)]
-    fn fill_triangle(
+    pub(crate) fn fill_triangle(
         &mut self,
         area: &Triangle,
         color: Rgb888,
     ) -> Result<(), crate::DrawError> {
         let color = gx_color(color);
 
-        Gx::begin(GX_TRIANGLES as _, GX_VTXFMT0 as _, 3);
+        let mut triangle = Gx::begin(Primitive::Triangles, &self.format, 3);
 
         for vertex in area.vertices {
-            Gx::position_3f32(vertex.x as _, vertex.y as _, 0.0);
-            Gx::color_1u32(color);
+            triangle.position_3f32(vertex.x as _, vertex.y as _, 0.0);
+            triangle.color_1u32(color);
         }
 
-        Gx::end();
-
         Ok(())
     }
 }
 
+fn view_matrix() -> Mtx34 {
+    let mut view: Mtx34 = [[0.0; 4]; 3];
+    Gu::mtx_identity(&mut view);
+    Gu::mtx_trans_apply(&mut view.clone(), &mut view, 0.0, 0.0, -100.0);
+    view
+}
+
 fn gx_color(color: Rgb888) -> u32 {
     // color.into_storage()
     // Turns 0x00RRGGBB => 0xRRGGBBFF
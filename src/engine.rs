@@ -1,7 +1,37 @@
 extern crate alloc;
 
-use crate::display::Display;
-use ogc::{asnd::Asnd, gx::Gx, mp3player::Mp3Player, pad::Pad, video::Video};
+use crate::connection::ConnectionEvent;
+#[cfg(target_os = "rvl-ios")]
+use crate::{
+    audio::Audio,
+    display::Display,
+    frame::InputFrame,
+    mixer::Mixer,
+    music::Music,
+    pointer::Pointer,
+    random::Random,
+    replay::Replay,
+    rumble::Rumble,
+    spatial::Listener,
+    stats::{self, FrameTimer, Stats},
+    tracker::{Tracker, TrackerEvent},
+    usb::Usb,
+};
+#[cfg(target_os = "rvl-ios")]
+use embedded_graphics::prelude::Size;
+#[cfg(target_os = "rvl-ios")]
+use ogc::{
+    asnd::Asnd,
+    fs::Fs,
+    gx::Gx,
+    keyboard::KeyboardEvent,
+    mouse::MouseEvent,
+    mp3player::Mp3Player,
+    pad::Pad,
+    system::System,
+    video::Video,
+    wpad::{DataFormat, Remote, Wpad},
+};
 
 /// Trait for enabling state.
 ///
@@ -37,22 +67,47 @@ use ogc::{asnd::Asnd, gx::Gx, mp3player::Mp3Player, pad::Pad, video::Video};
 /// ```
 pub trait State {
     fn init() {}
+    /// Called once after the display is set up, before the first frame.
+    #[cfg(target_os = "rvl-ios")]
+    fn setup(&mut self, _display: &mut Display) {}
+    #[cfg(target_os = "rvl-ios")]
     fn draw(&self, _display: &mut Display) -> Result<(), crate::DrawError> {
         Ok(())
     }
     fn update(&mut self) {}
+    /// Called before `update` whenever a controller is plugged in or removed.
+    fn connection(&mut self, _event: ConnectionEvent) {}
+    /// Called before `update` for every key pressed or released on a USB keyboard.
+    #[cfg(target_os = "rvl-ios")]
+    fn key(&mut self, _event: KeyboardEvent) {}
+    /// Called before `update` for every character typed on a USB keyboard.
+    fn text(&mut self, _character: char) {}
+    /// Called before `update` for every report of a USB mouse.
+    #[cfg(target_os = "rvl-ios")]
+    fn mouse(&mut self, _event: MouseEvent) {}
+    /// Called before `update` for every row, sync effect and loop of the module
+    /// played by `Tracker`, as it is heard.
+    #[cfg(target_os = "rvl-ios")]
+    fn tracker(&mut self, _event: TrackerEvent) {}
 }
 
 /// Game engine abstraction.
+#[cfg(target_os = "rvl-ios")]
 pub struct Engine;
 
+#[cfg(target_os = "rvl-ios")]
 impl Engine {
     pub fn run<T: State>(mut state: T) -> ! {
         // Init
         let mut video = Video::init();
         Asnd::init();
         Mp3Player::init();
+        Audio::init();
+        Asnd::pause(false);
         Pad::init();
+        Wpad::init();
+        // Without an SD card or USB storage, only opening files fails
+        let _ = Fs::mount();
 
         Video::configure(Video::get_preferred_mode().into());
         Video::set_next_framebuffer(video.framebuffer);
@@ -66,19 +121,58 @@ impl Engine {
         let fb_width = video.render_config.framebuffer_width as _;
         let emb_height = video.render_config.embed_framebuffer_height as _;
 
+        for remote in Remote::ALL {
+            Wpad::set_data_format(remote, DataFormat::ButtonsAccelIr)
+                .expect("Error occured while configuring remotes");
+            Wpad::set_vres(remote, fb_width as _, emb_height as _)
+                .expect("Error occured while configuring remotes");
+        }
+
+        Pointer::set_bounds(Size::new(
+            video.render_config.framebuffer_width as _,
+            video.render_config.embed_framebuffer_height as _,
+        ));
+        Random::set_seed(System::get_time());
+
         T::init();
+        state.setup(&mut display);
+
+        let mut timer = FrameTimer::new();
 
         loop {
+            timer.start();
             Gx::set_viewport(0.0, 0.0, fb_width, emb_height, 0.0, 0.0);
-            Pad::scan_pads();
+            let pads = Pad::scan_pads();
+            Wpad::scan_pads();
+            Replay::advance(&mut state, || InputFrame::capture(pads));
+            Usb::update(&mut state);
+            Rumble::update();
+            Audio::update();
+            Listener::update();
+            Mixer::update();
+            Music::update();
+            Tracker::update(&mut state);
+            Stats::handle_combo();
 
             // Update
             state.update();
+            timer.update_done();
 
             // Draw
             state
                 .draw(&mut display)
                 .expect("Error occured while drawing");
+            if Pointer::are_cursors_visible() {
+                Pointer::draw_cursors(&mut display).expect("Error occured while drawing");
+            }
+            timer.draw_done();
+            Gx::draw_done();
+            let frame_stats = timer.gpu_done();
+            Stats::record(frame_stats);
+
+            if Stats::is_overlay_visible() {
+                stats::draw_overlay(&mut display, &frame_stats);
+            }
             display.flush(video.framebuffer);
 
             Video::set_next_framebuffer(video.framebuffer);
@@ -0,0 +1,365 @@
+#[cfg(target_os = "rvl-ios")]
+use crate::pointer::Pointer;
+use crate::{analog::StickVector, global::Global, history::History};
+use ogc::prelude::*;
+
+/// Snapshot of a GameCube controller, as clamped by `Pad::scan_pads`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PadFrame {
+    /// Buttons held down.
+    pub buttons: Buttons,
+    pub stick_x: i8,
+    pub stick_y: i8,
+    pub sub_stick_x: i8,
+    pub sub_stick_y: i8,
+    pub trigger_l: u8,
+    pub trigger_r: u8,
+    /// A controller is plugged into the port.
+    pub connected: bool,
+}
+
+impl PadFrame {
+    const SIZE: usize = 9;
+
+    #[cfg(target_os = "rvl-ios")]
+    fn capture(controller: Controller, pads: u32) -> Self {
+        Self {
+            buttons: Pad::buttons_held(controller),
+            stick_x: Pad::stick_x(controller),
+            stick_y: Pad::stick_y(controller),
+            sub_stick_x: Pad::sub_stick_x(controller),
+            sub_stick_y: Pad::sub_stick_y(controller),
+            trigger_l: Pad::trigger_l(controller),
+            trigger_r: Pad::trigger_r(controller),
+            connected: pads & 1 << controller as u32 != 0,
+        }
+    }
+
+    /// Control stick, normalized.
+    pub fn stick(&self) -> StickVector {
+        StickVector::from_raw(self.stick_x, self.stick_y, STICK_MAX)
+    }
+
+    /// C-stick, normalized.
+    pub fn sub_stick(&self) -> StickVector {
+        StickVector::from_raw(self.sub_stick_x, self.sub_stick_y, SUB_STICK_MAX)
+    }
+
+    fn encode(&self, out: &mut [u8]) {
+        out[0..2].copy_from_slice(&self.buttons.bits().to_be_bytes());
+        out[2] = self.stick_x as u8;
+        out[3] = self.stick_y as u8;
+        out[4] = self.sub_stick_x as u8;
+        out[5] = self.sub_stick_y as u8;
+        out[6] = self.trigger_l;
+        out[7] = self.trigger_r;
+        out[8] = self.connected as u8;
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        Self {
+            buttons: Buttons::from_bits_truncate(u16::from_be_bytes([bytes[0], bytes[1]])),
+            stick_x: bytes[2] as i8,
+            stick_y: bytes[3] as i8,
+            sub_stick_x: bytes[4] as i8,
+            sub_stick_y: bytes[5] as i8,
+            trigger_l: bytes[6],
+            trigger_r: bytes[7],
+            connected: bytes[8] != 0,
+        }
+    }
+}
+
+/// Snapshot of the controller attached to a Wii Remote.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ExpansionFrame {
+    #[default]
+    None,
+    Nunchuk {
+        stick: StickVector,
+    },
+    Classic {
+        left_stick: StickVector,
+        right_stick: StickVector,
+        /// In `0.0..=1.0`.
+        left_trigger: f32,
+        right_trigger: f32,
+    },
+}
+
+/// Snapshot of a Wii Remote.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RemoteFrame {
+    /// Buttons held down, including those of the expansion.
+    pub buttons: WpadButtons,
+    /// Position the remote points at on screen, if it sees the sensor bar.
+    pub pointer: Option<(f32, f32)>,
+    pub expansion: ExpansionFrame,
+    /// The remote is paired and connected.
+    pub connected: bool,
+}
+
+impl RemoteFrame {
+    const SIZE: usize = 19;
+
+    #[cfg(target_os = "rvl-ios")]
+    fn capture(remote: Remote) -> Self {
+        let ir = Wpad::ir(remote);
+        let expansion = match Wpad::expansion(remote) {
+            Expansion::Nunchuk(nunchuk) => ExpansionFrame::Nunchuk {
+                stick: StickVector::new(nunchuk.stick.x, nunchuk.stick.y),
+            },
+            Expansion::Classic(classic) => ExpansionFrame::Classic {
+                left_stick: StickVector::new(classic.left_stick.x, classic.left_stick.y),
+                right_stick: StickVector::new(classic.right_stick.x, classic.right_stick.y),
+                left_trigger: classic.left_trigger,
+                right_trigger: classic.right_trigger,
+            },
+            _ => ExpansionFrame::None,
+        };
+
+        Self {
+            buttons: Wpad::buttons_held(remote),
+            pointer: if ir.valid { Some((ir.x, ir.y)) } else { None },
+            expansion,
+            connected: Wpad::is_connected(remote),
+        }
+    }
+
+    // Layout: buttons (4), flags (1), pointer x and y (4 + 4), expansion (6).
+    // Flags: pointer (bit 0), expansion type (bits 1 and 2), connected (bit 3).
+    // Expansion sticks and triggers are quantized to a byte.
+    fn encode(&self, out: &mut [u8]) {
+        out.fill(0);
+        out[0..4].copy_from_slice(&self.buttons.bits().to_be_bytes());
+
+        let (x, y) = self.pointer.unwrap_or((0.0, 0.0));
+        out[4] = self.pointer.is_some() as u8 | (self.connected as u8) << 3;
+        out[5..9].copy_from_slice(&x.to_bits().to_be_bytes());
+        out[9..13].copy_from_slice(&y.to_bits().to_be_bytes());
+
+        let axis = |value: f32| (libm::roundf(value.clamp(-1.0, 1.0) * 127.0) as i8) as u8;
+        let trigger = |value: f32| libm::roundf(value.clamp(0.0, 1.0) * 255.0) as u8;
+        match self.expansion {
+            ExpansionFrame::None => {}
+            ExpansionFrame::Nunchuk { stick } => {
+                out[4] |= 1 << 1;
+                out[13] = axis(stick.x);
+                out[14] = axis(stick.y);
+            }
+            ExpansionFrame::Classic {
+                left_stick,
+                right_stick,
+                left_trigger,
+                right_trigger,
+            } => {
+                out[4] |= 2 << 1;
+                out[13] = axis(left_stick.x);
+                out[14] = axis(left_stick.y);
+                out[15] = axis(right_stick.x);
+                out[16] = axis(right_stick.y);
+                out[17] = trigger(left_trigger);
+                out[18] = trigger(right_trigger);
+            }
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let word =
+            |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let axis = |i: usize| bytes[i] as i8 as f32 / 127.0;
+        let trigger = |i: usize| bytes[i] as f32 / 255.0;
+
+        let pointer = if bytes[4] & 1 != 0 {
+            Some((f32::from_bits(word(5)), f32::from_bits(word(9))))
+        } else {
+            None
+        };
+        let expansion = match (bytes[4] >> 1) & 3 {
+            1 => ExpansionFrame::Nunchuk {
+                stick: StickVector::new(axis(13), axis(14)),
+            },
+            2 => ExpansionFrame::Classic {
+                left_stick: StickVector::new(axis(13), axis(14)),
+                right_stick: StickVector::new(axis(15), axis(16)),
+                left_trigger: trigger(17),
+                right_trigger: trigger(18),
+            },
+            _ => ExpansionFrame::None,
+        };
+
+        Self {
+            buttons: WpadButtons::from_bits_truncate(word(0)),
+            pointer,
+            expansion,
+            connected: bytes[4] & 1 << 3 != 0,
+        }
+    }
+}
+
+/// Snapshot of every controller during a single frame.
+///
+/// `Engine::run` takes one after scanning the controllers, or reads it from a
+/// replay, see `Replay`. Reading input through it instead of `Pad` and `Wpad`
+/// keeps a game deterministic under replays.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct InputFrame {
+    pub pads: [PadFrame; 4],
+    pub remotes: [RemoteFrame; 4],
+}
+
+/// Size of an encoded `InputFrame`, in bytes.
+pub const FRAME_SIZE: usize = 4 * PadFrame::SIZE + 4 * RemoteFrame::SIZE;
+
+static CURRENT: Global<InputFrame> = Global::new(InputFrame::EMPTY);
+static PREVIOUS: Global<InputFrame> = Global::new(InputFrame::EMPTY);
+
+impl InputFrame {
+    const EMPTY_PAD: PadFrame = PadFrame {
+        buttons: Buttons::empty(),
+        stick_x: 0,
+        stick_y: 0,
+        sub_stick_x: 0,
+        sub_stick_y: 0,
+        trigger_l: 0,
+        trigger_r: 0,
+        connected: false,
+    };
+
+    const EMPTY_REMOTE: RemoteFrame = RemoteFrame {
+        buttons: WpadButtons::empty(),
+        pointer: None,
+        expansion: ExpansionFrame::None,
+        connected: false,
+    };
+
+    /// A frame with nothing pressed.
+    pub const EMPTY: InputFrame = InputFrame {
+        pads: [Self::EMPTY_PAD; 4],
+        remotes: [Self::EMPTY_REMOTE; 4],
+    };
+
+    /// Reads every controller. `Pad::scan_pads` and `Wpad::scan_pads` must have been called,
+    /// and `pads` is the mask of connected controllers the former returned.
+    ///
+    /// Values are quantized the same way `encode` stores them, so a replayed frame
+    /// is identical to the captured one.
+    #[cfg(target_os = "rvl-ios")]
+    pub fn capture(pads: u32) -> Self {
+        let mut frame = InputFrame::EMPTY;
+        for controller in Controller::ALL {
+            frame.pads[controller as usize] = PadFrame::capture(controller, pads);
+        }
+        for remote in Remote::ALL {
+            frame.remotes[remote as usize] = RemoteFrame::capture(remote);
+        }
+
+        let mut bytes = [0; FRAME_SIZE];
+        frame.encode(&mut bytes);
+        Self::decode(&bytes)
+    }
+
+    /// Frame the current `State::update` runs with.
+    pub fn current() -> InputFrame {
+        CURRENT.get()
+    }
+
+    /// Frame before the current one.
+    pub fn previous() -> InputFrame {
+        PREVIOUS.get()
+    }
+
+    /// Buttons of `controller` pressed since the previous frame.
+    pub fn pad_down(controller: Controller) -> Buttons {
+        let (current, previous) = Self::pad_pair(controller);
+        current & !previous
+    }
+
+    /// Buttons of `controller` held down.
+    pub fn pad_held(controller: Controller) -> Buttons {
+        Self::pad_pair(controller).0
+    }
+
+    /// Buttons of `controller` released since the previous frame.
+    pub fn pad_up(controller: Controller) -> Buttons {
+        let (current, previous) = Self::pad_pair(controller);
+        previous & !current
+    }
+
+    /// Buttons of `remote` pressed since the previous frame.
+    pub fn remote_down(remote: Remote) -> WpadButtons {
+        let (current, previous) = Self::remote_pair(remote);
+        current & !previous
+    }
+
+    /// Buttons of `remote` held down.
+    pub fn remote_held(remote: Remote) -> WpadButtons {
+        Self::remote_pair(remote).0
+    }
+
+    /// Buttons of `remote` released since the previous frame.
+    pub fn remote_up(remote: Remote) -> WpadButtons {
+        let (current, previous) = Self::remote_pair(remote);
+        previous & !current
+    }
+
+    pub fn pad(&self, controller: Controller) -> &PadFrame {
+        &self.pads[controller as usize]
+    }
+
+    pub fn remote(&self, remote: Remote) -> &RemoteFrame {
+        &self.remotes[remote as usize]
+    }
+
+    /// Writes the frame into `out`, which must be `FRAME_SIZE` bytes long.
+    pub fn encode(&self, out: &mut [u8]) {
+        let (pads, remotes) = out.split_at_mut(4 * PadFrame::SIZE);
+        for (pad, out) in self.pads.iter().zip(pads.chunks_exact_mut(PadFrame::SIZE)) {
+            pad.encode(out);
+        }
+        for (remote, out) in self
+            .remotes
+            .iter()
+            .zip(remotes.chunks_exact_mut(RemoteFrame::SIZE))
+        {
+            remote.encode(out);
+        }
+    }
+
+    /// Reads a frame written by `encode`. `bytes` must be `FRAME_SIZE` bytes long.
+    pub fn decode(bytes: &[u8]) -> Self {
+        let (pads, remotes) = bytes.split_at(4 * PadFrame::SIZE);
+        let mut frame = InputFrame::EMPTY;
+        for (pad, bytes) in frame.pads.iter_mut().zip(pads.chunks_exact(PadFrame::SIZE)) {
+            *pad = PadFrame::decode(bytes);
+        }
+        for (remote, bytes) in frame
+            .remotes
+            .iter_mut()
+            .zip(remotes.chunks_exact(RemoteFrame::SIZE))
+        {
+            *remote = RemoteFrame::decode(bytes);
+        }
+        frame
+    }
+
+    /// Makes `frame` the current one, adds it to the `History` and moves the `Pointer`.
+    pub(crate) fn advance(frame: InputFrame) {
+        PREVIOUS.set(CURRENT.replace(frame));
+        History::record(&frame);
+        #[cfg(target_os = "rvl-ios")]
+        Pointer::record(&frame);
+    }
+
+    fn pad_pair(controller: Controller) -> (Buttons, Buttons) {
+        let i = controller as usize;
+        let buttons = |frame: &InputFrame| frame.pads[i].buttons;
+        (CURRENT.with(buttons), PREVIOUS.with(buttons))
+    }
+
+    fn remote_pair(remote: Remote) -> (WpadButtons, WpadButtons) {
+        let i = remote as usize;
+        let buttons = |frame: &InputFrame| frame.remotes[i].buttons;
+        (CURRENT.with(buttons), PREVIOUS.with(buttons))
+    }
+}
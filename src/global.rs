@@ -0,0 +1,42 @@
+use core::cell::RefCell;
+
+/// State of the engine kept in a `static`.
+///
+/// The engine runs on the single thread of the game and the statics are never touched
+/// from interrupt handlers, so a `RefCell` is enough to share them. Access is scoped to a
+/// closure: overlapping mutable access panics instead of aliasing.
+pub(crate) struct Global<T>(RefCell<T>);
+
+// SAFETY: only the thread running the engine accesses the statics, see above.
+unsafe impl<T> Sync for Global<T> {}
+
+impl<T> Global<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self(RefCell::new(value))
+    }
+
+    /// Calls `f` with the value borrowed.
+    pub(crate) fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.0.borrow())
+    }
+
+    /// Calls `f` with the value borrowed mutably.
+    pub(crate) fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.0.borrow_mut())
+    }
+
+    /// Replaces the value, returning the previous one.
+    pub(crate) fn replace(&self, value: T) -> T {
+        self.0.replace(value)
+    }
+}
+
+impl<T: Copy> Global<T> {
+    pub(crate) fn get(&self) -> T {
+        *self.0.borrow()
+    }
+
+    pub(crate) fn set(&self, value: T) {
+        *self.0.borrow_mut() = value;
+    }
+}
@@ -0,0 +1,503 @@
+use crate::{
+    analog::StickVector,
+    connection::Device,
+    frame::{ExpansionFrame, InputFrame},
+    global::Global,
+};
+use ogc::prelude::*;
+
+/// Number of frames kept by `History`, a little over a second at 60 Hz.
+pub const HISTORY_LEN: usize = 64;
+
+/// Deflection a stick needs before `Direction` leaves `Neutral`.
+const DIRECTION_THRESHOLD: f32 = 0.5;
+
+/// Eight-way direction of a stick.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Neutral,
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// Down, down-right, right.
+    pub const QUARTER_CIRCLE_RIGHT: [Direction; 3] =
+        [Direction::Down, Direction::DownRight, Direction::Right];
+
+    /// Down, down-left, left.
+    pub const QUARTER_CIRCLE_LEFT: [Direction; 3] =
+        [Direction::Down, Direction::DownLeft, Direction::Left];
+
+    /// Right, down, down-right.
+    pub const DRAGON_PUNCH_RIGHT: [Direction; 3] =
+        [Direction::Right, Direction::Down, Direction::DownRight];
+
+    /// Left, down, down-left.
+    pub const DRAGON_PUNCH_LEFT: [Direction; 3] =
+        [Direction::Left, Direction::Down, Direction::DownLeft];
+
+    /// Direction of `stick`, in sectors of 45 degrees.
+    pub fn from_stick(stick: StickVector) -> Self {
+        if stick.length() < DIRECTION_THRESHOLD {
+            return Direction::Neutral;
+        }
+
+        const SECTORS: [Direction; 8] = [
+            Direction::Right,
+            Direction::UpRight,
+            Direction::Up,
+            Direction::UpLeft,
+            Direction::Left,
+            Direction::DownLeft,
+            Direction::Down,
+            Direction::DownRight,
+        ];
+        let sector = libm::roundf(stick.angle() / core::f32::consts::FRAC_PI_4) as i32;
+        SECTORS[sector.rem_euclid(8) as usize]
+    }
+
+    /// The same direction, mirrored horizontally. Useful for characters facing left.
+    pub fn mirrored(self) -> Self {
+        match self {
+            Direction::UpRight => Direction::UpLeft,
+            Direction::Right => Direction::Left,
+            Direction::DownRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::DownRight,
+            Direction::Left => Direction::Right,
+            Direction::UpLeft => Direction::UpRight,
+            other => other,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Entry {
+    buttons: u32,
+    direction: Direction,
+}
+
+struct Track {
+    entries: [Entry; HISTORY_LEN],
+    newest: usize,
+    len: usize,
+    held: [u32; 32],
+    last_hold: [u32; 32],
+}
+
+impl Track {
+    const EMPTY: Track = Track {
+        entries: [Entry {
+            buttons: 0,
+            direction: Direction::Neutral,
+        }; HISTORY_LEN],
+        newest: 0,
+        len: 0,
+        held: [0; 32],
+        last_hold: [0; 32],
+    };
+
+    fn push(&mut self, entry: Entry) {
+        self.newest = (self.newest + 1) % HISTORY_LEN;
+        self.entries[self.newest] = entry;
+        self.len = (self.len + 1).min(HISTORY_LEN);
+
+        for bit in 0..32 {
+            if entry.buttons & 1 << bit != 0 {
+                self.held[bit] += 1;
+            } else if self.held[bit] != 0 {
+                self.last_hold[bit] = self.held[bit];
+                self.held[bit] = 0;
+            }
+        }
+    }
+
+    /// Entry `ago` frames back, 0 being the current frame.
+    fn entry(&self, ago: usize) -> Option<Entry> {
+        if ago < self.len {
+            Some(self.entries[(self.newest + HISTORY_LEN - ago) % HISTORY_LEN])
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if every button of `mask` is held `ago` frames back, and not all of
+    /// them the frame before. The oldest entry has no frame before it, so a button held
+    /// there may have been pressed earlier and never counts.
+    fn pressed_at(&self, mask: u32, ago: usize) -> bool {
+        if ago + 1 >= self.len {
+            return false;
+        }
+        let now = self.entry(ago).map_or(0, |e| e.buttons);
+        let before = self.entry(ago + 1).map_or(0, |e| e.buttons);
+        mask != 0 && now & mask == mask && before & mask != mask
+    }
+
+    fn pressed_ago(&self, mask: u32, from: usize, window: usize) -> Option<usize> {
+        (from..window.min(self.len)).find(|&ago| self.pressed_at(mask, ago))
+    }
+
+    fn bits(mask: u32) -> impl Iterator<Item = usize> {
+        (0..32).filter(move |bit| mask & 1 << bit != 0)
+    }
+
+    fn held_for(&self, mask: u32) -> u32 {
+        Self::bits(mask)
+            .map(|bit| self.held[bit])
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn released_after(&self, mask: u32) -> Option<u32> {
+        let now = self.entry(0).map_or(0, |e| e.buttons);
+        let before = self.entry(1).map_or(0, |e| e.buttons);
+        if mask != 0 && before & mask == mask && now & mask != mask {
+            Self::bits(mask)
+                .filter(|&bit| now & 1 << bit == 0)
+                .map(|bit| self.last_hold[bit])
+                .max()
+        } else {
+            None
+        }
+    }
+
+    fn sequence(&self, steps: &[u32], window: usize) -> bool {
+        let (last, rest) = match steps.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        if !self.pressed_at(*last, 0) {
+            return false;
+        }
+
+        let mut ago = 0;
+        for &step in rest.iter().rev() {
+            match self.pressed_ago(step, ago + 1, window) {
+                Some(found) => ago = found,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn motion(&self, steps: &[Direction], window: usize) -> bool {
+        let mut next = 0;
+        for ago in (0..window.min(self.len)).rev() {
+            if next == steps.len() {
+                break;
+            }
+            if self.entry(ago).map(|e| e.direction) == Some(steps[next]) {
+                next += 1;
+            }
+        }
+        !steps.is_empty() && next == steps.len()
+    }
+}
+
+// Tracks 0 to 3 are the GameCube controllers, 4 to 7 the Wii Remotes.
+static TRACKS: Global<[Track; 8]> = Global::new([Track::EMPTY; 8]);
+
+fn with_track<R>(device: Device, f: impl FnOnce(&Track) -> R) -> R {
+    let index = match device {
+        Device::Pad(pad) => pad as usize,
+        Device::Remote(remote) => 4 + remote as usize,
+    };
+    TRACKS.with(|tracks| f(&tracks[index]))
+}
+
+/// Recent input of a GameCube controller, see `History::pad`.
+#[derive(Copy, Clone, Debug)]
+pub struct PadHistory {
+    controller: Controller,
+}
+
+impl PadHistory {
+    fn with_track<R>(&self, f: impl FnOnce(&Track) -> R) -> R {
+        with_track(Device::Pad(self.controller), f)
+    }
+
+    /// Frames since all of `buttons` were last pressed, 0 meaning this frame.
+    pub fn pressed_ago(&self, buttons: Buttons) -> Option<usize> {
+        self.with_track(|track| track.pressed_ago(buttons.bits() as u32, 0, HISTORY_LEN))
+    }
+
+    /// Returns true if all of `buttons` were pressed within the last `frames` frames,
+    /// for buffering inputs slightly ahead of when they can be acted upon.
+    pub fn pressed_within(&self, buttons: Buttons, frames: usize) -> bool {
+        self.with_track(|track| {
+            track
+                .pressed_ago(buttons.bits() as u32, 0, frames)
+                .is_some()
+        })
+    }
+
+    /// Returns true if `buttons` were pressed this frame and once before within `frames` frames.
+    pub fn double_tapped(&self, buttons: Buttons, frames: usize) -> bool {
+        let mask = buttons.bits() as u32;
+        self.with_track(|track| {
+            track.pressed_at(mask, 0) && track.pressed_ago(mask, 1, frames).is_some()
+        })
+    }
+
+    /// Frames all of `buttons` have been held, or 0 if they are not.
+    pub fn held_for(&self, buttons: Buttons) -> u32 {
+        self.with_track(|track| track.held_for(buttons.bits() as u32))
+    }
+
+    /// If `buttons` were released this frame, how many frames they were held for.
+    /// Useful for charge attacks.
+    pub fn released_after(&self, buttons: Buttons) -> Option<u32> {
+        self.with_track(|track| track.released_after(buttons.bits() as u32))
+    }
+
+    /// Returns true if `steps` were pressed in order within `frames` frames, the last one this frame.
+    pub fn sequence(&self, steps: &[Buttons], frames: usize) -> bool {
+        let mut masks = [0; HISTORY_LEN];
+        let steps = &steps[..steps.len().min(HISTORY_LEN)];
+        for (mask, step) in masks.iter_mut().zip(steps) {
+            *mask = step.bits() as u32;
+        }
+        self.with_track(|track| track.sequence(&masks[..steps.len()], frames))
+    }
+
+    /// Current direction of the control stick.
+    pub fn direction(&self) -> Direction {
+        self.with_track(|track| track.entry(0).map_or(Direction::Neutral, |e| e.direction))
+    }
+
+    /// Returns true if the control stick went through `steps` in order within `frames` frames.
+    pub fn motion(&self, steps: &[Direction], frames: usize) -> bool {
+        self.with_track(|track| track.motion(steps, frames))
+    }
+}
+
+/// Recent input of a Wii Remote, see `History::remote`. Directions follow the stick
+/// of the Nunchuk or the left stick of the Classic Controller.
+#[derive(Copy, Clone, Debug)]
+pub struct RemoteHistory {
+    remote: Remote,
+}
+
+impl RemoteHistory {
+    fn with_track<R>(&self, f: impl FnOnce(&Track) -> R) -> R {
+        with_track(Device::Remote(self.remote), f)
+    }
+
+    /// Frames since all of `buttons` were last pressed, 0 meaning this frame.
+    pub fn pressed_ago(&self, buttons: WpadButtons) -> Option<usize> {
+        self.with_track(|track| track.pressed_ago(buttons.bits(), 0, HISTORY_LEN))
+    }
+
+    /// Returns true if all of `buttons` were pressed within the last `frames` frames.
+    pub fn pressed_within(&self, buttons: WpadButtons, frames: usize) -> bool {
+        self.with_track(|track| track.pressed_ago(buttons.bits(), 0, frames).is_some())
+    }
+
+    /// Returns true if `buttons` were pressed this frame and once before within `frames` frames.
+    pub fn double_tapped(&self, buttons: WpadButtons, frames: usize) -> bool {
+        let mask = buttons.bits();
+        self.with_track(|track| {
+            track.pressed_at(mask, 0) && track.pressed_ago(mask, 1, frames).is_some()
+        })
+    }
+
+    /// Frames all of `buttons` have been held, or 0 if they are not.
+    pub fn held_for(&self, buttons: WpadButtons) -> u32 {
+        self.with_track(|track| track.held_for(buttons.bits()))
+    }
+
+    /// If `buttons` were released this frame, how many frames they were held for.
+    pub fn released_after(&self, buttons: WpadButtons) -> Option<u32> {
+        self.with_track(|track| track.released_after(buttons.bits()))
+    }
+
+    /// Returns true if `steps` were pressed in order within `frames` frames, the last one this frame.
+    pub fn sequence(&self, steps: &[WpadButtons], frames: usize) -> bool {
+        let mut masks = [0; HISTORY_LEN];
+        let steps = &steps[..steps.len().min(HISTORY_LEN)];
+        for (mask, step) in masks.iter_mut().zip(steps) {
+            *mask = step.bits();
+        }
+        self.with_track(|track| track.sequence(&masks[..steps.len()], frames))
+    }
+
+    /// Current direction of the expansion stick.
+    pub fn direction(&self) -> Direction {
+        self.with_track(|track| track.entry(0).map_or(Direction::Neutral, |e| e.direction))
+    }
+
+    /// Returns true if the expansion stick went through `steps` in order within `frames` frames.
+    pub fn motion(&self, steps: &[Direction], frames: usize) -> bool {
+        self.with_track(|track| track.motion(steps, frames))
+    }
+}
+
+/// The last `HISTORY_LEN` frames of every controller, recorded from `InputFrame` so
+/// replays fill it the same way.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// let pad = History::pad(Controller::One);
+///
+/// // Hadouken
+/// if pad.motion(&Direction::QUARTER_CIRCLE_RIGHT, 15) && pad.pressed_within(Buttons::A, 3) {
+///     // ...
+/// }
+///
+/// // Charge shot
+/// if let Some(frames) = pad.released_after(Buttons::B) {
+///     let power = frames.min(120);
+/// }
+/// ```
+pub struct History;
+
+impl History {
+    pub fn pad(controller: Controller) -> PadHistory {
+        PadHistory { controller }
+    }
+
+    pub fn remote(remote: Remote) -> RemoteHistory {
+        RemoteHistory { remote }
+    }
+
+    pub(crate) fn record(frame: &InputFrame) {
+        TRACKS.with_mut(|tracks| {
+            for (track, pad) in tracks[..4].iter_mut().zip(frame.pads.iter()) {
+                track.push(Entry {
+                    buttons: pad.buttons.bits() as u32,
+                    direction: Direction::from_stick(pad.stick()),
+                });
+            }
+
+            for (track, remote) in tracks[4..].iter_mut().zip(frame.remotes.iter()) {
+                let stick = match remote.expansion {
+                    ExpansionFrame::Nunchuk { stick } => stick,
+                    ExpansionFrame::Classic { left_stick, .. } => left_stick,
+                    ExpansionFrame::None => StickVector::ZERO,
+                };
+                track.push(Entry {
+                    buttons: remote.buttons.bits(),
+                    direction: Direction::from_stick(stick),
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: u32 = 1;
+    const B: u32 = 2;
+
+    fn track(frames: &[(u32, Direction)]) -> Track {
+        let mut track = Track::EMPTY;
+        for &(buttons, direction) in frames {
+            track.push(Entry { buttons, direction });
+        }
+        track
+    }
+
+    fn buttons(frames: &[u32]) -> Track {
+        let frames: Vec<_> = frames.iter().map(|&b| (b, Direction::Neutral)).collect();
+        track(&frames)
+    }
+
+    #[test]
+    fn button_held_before_the_window_is_not_a_press() {
+        assert!(!buttons(&[A]).pressed_at(A, 0));
+        assert!(!buttons(&[A, A, A]).pressed_at(A, 2));
+        assert_eq!(None, buttons(&[A, A, A]).pressed_ago(A, 0, HISTORY_LEN));
+
+        let full = buttons(&[A; HISTORY_LEN + 8]);
+        assert_eq!(HISTORY_LEN, full.len);
+        assert_eq!(None, full.pressed_ago(A, 0, HISTORY_LEN));
+    }
+
+    #[test]
+    fn press_is_found_frames_ago() {
+        let track = buttons(&[0, A, A, 0, 0]);
+        assert_eq!(Some(3), track.pressed_ago(A, 0, HISTORY_LEN));
+        assert_eq!(None, track.pressed_ago(A, 0, 3));
+        assert!(!track.pressed_at(0, 3));
+    }
+
+    #[test]
+    fn presses_of_every_button_in_the_mask() {
+        let track = buttons(&[0, A, A | B]);
+        assert!(track.pressed_at(A | B, 0));
+        assert!(!track.pressed_at(A, 0));
+        assert!(track.pressed_at(A, 1));
+    }
+
+    #[test]
+    fn held_and_released_after() {
+        let track = buttons(&[0, A, A, A]);
+        assert_eq!(3, track.held_for(A));
+        assert_eq!(0, track.held_for(A | B));
+        assert_eq!(None, track.released_after(A));
+
+        let released = buttons(&[0, A, A, A, 0]);
+        assert_eq!(0, released.held_for(A));
+        assert_eq!(Some(3), released.released_after(A));
+        assert_eq!(None, buttons(&[0, 0]).released_after(A));
+    }
+
+    #[test]
+    fn sequence_in_order_within_the_window() {
+        let track = buttons(&[0, A, 0, B, 0, A]);
+        assert!(track.sequence(&[A, B, A], HISTORY_LEN));
+        assert!(track.sequence(&[B, A], 3));
+        assert!(!track.sequence(&[A, B, A], 4));
+        assert!(!track.sequence(&[B, B, A], HISTORY_LEN));
+        assert!(!track.sequence(&[], HISTORY_LEN));
+
+        // The last step has to be pressed this frame
+        assert!(!buttons(&[0, A, 0, B, 0]).sequence(&[A, B], HISTORY_LEN));
+    }
+
+    #[test]
+    fn motion_through_directions_in_order() {
+        use Direction::*;
+
+        let track = track(&[
+            (0, Neutral),
+            (0, Down),
+            (0, Down),
+            (0, DownRight),
+            (0, Neutral),
+            (0, Right),
+        ]);
+        assert!(track.motion(&Direction::QUARTER_CIRCLE_RIGHT, HISTORY_LEN));
+        assert!(!track.motion(&Direction::QUARTER_CIRCLE_RIGHT, 3));
+        assert!(!track.motion(&Direction::QUARTER_CIRCLE_LEFT, HISTORY_LEN));
+        assert!(!track.motion(&[], HISTORY_LEN));
+    }
+
+    #[test]
+    fn direction_sectors() {
+        assert_eq!(
+            Direction::Neutral,
+            Direction::from_stick(StickVector::new(0.3, 0.3))
+        );
+        assert_eq!(
+            Direction::UpRight,
+            Direction::from_stick(StickVector::new(0.6, 0.6))
+        );
+        assert_eq!(
+            Direction::Left,
+            Direction::from_stick(StickVector::new(-1.0, -0.1))
+        );
+        assert_eq!(Direction::DownLeft, Direction::DownRight.mirrored());
+    }
+}
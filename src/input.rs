@@ -0,0 +1,436 @@
+extern crate alloc;
+
+use crate::{
+    analog::{StickConfig, StickVector, TriggerConfig},
+    frame::{ExpansionFrame, InputFrame},
+    replay::Replay,
+    usb::Usb,
+};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use ogc::prelude::*;
+
+/// A player slot, each with its own devices and bindings.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Player {
+    One = 0,
+    Two = 1,
+    Three = 2,
+    Four = 3,
+}
+
+impl Player {
+    /// Every player, in slot order.
+    pub const ALL: [Player; 4] = [Player::One, Player::Two, Player::Three, Player::Four];
+}
+
+/// A digital input an action can be bound to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ButtonBinding {
+    /// GameCube controller buttons.
+    Pad(Buttons),
+    /// Wii Remote buttons. `WpadButtons::NUNCHUK_Z` and `WpadButtons::NUNCHUK_C` need a Nunchuk.
+    Wpad(WpadButtons),
+    /// Classic Controller buttons, the `WpadButtons::CLASSIC_*` ones.
+    Classic(WpadButtons),
+    /// A key of any USB keyboard, once `Usb::enable` was called. Not tied to the player's devices.
+    /// Never active while `Replay` is recording or playing, since replays do not contain it.
+    Key(Key),
+    /// USB mouse buttons, once `Usb::enable` was called. Not tied to the player's devices.
+    /// Never active while `Replay` is recording or playing, since replays do not contain it.
+    Mouse(MouseButtons),
+}
+
+/// An analog input an axis can be bound to. Every axis reports a value in `-1.0..=1.0`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AxisBinding {
+    PadStickX,
+    PadStickY,
+    PadSubStickX,
+    PadSubStickY,
+    PadTriggerL,
+    PadTriggerR,
+    NunchukStickX,
+    NunchukStickY,
+    ClassicLeftStickX,
+    ClassicLeftStickY,
+    ClassicRightStickX,
+    ClassicRightStickY,
+    ClassicTriggerL,
+    ClassicTriggerR,
+    /// Two buttons acting as the negative and positive end of an axis.
+    Buttons(ButtonBinding, ButtonBinding),
+}
+
+/// An analog stick of one of the devices of a player.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AnalogStick {
+    Pad,
+    PadSub,
+    Nunchuk,
+    ClassicLeft,
+    ClassicRight,
+}
+
+/// Devices and bindings of a single player.
+#[derive(Clone, Debug)]
+pub struct PlayerInput {
+    pad: Option<Controller>,
+    remote: Option<Remote>,
+    actions: BTreeMap<String, Vec<ButtonBinding>>,
+    axes: BTreeMap<String, Vec<AxisBinding>>,
+    stick_config: StickConfig,
+    trigger_config: TriggerConfig,
+}
+
+#[derive(Copy, Clone)]
+enum Edge {
+    Down,
+    Held,
+    Up,
+}
+
+impl PlayerInput {
+    fn new(pad: Controller, remote: Remote) -> Self {
+        Self {
+            pad: Some(pad),
+            remote: Some(remote),
+            actions: BTreeMap::new(),
+            axes: BTreeMap::new(),
+            stick_config: StickConfig::new(),
+            trigger_config: TriggerConfig::new(),
+        }
+    }
+
+    /// Assigns a GameCube controller to this player, or none.
+    pub fn set_pad(&mut self, pad: Option<Controller>) {
+        self.pad = pad;
+    }
+
+    /// Assigns a Wii Remote, with its expansion, to this player, or none.
+    pub fn set_remote(&mut self, remote: Option<Remote>) {
+        self.remote = remote;
+    }
+
+    pub fn pad(&self) -> Option<Controller> {
+        self.pad
+    }
+
+    pub fn remote(&self) -> Option<Remote> {
+        self.remote
+    }
+
+    /// Dead zone and response curve applied to every stick of this player.
+    pub fn set_stick_config(&mut self, config: StickConfig) {
+        self.stick_config = config;
+    }
+
+    /// Threshold applied to every trigger of this player.
+    pub fn set_trigger_config(&mut self, config: TriggerConfig) {
+        self.trigger_config = config;
+    }
+
+    pub fn stick_config(&self) -> StickConfig {
+        self.stick_config
+    }
+
+    pub fn trigger_config(&self) -> TriggerConfig {
+        self.trigger_config
+    }
+
+    /// Takes the current stick and trigger positions of the GameCube controller as their
+    /// new origin. Wii Remote expansions are calibrated by libogc.
+    pub fn recalibrate(&self) -> bool {
+        self.pad.map_or(true, |pad| Pad::recalibrate(&[pad]))
+    }
+
+    /// Position of `stick`, with the stick config applied. Zero if the device is missing.
+    pub fn stick(&self, stick: AnalogStick) -> StickVector {
+        let frame = InputFrame::current();
+        let raw = match stick {
+            AnalogStick::Pad => self.pad.map(|pad| frame.pad(pad).stick()),
+            AnalogStick::PadSub => self.pad.map(|pad| frame.pad(pad).sub_stick()),
+            _ => match (stick, self.expansion()) {
+                (AnalogStick::Nunchuk, ExpansionFrame::Nunchuk { stick }) => Some(stick),
+                (AnalogStick::ClassicLeft, ExpansionFrame::Classic { left_stick, .. }) => {
+                    Some(left_stick)
+                }
+                (AnalogStick::ClassicRight, ExpansionFrame::Classic { right_stick, .. }) => {
+                    Some(right_stick)
+                }
+                _ => None,
+            },
+        };
+
+        raw.map_or(StickVector::ZERO, |raw| self.stick_config.apply(raw))
+    }
+
+    fn expansion(&self) -> ExpansionFrame {
+        self.remote.map_or(ExpansionFrame::None, |remote| {
+            InputFrame::current().remote(remote).expansion
+        })
+    }
+
+    /// Adds a binding to `action`, keeping the existing ones.
+    pub fn bind(&mut self, action: &str, binding: ButtonBinding) {
+        let bindings = self.actions.entry(action.into()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Adds a binding to `axis`, keeping the existing ones.
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        let bindings = self.axes.entry(axis.into()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Replaces every binding of `action` with `binding`.
+    pub fn rebind(&mut self, action: &str, binding: ButtonBinding) {
+        self.unbind(action);
+        self.bind(action, binding);
+    }
+
+    /// Replaces every binding of `axis` with `binding`.
+    pub fn rebind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        self.unbind_axis(axis);
+        self.bind_axis(axis, binding);
+    }
+
+    /// Removes every binding of `action`.
+    pub fn unbind(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    /// Removes every binding of `axis`.
+    pub fn unbind_axis(&mut self, axis: &str) {
+        self.axes.remove(axis);
+    }
+
+    /// Current bindings of `action`.
+    pub fn bindings(&self, action: &str) -> &[ButtonBinding] {
+        self.actions.get(action).map_or(&[], |b| b.as_slice())
+    }
+
+    /// Current bindings of `axis`.
+    pub fn axis_bindings(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map_or(&[], |b| b.as_slice())
+    }
+
+    /// Returns true if any binding of `action` was pressed this frame.
+    pub fn pressed(&self, action: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|&b| self.button(b, Edge::Down))
+    }
+
+    /// Returns true if any binding of `action` is held down.
+    pub fn held(&self, action: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|&b| self.button(b, Edge::Held))
+    }
+
+    /// Returns true if any binding of `action` was released this frame.
+    pub fn released(&self, action: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|&b| self.button(b, Edge::Up))
+    }
+
+    /// Value of `axis` in `-1.0..=1.0`, taken from the binding deflected the most.
+    pub fn axis(&self, axis: &str) -> f32 {
+        self.axis_bindings(axis)
+            .iter()
+            .map(|&b| self.axis_value(b))
+            .fold(0.0, |a, b| {
+                if libm::fabsf(b) > libm::fabsf(a) {
+                    b
+                } else {
+                    a
+                }
+            })
+    }
+
+    fn button(&self, binding: ButtonBinding, edge: Edge) -> bool {
+        match binding {
+            ButtonBinding::Pad(mask) => self.pad.map_or(false, |pad| {
+                let buttons = match edge {
+                    Edge::Down => InputFrame::pad_down(pad),
+                    Edge::Held => InputFrame::pad_held(pad),
+                    Edge::Up => InputFrame::pad_up(pad),
+                };
+                buttons.any(mask)
+            }),
+            ButtonBinding::Wpad(mask) => self.remote.map_or(false, |remote| {
+                // The upper half is shared between the Nunchuk and the Classic Controller.
+                let mask = if matches!(self.expansion(), ExpansionFrame::Nunchuk { .. }) {
+                    mask
+                } else {
+                    mask & WpadButtons::REMOTE
+                };
+                wpad_buttons(remote, edge).any(mask)
+            }),
+            ButtonBinding::Classic(mask) => self.remote.map_or(false, |remote| {
+                matches!(self.expansion(), ExpansionFrame::Classic { .. })
+                    && wpad_buttons(remote, edge).any(mask & WpadButtons::EXPANSION)
+            }),
+            ButtonBinding::Key(_) | ButtonBinding::Mouse(_)
+                if Replay::is_recording() || Replay::is_playing() =>
+            {
+                false
+            }
+            ButtonBinding::Key(key) => match edge {
+                Edge::Down => Usb::key_down(key),
+                Edge::Held => Usb::key_held(key),
+                Edge::Up => Usb::key_up(key),
+            },
+            ButtonBinding::Mouse(mask) => match edge {
+                Edge::Down => Usb::mouse_down().intersects(mask),
+                Edge::Held => Usb::mouse_held().intersects(mask),
+                Edge::Up => Usb::mouse_up().intersects(mask),
+            },
+        }
+    }
+
+    fn axis_value(&self, binding: AxisBinding) -> f32 {
+        let pad = self.pad.map(|pad| *InputFrame::current().pad(pad));
+        let (left_trigger, right_trigger) = match self.expansion() {
+            ExpansionFrame::Classic {
+                left_trigger,
+                right_trigger,
+                ..
+            } => (left_trigger, right_trigger),
+            _ => (0.0, 0.0),
+        };
+
+        match binding {
+            AxisBinding::PadStickX => self.stick(AnalogStick::Pad).x,
+            AxisBinding::PadStickY => self.stick(AnalogStick::Pad).y,
+            AxisBinding::PadSubStickX => self.stick(AnalogStick::PadSub).x,
+            AxisBinding::PadSubStickY => self.stick(AnalogStick::PadSub).y,
+            AxisBinding::PadTriggerL => {
+                pad.map_or(0.0, |pad| self.trigger_config.apply_raw(pad.trigger_l))
+            }
+            AxisBinding::PadTriggerR => {
+                pad.map_or(0.0, |pad| self.trigger_config.apply_raw(pad.trigger_r))
+            }
+            AxisBinding::NunchukStickX => self.stick(AnalogStick::Nunchuk).x,
+            AxisBinding::NunchukStickY => self.stick(AnalogStick::Nunchuk).y,
+            AxisBinding::ClassicLeftStickX => self.stick(AnalogStick::ClassicLeft).x,
+            AxisBinding::ClassicLeftStickY => self.stick(AnalogStick::ClassicLeft).y,
+            AxisBinding::ClassicRightStickX => self.stick(AnalogStick::ClassicRight).x,
+            AxisBinding::ClassicRightStickY => self.stick(AnalogStick::ClassicRight).y,
+            AxisBinding::ClassicTriggerL => self.trigger_config.apply(left_trigger),
+            AxisBinding::ClassicTriggerR => self.trigger_config.apply(right_trigger),
+            AxisBinding::Buttons(negative, positive) => {
+                let negative = self.button(negative, Edge::Held) as i32 as f32;
+                let positive = self.button(positive, Edge::Held) as i32 as f32;
+                positive - negative
+            }
+        }
+    }
+}
+
+fn wpad_buttons(remote: Remote, edge: Edge) -> WpadButtons {
+    match edge {
+        Edge::Down => InputFrame::remote_down(remote),
+        Edge::Held => InputFrame::remote_held(remote),
+        Edge::Up => InputFrame::remote_up(remote),
+    }
+}
+
+/// Named actions and axes bound to GameCube controllers, Wii Remotes and their expansions.
+///
+/// By default player one reads GameCube controller one and Wii Remote one, and so on.
+/// Every query reads `InputFrame::current`, so replays drive it like the controllers do.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// let mut input = Input::new();
+/// input.bind("jump", ButtonBinding::Pad(Buttons::A));
+/// input.bind("jump", ButtonBinding::Wpad(WpadButtons::TWO));
+/// input.bind_axis("move_x", AxisBinding::PadStickX);
+/// input.bind_axis("move_x", AxisBinding::NunchukStickX);
+///
+/// if input.pressed(Player::One, "jump") {
+///     // ...
+/// }
+/// let speed = input.axis(Player::One, "move_x") * 4.0;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Input {
+    players: [PlayerInput; 4],
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            players: [
+                PlayerInput::new(Controller::One, Remote::One),
+                PlayerInput::new(Controller::Two, Remote::Two),
+                PlayerInput::new(Controller::Three, Remote::Three),
+                PlayerInput::new(Controller::Four, Remote::Four),
+            ],
+        }
+    }
+
+    /// Devices and bindings of `player`, for per-player assignment and rebinding.
+    pub fn player(&self, player: Player) -> &PlayerInput {
+        &self.players[player as usize]
+    }
+
+    /// Mutable access to the devices and bindings of `player`.
+    pub fn player_mut(&mut self, player: Player) -> &mut PlayerInput {
+        &mut self.players[player as usize]
+    }
+
+    /// Adds a binding to `action` for every player.
+    pub fn bind(&mut self, action: &str, binding: ButtonBinding) {
+        for player in self.players.iter_mut() {
+            player.bind(action, binding);
+        }
+    }
+
+    /// Adds a binding to `axis` for every player.
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        for player in self.players.iter_mut() {
+            player.bind_axis(axis, binding);
+        }
+    }
+
+    /// See `PlayerInput::pressed`.
+    pub fn pressed(&self, player: Player, action: &str) -> bool {
+        self.player(player).pressed(action)
+    }
+
+    /// See `PlayerInput::held`.
+    pub fn held(&self, player: Player, action: &str) -> bool {
+        self.player(player).held(action)
+    }
+
+    /// See `PlayerInput::released`.
+    pub fn released(&self, player: Player, action: &str) -> bool {
+        self.player(player).released(action)
+    }
+
+    /// See `PlayerInput::stick`.
+    pub fn stick(&self, player: Player, stick: AnalogStick) -> StickVector {
+        self.player(player).stick(stick)
+    }
+
+    /// See `PlayerInput::axis`.
+    pub fn axis(&self, player: Player, axis: &str) -> f32 {
+        self.player(player).axis(axis)
+    }
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,4 @@
-#![no_std]
-#![feature(associated_type_defaults)]
+#![cfg_attr(not(test), no_std)]
 
 //! `ogc-engine` is a simple engine for creating games targeting the Wii.
 //! Internally it uses `ogc-rs` and `embedded-graphics`.
@@ -27,18 +26,119 @@
 //! [repository](https://github.com/knarkzel/ogc-engine/tree/master/examples)
 
 /// Contains implementation for drawing and manipulating the screen.
+#[cfg(target_os = "rvl-ios")]
 pub mod display;
 
 /// Provides necessary abstractions for ergonomic game development.
 pub mod engine;
 
+/// Static geometry recorded once and replayed every frame.
+#[cfg(target_os = "rvl-ios")]
+pub mod batch;
+
+/// Frame timing statistics and the debug overlay.
+#[cfg(target_os = "rvl-ios")]
+pub mod stats;
+
+/// Image encoding for screenshots of the external framebuffer.
+pub mod screenshot;
+
+/// Named actions and axes mapped onto every supported controller.
+#[cfg(target_os = "rvl-ios")]
+pub mod input;
+
+/// Connection status of the controller slots and hot-plug events.
+pub mod connection;
+
+/// Rumble patterns for the GameCube controllers and Wii Remotes.
+#[cfg(target_os = "rvl-ios")]
+pub mod rumble;
+
+/// USB keyboard and mouse input.
+#[cfg(target_os = "rvl-ios")]
+pub mod usb;
+
+/// Per-frame snapshots of every controller.
+pub mod frame;
+
+/// Wii Remote pointers mapped onto the display, with cursors and hit testing.
+#[cfg(target_os = "rvl-ios")]
+pub mod pointer;
+
+/// Recent input of every controller, for buffering, combos and stick motions.
+pub mod history;
+
+/// Recording and playback of the input of a play session.
+pub mod replay;
+
+/// Deterministic pseudo-random numbers.
+pub mod random;
+
+/// Sound effects on `Asnd` voices and a music channel.
+#[cfg(target_os = "rvl-ios")]
+pub mod audio;
+
+/// Buses sounds and music are mixed on, with effects and ducking.
+#[cfg(target_os = "rvl-ios")]
+pub mod mixer;
+
+/// Music streamed from files, decoded while it plays.
+#[cfg(target_os = "rvl-ios")]
+pub mod music;
+
+/// Sources of music, decoding into PCM for `Music`.
+pub mod decoder;
+
+/// Tracker music: MOD files replayed into a music stream.
+pub mod tracker;
+
+/// Sounds positioned in the world, panned and attenuated around a listener.
+#[cfg(target_os = "rvl-ios")]
+pub mod spatial;
+
+/// Sound effects synthesized at runtime from oscillators and envelopes.
+pub mod synth;
+
+/// Loading and resampling of WAV files.
+pub mod wav;
+
+/// Dead zones, response curves and normalization of analog sticks and triggers.
+pub mod analog;
+
+/// Engine state kept in statics, shared between the modules.
+mod global;
+
 /// Draw error abstraction.
 pub type DrawError = core::convert::Infallible;
 
+#[cfg(target_os = "rvl-ios")]
 pub mod prelude {
     pub use super::DrawError;
+    pub use crate::analog::{DeadZone, ResponseCurve, StickConfig, StickVector, TriggerConfig};
+    pub use crate::audio::{Audio, PlayOptions, Sound, SoundHandle};
+    pub use crate::batch::StaticBatch;
+    pub use crate::connection::{ConnectionEvent, Connections, Device};
     pub use crate::display::Display;
     pub use crate::engine::{Engine, State};
+    pub use crate::frame::{ExpansionFrame, InputFrame, PadFrame, RemoteFrame};
+    pub use crate::history::{Direction, History, PadHistory, RemoteHistory};
+    pub use crate::input::{AnalogStick, AxisBinding, ButtonBinding, Input, Player, PlayerInput};
+    pub use crate::mixer::{
+        Bus, Ducking, Echo, Effect, EffectChain, HighPass, LowPass, Mixer, Reverb,
+    };
+    pub use crate::music::{Decoder, Mp3Stream, Music, Track};
+    pub use crate::pointer::{Pointer, PointerState, CURSOR_COLORS};
+    pub use crate::random::{Random, Rng};
+    pub use crate::replay::{Recording, Replay};
+    pub use crate::rumble::{Rumble, RumblePattern, RumbleStep};
+    pub use crate::screenshot::ImageFormat;
+    pub use crate::spatial::{Attenuation, Emitter, Listener, Rolloff};
+    pub use crate::stats::{FrameStats, Stats};
+    pub use crate::synth::{Envelope, Oscillator, Sfx, Waveform};
+    pub use crate::tracker::{ModPlayer, Module, Tracker, TrackerEvent, TrackerPosition};
+    pub use crate::usb::Usb;
+    pub use crate::wav::{Wav, DSP_RATE};
+    pub use embedded_graphics::primitives::Primitive;
     pub use embedded_graphics::{self, pixelcolor::Rgb888 as Rgb, prelude::*};
     pub use ogc::{self, prelude::*};
 }
@@ -0,0 +1,697 @@
+extern crate alloc;
+
+use crate::{
+    audio::{Audio, Sound},
+    global::Global,
+    music::Music,
+};
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{f32::consts::TAU, time::Duration};
+use ogc::prelude::*;
+
+/// Where a sound is mixed, each with its own effects, volume and ducking.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Bus {
+    /// Tracks streamed by `Music` and modules played by `Tracker`.
+    Music,
+    /// Sound effects, where `Audio` plays sounds unless told otherwise.
+    Effects,
+    /// Speech, which the other buses are usually ducked under.
+    Dialogue,
+}
+
+const BUSES: usize = 3;
+
+impl Bus {
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Processes PCM samples on their way to the DSP, see `EffectChain`.
+pub trait Effect: BoxedEffect {
+    /// Processes interleaved `samples` of `channels` channels, 1 or 2, at `rate` Hz in
+    /// place. Samples are in `-1.0..=1.0`, but may overshoot between effects.
+    fn process(&mut self, samples: &mut [f32], channels: usize, rate: u32);
+
+    /// How long the effect keeps sounding once its input is silent.
+    fn tail(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+/// Copies an `Effect` behind a `Box`. Implemented for every effect that is `Clone`.
+pub trait BoxedEffect {
+    fn boxed(&self) -> Box<dyn Effect>;
+}
+
+impl<T: Effect + Clone + 'static> BoxedEffect for T {
+    fn boxed(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+}
+
+/// Coefficient of a one-pole filter with a cutoff at `cutoff` Hz.
+fn one_pole(cutoff: f32, rate: u32) -> f32 {
+    1.0 - libm::expf(-TAU * cutoff.max(0.0) / rate.max(1) as f32)
+}
+
+/// Removes the frequencies above a cutoff, muffling the sound as if behind a wall.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LowPass {
+    cutoff: f32,
+    state: [f32; 2],
+}
+
+impl LowPass {
+    /// Cuts above `cutoff` Hz, gently at 6 dB per octave.
+    pub fn new(cutoff: f32) -> Self {
+        Self {
+            cutoff,
+            state: [0.0; 2],
+        }
+    }
+}
+
+impl Effect for LowPass {
+    fn process(&mut self, samples: &mut [f32], channels: usize, rate: u32) {
+        let a = one_pole(self.cutoff, rate);
+        for frame in samples.chunks_exact_mut(channels) {
+            for (sample, state) in frame.iter_mut().zip(self.state.iter_mut()) {
+                *state += a * (*sample - *state);
+                *sample = *state;
+            }
+        }
+    }
+}
+
+/// Removes the frequencies below a cutoff, thinning the sound like a radio or phone.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HighPass {
+    cutoff: f32,
+    state: [f32; 2],
+}
+
+impl HighPass {
+    /// Cuts below `cutoff` Hz, gently at 6 dB per octave.
+    pub fn new(cutoff: f32) -> Self {
+        Self {
+            cutoff,
+            state: [0.0; 2],
+        }
+    }
+}
+
+impl Effect for HighPass {
+    fn process(&mut self, samples: &mut [f32], channels: usize, rate: u32) {
+        let a = one_pole(self.cutoff, rate);
+        for frame in samples.chunks_exact_mut(channels) {
+            for (sample, state) in frame.iter_mut().zip(self.state.iter_mut()) {
+                *state += a * (*sample - *state);
+                *sample -= *state;
+            }
+        }
+    }
+}
+
+/// Repeats the sound after a delay, each repeat quieter than the last.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Echo {
+    delay: Duration,
+    feedback: f32,
+    mix: f32,
+    line: Vec<f32>,
+    position: usize,
+}
+
+impl Echo {
+    /// Repeats after `delay`, each repeat `feedback` times as loud as the one before,
+    /// in `0.0..=0.95`.
+    pub fn new(delay: Duration, feedback: f32) -> Self {
+        Self {
+            delay,
+            feedback: feedback.max(0.0).min(0.95),
+            mix: 0.5,
+            line: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Volume of the repeats, in `0.0..=1.0`. Half by default.
+    pub fn mix(self, mix: f32) -> Self {
+        Self { mix, ..self }
+    }
+}
+
+impl Effect for Echo {
+    fn process(&mut self, samples: &mut [f32], channels: usize, rate: u32) {
+        let frames = ((self.delay.as_secs_f32() * rate as f32) as usize).max(1);
+        if self.line.len() != frames * channels {
+            self.line = vec![0.0; frames * channels];
+            self.position = 0;
+        }
+
+        for sample in samples.iter_mut() {
+            let delayed = self.line[self.position];
+            self.line[self.position] = *sample + delayed * self.feedback;
+            *sample += delayed * self.mix;
+            self.position = (self.position + 1) % self.line.len();
+        }
+    }
+
+    /// Until the repeats fall below -60 dB.
+    fn tail(&self) -> Duration {
+        let repeats = if self.feedback > 0.0 {
+            libm::ceilf(libm::logf(0.001) / libm::logf(self.feedback)).max(1.0)
+        } else {
+            1.0
+        };
+        self.delay.mul_f32(repeats)
+    }
+}
+
+/// Delays of the comb and all-pass filters of `Reverb`, in samples at 44.1 kHz.
+const COMBS: [usize; 4] = [1116, 1188, 1277, 1356];
+const ALL_PASSES: [usize; 2] = [556, 441];
+/// Added to the delays of the right channel, to widen the reverb.
+const STEREO_SPREAD: usize = 23;
+const TUNING_RATE: f32 = 44_100.0;
+
+#[derive(Clone, Debug)]
+struct Comb {
+    buffer: Vec<f32>,
+    position: usize,
+    filter: f32,
+}
+
+impl Comb {
+    fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![0.0; length.max(1)],
+            position: 0,
+            filter: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.position];
+        self.filter = output * (1.0 - damping) + self.filter * damping;
+        self.buffer[self.position] = input + self.filter * feedback;
+        self.position = (self.position + 1) % self.buffer.len();
+        output
+    }
+}
+
+#[derive(Clone, Debug)]
+struct AllPass {
+    buffer: Vec<f32>,
+    position: usize,
+}
+
+impl AllPass {
+    fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![0.0; length.max(1)],
+            position: 0,
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let buffered = self.buffer[self.position];
+        self.buffer[self.position] = input + buffered * 0.5;
+        self.position = (self.position + 1) % self.buffer.len();
+        buffered - input
+    }
+}
+
+/// The filters of one channel of `Reverb`.
+#[derive(Clone, Debug)]
+struct ReverbChannel {
+    combs: Vec<Comb>,
+    all_passes: Vec<AllPass>,
+}
+
+/// The reflections of a room, after Freeverb.
+#[derive(Clone, Debug)]
+pub struct Reverb {
+    room_size: f32,
+    damping: f32,
+    mix: f32,
+    /// Rate the filters are sized for.
+    rate: u32,
+    channels: Vec<ReverbChannel>,
+}
+
+impl Reverb {
+    /// A medium room, mixed at 30%.
+    pub fn new() -> Self {
+        Self {
+            room_size: 0.5,
+            damping: 0.5,
+            mix: 0.3,
+            rate: 0,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Size of the room, in `0.0..=1.0`. Bigger rooms ring longer.
+    pub fn room_size(self, room_size: f32) -> Self {
+        Self {
+            room_size: room_size.max(0.0).min(1.0),
+            ..self
+        }
+    }
+
+    /// How much the walls absorb the high frequencies, in `0.0..=1.0`.
+    pub fn damping(self, damping: f32) -> Self {
+        Self {
+            damping: damping.max(0.0).min(1.0),
+            ..self
+        }
+    }
+
+    /// Volume of the reverb against the original sound, in `0.0..=1.0`.
+    pub fn mix(self, mix: f32) -> Self {
+        Self { mix, ..self }
+    }
+
+    fn feedback(&self) -> f32 {
+        0.7 + 0.28 * self.room_size
+    }
+
+    fn scaled(length: usize, rate: u32) -> usize {
+        (length as f32 * rate as f32 / TUNING_RATE) as usize
+    }
+}
+
+impl Default for Reverb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Reverb {
+    fn process(&mut self, samples: &mut [f32], channels: usize, rate: u32) {
+        if self.rate != rate || self.channels.len() != channels {
+            self.rate = rate;
+            self.channels = (0..channels)
+                .map(|channel| {
+                    let spread = channel * STEREO_SPREAD;
+                    ReverbChannel {
+                        combs: COMBS
+                            .iter()
+                            .map(|&length| Comb::new(Self::scaled(length + spread, rate)))
+                            .collect(),
+                        all_passes: ALL_PASSES
+                            .iter()
+                            .map(|&length| AllPass::new(Self::scaled(length + spread, rate)))
+                            .collect(),
+                    }
+                })
+                .collect();
+        }
+
+        let feedback = self.feedback();
+        let damping = self.damping * 0.4;
+        for frame in samples.chunks_exact_mut(channels) {
+            for (sample, channel) in frame.iter_mut().zip(self.channels.iter_mut()) {
+                let input = *sample * 0.03;
+                let mut wet = channel
+                    .combs
+                    .iter_mut()
+                    .map(|comb| comb.process(input, feedback, damping))
+                    .sum::<f32>();
+                for all_pass in channel.all_passes.iter_mut() {
+                    wet = all_pass.process(wet);
+                }
+                *sample = *sample * (1.0 - self.mix) + wet * self.mix * 3.0;
+            }
+        }
+    }
+
+    /// Until the longest comb filter falls below -60 dB.
+    fn tail(&self) -> Duration {
+        let longest = COMBS[COMBS.len() - 1] + STEREO_SPREAD;
+        let decays = libm::logf(0.001) / libm::logf(self.feedback());
+        Duration::from_secs_f32(longest as f32 / TUNING_RATE * decays)
+    }
+}
+
+/// Effects applied one after the other.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// // Music heard from underwater.
+/// let chain = EffectChain::new()
+///     .with(LowPass::new(800.0))
+///     .with(Reverb::new().room_size(0.8).mix(0.4));
+/// Mixer::set_effects(Bus::Music, Some(chain));
+/// ```
+#[derive(Default)]
+pub struct EffectChain {
+    effects: Vec<Box<dyn Effect>>,
+    scratch: Vec<f32>,
+}
+
+impl EffectChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `effect` after the others.
+    pub fn with<E: Effect + 'static>(mut self, effect: E) -> Self {
+        self.effects.push(Box::new(effect));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// How long the chain keeps sounding once its input is silent.
+    pub fn tail(&self) -> Duration {
+        self.effects.iter().map(|effect| effect.tail()).sum()
+    }
+
+    /// Processes interleaved 16-bit `samples` of `channels` channels at `rate` Hz in place.
+    pub fn process(&mut self, samples: &mut [i16], channels: usize, rate: u32) {
+        if self.effects.is_empty() || channels == 0 {
+            return;
+        }
+
+        self.scratch.clear();
+        self.scratch
+            .extend(samples.iter().map(|&sample| sample as f32 / 32768.0));
+        for effect in self.effects.iter_mut() {
+            effect.process(&mut self.scratch, channels, rate);
+        }
+        for (sample, &value) in samples.iter_mut().zip(self.scratch.iter()) {
+            *sample = (value * 32768.0).max(-32768.0).min(32767.0) as i16;
+        }
+    }
+}
+
+impl Clone for EffectChain {
+    fn clone(&self) -> Self {
+        Self {
+            effects: self.effects.iter().map(|effect| effect.boxed()).collect(),
+            scratch: Vec::new(),
+        }
+    }
+}
+
+/// Lowers a bus while sounds play on another one, like music under dialogue.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ducking {
+    trigger: Bus,
+    level: f32,
+    attack: Duration,
+    release: Duration,
+}
+
+impl Ducking {
+    /// Lowers the bus to `level`, in `0.0..=1.0`, while a sound plays on `trigger`.
+    /// Takes 100ms to lower it and 500ms to bring it back.
+    pub fn new(trigger: Bus, level: f32) -> Self {
+        Self {
+            trigger,
+            level: level.max(0.0).min(1.0),
+            attack: Duration::from_millis(100),
+            release: Duration::from_millis(500),
+        }
+    }
+
+    /// Time to lower the bus.
+    pub fn attack(self, attack: Duration) -> Self {
+        Self { attack, ..self }
+    }
+
+    /// Time to bring the bus back once `trigger` is silent.
+    pub fn release(self, release: Duration) -> Self {
+        Self { release, ..self }
+    }
+}
+
+/// A sound prepared for a bus, see `Mixer::prepare`.
+struct Prepared {
+    source: Sound,
+    tail: bool,
+    /// `source` processed by the effects of the bus, `None` while it has none.
+    sound: Option<Sound>,
+}
+
+struct BusState {
+    /// Copied by every sound and stream that plays on the bus, see `BusEffects`.
+    effects: Option<EffectChain>,
+    generation: u32,
+    /// Processed again whenever `effects` change.
+    prepared: Vec<Prepared>,
+    volume: f32,
+    ducking: Option<Ducking>,
+    /// Current level of the ducking.
+    duck: f32,
+}
+
+impl BusState {
+    const NEW: BusState = BusState {
+        effects: None,
+        generation: 0,
+        prepared: Vec::new(),
+        volume: 1.0,
+        ducking: None,
+        duck: 1.0,
+    };
+}
+
+static BUS_STATES: Global<[BusState; BUSES]> = Global::new([BusState::NEW; BUSES]);
+static LAST_UPDATE: Global<u64> = Global::new(0);
+
+/// Calls `f` with the state of `bus`.
+fn with_bus<R>(bus: Bus, f: impl FnOnce(&mut BusState) -> R) -> R {
+    BUS_STATES.with_mut(|states| f(&mut states[bus.index()]))
+}
+
+/// `sound` processed by a copy of `effects`, if there are any.
+fn process(effects: &Option<EffectChain>, sound: &Sound, tail: bool) -> Option<Sound> {
+    effects
+        .clone()
+        .map(|mut effects| sound.processed(&mut effects, tail))
+}
+
+/// The effects of a bus, copied for one stream so that streams playing at the same
+/// time, such as during a crossfade, do not share their state.
+pub(crate) struct BusEffects {
+    bus: Bus,
+    generation: u32,
+    chain: Option<EffectChain>,
+}
+
+impl BusEffects {
+    pub(crate) fn new(bus: Bus) -> Self {
+        with_bus(bus, |state| Self {
+            bus,
+            generation: state.generation,
+            chain: state.effects.clone(),
+        })
+    }
+
+    /// Processes `samples` with the effects of the bus, picking up changes to them.
+    pub(crate) fn process(&mut self, samples: &mut [i16], channels: usize, rate: u32) {
+        with_bus(self.bus, |state| {
+            if self.generation != state.generation {
+                self.generation = state.generation;
+                self.chain = state.effects.clone();
+            }
+        });
+        if let Some(chain) = self.chain.as_mut() {
+            chain.process(samples, channels, rate);
+        }
+    }
+}
+
+/// Effects, volume and ducking of the buses sounds and music play on.
+///
+/// `Music` is processed as it is decoded, up to two buffers (about a third of a second)
+/// ahead of what is heard, so changes to its effects take that long to be heard. The
+/// music of `Audio::play_music` is decoded by `Mp3Player` and only follows the volume.
+///
+/// Sounds played by `Audio` are mixed by the DSP, out of reach of the effects, so they
+/// cannot be processed as one stream like music. Each sound is processed on its own
+/// instead, ahead of time by `Mixer::prepare`, and rings out with a tail of its own.
+/// Sounds that were not prepared for their bus play without its effects.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Game {
+///     line: Sound,
+/// }
+///
+/// impl State for Game {
+///     fn init() {
+///         Mixer::set_ducking(Bus::Music, Some(Ducking::new(Bus::Dialogue, 0.3)));
+///         Mixer::set_effects(Bus::Dialogue, Some(EffectChain::new().with(Reverb::new())));
+///     }
+///
+///     fn setup(&mut self, _display: &mut Display) {
+///         Mixer::prepare(Bus::Dialogue, &self.line, false);
+///     }
+///
+///     fn update(&mut self) {
+///         if InputFrame::pad_down(Controller::One).contains(Buttons::A) {
+///             let _ = Audio::play_with(&self.line, PlayOptions::new().bus(Bus::Dialogue));
+///         }
+///     }
+/// }
+/// ```
+pub struct Mixer;
+
+impl Mixer {
+    /// Processes what plays on `bus` with `effects`, or with nothing for `None`.
+    /// Sounds already playing keep the effects they started with, and the sounds
+    /// prepared for the bus are processed again.
+    pub fn set_effects(bus: Bus, effects: Option<EffectChain>) {
+        with_bus(bus, |state| {
+            state.effects = effects.filter(|effects| !effects.is_empty());
+            state.generation = state.generation.wrapping_add(1);
+            for prepared in state.prepared.iter_mut() {
+                prepared.sound = process(&state.effects, &prepared.source, prepared.tail);
+            }
+        });
+    }
+
+    /// Processes `sound` with the effects of `bus` now, which takes time in proportion
+    /// to its length, so `Audio` plays it processed on that bus until `Mixer::discard`.
+    /// Looping sounds are not lengthened by the tail of the effects, which would leave
+    /// a gap in the loop.
+    pub fn prepare(bus: Bus, sound: &Sound, looping: bool) {
+        let tail = !looping;
+        with_bus(bus, |state| {
+            let prepared = state
+                .prepared
+                .iter()
+                .any(|prepared| prepared.tail == tail && prepared.source.is_same(sound));
+            if !prepared {
+                state.prepared.push(Prepared {
+                    source: sound.clone(),
+                    tail,
+                    sound: process(&state.effects, sound, tail),
+                });
+            }
+        });
+    }
+
+    /// Frees the copies of `sound` prepared for `bus`. It plays without effects again.
+    pub fn discard(bus: Bus, sound: &Sound) {
+        with_bus(bus, |state| {
+            state
+                .prepared
+                .retain(|prepared| !prepared.source.is_same(sound))
+        });
+    }
+
+    /// Volume of `bus`, in `0.0..=1.0`, on top of the volume of each sound.
+    pub fn set_volume(bus: Bus, volume: f32) {
+        with_bus(bus, |state| state.volume = volume.max(0.0).min(1.0));
+        Self::apply();
+    }
+
+    pub fn volume(bus: Bus) -> f32 {
+        with_bus(bus, |state| state.volume)
+    }
+
+    /// Lowers `bus` while another one plays, or stops doing so for `None`.
+    pub fn set_ducking(bus: Bus, ducking: Option<Ducking>) {
+        let released = with_bus(bus, |state| {
+            state.ducking = ducking;
+            let released = ducking.is_none() && state.duck != 1.0;
+            if released {
+                state.duck = 1.0;
+            }
+            released
+        });
+        if released {
+            Self::apply();
+        }
+    }
+
+    /// Volume of `bus`, lowered by its ducking.
+    pub fn gain(bus: Bus) -> f32 {
+        with_bus(bus, |state| state.volume * state.duck)
+    }
+
+    /// The copy of `sound` prepared for `bus`, if it has effects.
+    pub(crate) fn prepared(bus: Bus, sound: &Sound, looping: bool) -> Option<Sound> {
+        let tail = !looping;
+        with_bus(bus, |state| {
+            state
+                .prepared
+                .iter()
+                .find(|prepared| prepared.tail == tail && prepared.source.is_same(sound))
+                .and_then(|prepared| prepared.sound.clone())
+        })
+    }
+
+    fn is_playing(bus: Bus) -> bool {
+        match bus {
+            Bus::Music => Music::is_playing() || Audio::is_bus_playing(bus),
+            _ => Audio::is_bus_playing(bus),
+        }
+    }
+
+    fn apply() {
+        Audio::apply_mixer();
+        Music::apply_mixer();
+    }
+
+    /// Moves the ducking of every bus towards its level.
+    pub(crate) fn update() {
+        let now = System::get_time();
+        let last = LAST_UPDATE.replace(now);
+        let elapsed = System::ticks_to_duration(now.saturating_sub(last)).as_secs_f32();
+
+        let mut changed = false;
+        BUS_STATES.with_mut(|states| {
+            for state in states.iter_mut() {
+                let ducking = match state.ducking {
+                    Some(ducking) => ducking,
+                    None => continue,
+                };
+
+                let ducked = Self::is_playing(ducking.trigger);
+                let (target, time) = if ducked {
+                    (ducking.level, ducking.attack)
+                } else {
+                    (1.0, ducking.release)
+                };
+                if state.duck == target {
+                    continue;
+                }
+
+                // The full swing of the ducking, unless the bus is further away, for
+                // instance after the level changed while it was lowered.
+                let swing = (1.0 - ducking.level).max(libm::fabsf(target - state.duck));
+                let step = if time.is_zero() {
+                    1.0
+                } else {
+                    swing * elapsed / time.as_secs_f32()
+                };
+                state.duck = if state.duck > target {
+                    (state.duck - step).max(target)
+                } else {
+                    (state.duck + step).min(target)
+                };
+                changed = true;
+            }
+        });
+
+        if changed {
+            Self::apply();
+        }
+    }
+}
@@ -0,0 +1,579 @@
+extern crate alloc;
+
+pub use crate::decoder::Decoder;
+use crate::{
+    audio::Fade,
+    global::Global,
+    mixer::{Bus, BusEffects, Mixer},
+    wav::DSP_RATE,
+};
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::time::Duration;
+use ogc::{prelude::*, OgcError, Result};
+
+/// Samples per channel in each of the two buffers of a stream, about 170ms at 48 kHz.
+/// The engine refills them once per frame, so this leaves plenty of room for slow frames.
+const BUFFER_FRAMES: usize = 8192;
+
+/// Adapts `Mp3Decoder`, which decodes whole frames, to `Decoder`.
+pub struct Mp3Stream<R> {
+    decoder: Mp3Decoder<R>,
+    pending: Vec<i16>,
+    offset: usize,
+    info: Mp3Frame,
+    /// Byte offset of the first frame, past the ID3v2 tag if any.
+    start: u32,
+}
+
+impl<R: Read + Seek> Mp3Stream<R> {
+    /// Skips the ID3v2 tag and decodes the first frame, to learn the format of the stream.
+    /// Fails if no frame decodes.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut header = [0; 10];
+        let read = reader.read(&mut header)?;
+        let start = id3_size(&header[..read]);
+        reader.seek(start)?;
+
+        let mut decoder = Mp3Decoder::new(reader);
+        let mut pending = Vec::new();
+        let info = match decoder.next_frame(&mut pending)? {
+            Some(info) if info.rate > 0 => info,
+            _ => {
+                return Err(OgcError::Audio(
+                    "Mp3Stream::new() found no MP3 frame".into(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            decoder,
+            pending,
+            offset: 0,
+            info,
+            start,
+        })
+    }
+}
+
+/// Size of the ID3v2 tag `header` starts with, or 0 if it has none.
+fn id3_size(header: &[u8]) -> u32 {
+    if header.len() < 10 || &header[..3] != b"ID3" {
+        return 0;
+    }
+
+    // Sizes are stored in 7 bits per byte, excluding the header and the optional footer.
+    let size = header[6..10]
+        .iter()
+        .fold(0, |size, &byte| size << 7 | (byte & 0x7F) as u32);
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    10 + size + footer
+}
+
+impl<R: Read + Seek> Decoder for Mp3Stream<R> {
+    fn channels(&self) -> u16 {
+        self.info.channels.max(1)
+    }
+
+    fn rate(&self) -> u32 {
+        self.info.rate
+    }
+
+    fn decode(&mut self, samples: &mut [i16]) -> Result<usize> {
+        let mut written = 0;
+        while written < samples.len() {
+            if self.offset == self.pending.len() {
+                self.pending.clear();
+                self.offset = 0;
+                if self.decoder.next_frame(&mut self.pending)?.is_none() {
+                    break;
+                }
+            }
+
+            let count = (samples.len() - written).min(self.pending.len() - self.offset);
+            samples[written..written + count]
+                .copy_from_slice(&self.pending[self.offset..self.offset + count]);
+            written += count;
+            self.offset += count;
+        }
+        Ok(written)
+    }
+
+    /// MP3s have no index, so the position is estimated from the bit rate of the first
+    /// frame. Exact for constant bit rates and for seeking to the start.
+    fn seek(&mut self, frame: u64) -> Result<()> {
+        let offset = frame * self.info.bitrate as u64 / 8 / self.info.rate as u64;
+        self.decoder.reader_mut().seek(self.start + offset as u32)?;
+        self.decoder.reset();
+        self.pending.clear();
+        self.offset = 0;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "vorbis")]
+impl<R: Read + Seek> Decoder for VorbisDecoder<R> {
+    fn channels(&self) -> u16 {
+        VorbisDecoder::channels(self)
+    }
+
+    fn rate(&self) -> u32 {
+        VorbisDecoder::rate(self)
+    }
+
+    fn decode(&mut self, samples: &mut [i16]) -> Result<usize> {
+        let mut written = 0;
+        while written < samples.len() {
+            match self.read(&mut samples[written..])? {
+                0 => break,
+                read => written += read,
+            }
+        }
+        Ok(written)
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<()> {
+        VorbisDecoder::seek(self, frame)
+    }
+}
+
+/// A piece of music to stream with `Music`.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// // Loops back to the end of the intro, 4.5 seconds in at 44.1 kHz.
+/// let track = Track::mp3(File::open("sd:/music/level1.mp3")?)?.loop_points(198_450, None);
+/// Music::play(track)?;
+/// ```
+pub struct Track {
+    decoder: Box<dyn Decoder>,
+    looping: bool,
+    loop_start: u64,
+    loop_end: Option<u64>,
+}
+
+impl Track {
+    pub fn new<D: Decoder + 'static>(decoder: D) -> Self {
+        Self {
+            decoder: Box::new(decoder),
+            looping: false,
+            loop_start: 0,
+            loop_end: None,
+        }
+    }
+
+    /// Streams an MP3 from a file or from memory, see `Cursor`.
+    pub fn mp3<R: Read + Seek + 'static>(reader: R) -> Result<Self> {
+        Ok(Self::new(Mp3Stream::new(reader)?))
+    }
+
+    /// Streams an Ogg Vorbis file from a file or from memory, see `Cursor`.
+    #[cfg(feature = "vorbis")]
+    pub fn vorbis<R: Read + Seek + 'static>(reader: R) -> Result<Self> {
+        Ok(Self::new(VorbisDecoder::new(reader)?))
+    }
+
+    /// Restarts from the beginning at the end of the track.
+    pub fn looping(self, looping: bool) -> Self {
+        Self { looping, ..self }
+    }
+
+    /// Loops from `end`, or the end of the track, back to `start`, in samples per channel.
+    /// Playback starts at the beginning, so an intro plays once before the loop.
+    pub fn loop_points(self, start: u64, end: Option<u64>) -> Self {
+        Self {
+            looping: true,
+            loop_start: start,
+            loop_end: end,
+            ..self
+        }
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.decoder.channels()
+    }
+
+    /// Sample rate, in Hz.
+    pub fn rate(&self) -> u32 {
+        self.decoder.rate()
+    }
+}
+
+/// A track playing on a voice, fed with two buffers in turn: one plays while the
+/// other is queued, and each is refilled once it has played.
+struct Stream {
+    /// Tells streams apart, see `Music::current`.
+    id: u32,
+    track: Track,
+    /// Declared before the buffers, so it stops playing them before they are freed.
+    voice: Voice,
+    buffers: [SoundBuffer; 2],
+    /// Frame of the track each buffer starts at.
+    starts: [u64; 2],
+    /// Frames queued since the voice started, and where each buffer starts and how
+    /// long it is among them.
+    queued: u64,
+    offsets: [u64; 2],
+    lengths: [u64; 2],
+    /// Buffer to refill once the voice is ready for another one.
+    next: usize,
+    /// Frame of the track the decoder is at.
+    position: u64,
+    scratch: Vec<i16>,
+    effects: BusEffects,
+    finished: bool,
+    paused: bool,
+    volume: f32,
+    fade: Option<Fade>,
+}
+
+impl Stream {
+    fn new(track: Track, volume: f32) -> Result<Self> {
+        let channels = track.channels() as usize;
+        let voice = Voice::claim()?;
+        let id = STREAM_ID.get().wrapping_add(1);
+        STREAM_ID.set(id);
+        let mut stream = Self {
+            id,
+            track,
+            voice,
+            buffers: [
+                SoundBuffer::zeroed(BUFFER_FRAMES * channels * 2),
+                SoundBuffer::zeroed(BUFFER_FRAMES * channels * 2),
+            ],
+            starts: [0; 2],
+            queued: 0,
+            offsets: [0; 2],
+            lengths: [0; 2],
+            next: 0,
+            position: 0,
+            scratch: vec![0; BUFFER_FRAMES * channels],
+            effects: BusEffects::new(Bus::Music),
+            finished: false,
+            paused: false,
+            volume,
+            fade: None,
+        };
+
+        stream.start()?;
+        Ok(stream)
+    }
+
+    fn format(&self) -> VoiceFormat {
+        if self.track.channels() == 1 {
+            VoiceFormat::Mono16Bit
+        } else {
+            VoiceFormat::Stereo16Bit
+        }
+    }
+
+    /// Starts the voice with both buffers, from the current position of the decoder.
+    fn start(&mut self) -> Result<()> {
+        let _ = self.voice.stop();
+        self.finished = false;
+        self.queued = 0;
+
+        if !self.fill(0)? {
+            self.finished = true;
+            return Ok(());
+        }
+        let (left, right) = self.volumes();
+        let options = VoiceOptions::new()
+            .format(self.format())
+            .pitch(self.track.rate())
+            .volume_left(left)
+            .volume_right(right);
+        self.voice.play(options, self.buffers[0].clone())?;
+        if self.paused {
+            self.voice.pause(true)?;
+        }
+
+        self.next = 1;
+        self.queue()
+    }
+
+    /// Refills the next buffer and queues it behind the playing one.
+    fn queue(&mut self) -> Result<()> {
+        let next = self.next;
+        // The voice is done with the buffer, so it can be written to again.
+        self.voice.release_buffers();
+        if self.fill(next)? {
+            self.voice.queue(self.buffers[next].clone())?;
+            self.next ^= 1;
+        } else {
+            self.finished = true;
+        }
+        Ok(())
+    }
+
+    /// Decodes into buffer `index`, following the loop points. Returns false at the end
+    /// of the track.
+    fn fill(&mut self, index: usize) -> Result<bool> {
+        let channels = self.track.channels() as u64;
+        let start = self.position;
+        let mut written = 0;
+        let mut looped = false;
+
+        while written < self.scratch.len() {
+            let mut wanted = self.scratch.len() - written;
+            if let (true, Some(end)) = (self.track.looping, self.track.loop_end) {
+                let left = end.saturating_sub(self.position) * channels;
+                wanted = wanted.min(left as usize);
+            }
+
+            let read = if wanted == 0 {
+                0
+            } else {
+                self.track
+                    .decoder
+                    .decode(&mut self.scratch[written..written + wanted])?
+            };
+
+            if read == 0 {
+                // Stop instead of spinning on a loop that produces no samples.
+                if !self.track.looping || looped {
+                    break;
+                }
+                self.track.decoder.seek(self.track.loop_start)?;
+                self.position = self.track.loop_start;
+                looped = true;
+                continue;
+            }
+
+            looped = false;
+            written += read;
+            self.position += read as u64 / channels;
+        }
+
+        if written == 0 {
+            return Ok(false);
+        }
+
+        self.starts[index] = start;
+        self.offsets[index] = self.queued;
+        self.lengths[index] = written as u64 / channels;
+        self.queued += self.lengths[index];
+        let rate = self.track.rate();
+        self.effects
+            .process(&mut self.scratch[..written], channels as usize, rate);
+        let scratch = &self.scratch[..written];
+        self.buffers[index].write(|buffer| {
+            for (bytes, sample) in buffer.chunks_exact_mut(2).zip(scratch) {
+                bytes.copy_from_slice(&sample.to_be_bytes());
+            }
+            written * 2
+        })?;
+        Ok(true)
+    }
+
+    fn volumes(&self) -> (u8, u8) {
+        let volume = self.volume * VOLUME.get() * Mixer::gain(Bus::Music);
+        let volume = volume.max(0.0).min(1.0) * 255.0;
+        (volume as u8, volume as u8)
+    }
+
+    fn apply_volume(&mut self) {
+        let (left, right) = self.volumes();
+        let _ = self.voice.set_volume(left, right);
+    }
+
+    /// Frame of the track the voice is playing.
+    fn played(&self) -> u64 {
+        // The voice counts samples at the rate of the DSP since it started.
+        let elapsed = self.voice.position() as u64 * self.track.rate() as u64 / DSP_RATE as u64;
+        (0..2)
+            .find(|&i| elapsed >= self.offsets[i] && elapsed - self.offsets[i] < self.lengths[i])
+            .map_or(
+                // The buffer before `next` is queued, the one at `next` is playing.
+                self.starts[self.next],
+                |i| self.starts[i] + elapsed - self.offsets[i],
+            )
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<()> {
+        self.track.decoder.seek(frame)?;
+        self.position = frame;
+        self.start()
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        let _ = self.voice.pause(paused);
+    }
+
+    /// Keeps the voice fed and advances the fade. Returns false once the stream is over.
+    fn update(&mut self, now: u64) -> Result<bool> {
+        if let Some(fade) = self.fade.as_ref() {
+            let (volume, done) = fade.volume(now);
+            let stop = done && fade.stop;
+            self.volume = volume;
+            self.apply_volume();
+            if stop {
+                return Ok(false);
+            }
+            if done {
+                self.fade = None;
+            }
+        }
+
+        let working = self.voice.status() == VoiceStatus::Working;
+        if self.finished {
+            return Ok(working || self.paused);
+        }
+        if !working && !self.paused {
+            // Starved by a long frame, start over from where the decoder is.
+            self.start()?;
+        } else if self.voice.is_ready() {
+            self.queue()?;
+        }
+        Ok(true)
+    }
+}
+
+static CURRENT: Global<Option<Stream>> = Global::new(None);
+/// The previous track, fading out during a crossfade.
+static OUTGOING: Global<Option<Stream>> = Global::new(None);
+static VOLUME: Global<f32> = Global::new(1.0);
+static STREAM_ID: Global<u32> = Global::new(0);
+
+/// Calls `f` with the current stream, then with the outgoing one.
+fn for_each_stream(mut f: impl FnMut(&mut Option<Stream>)) {
+    CURRENT.with_mut(&mut f);
+    OUTGOING.with_mut(f);
+}
+
+/// Music streamed from files or memory, decoded a little at a time while it plays.
+///
+/// Unlike `Audio::play_music`, tracks do not need to fit in memory, can loop between
+/// loop points, seek, and crossfade into each other. Ogg Vorbis needs the `vorbis` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Game;
+///
+/// impl State for Game {
+///     fn init() {
+///         let track = Track::mp3(File::open("sd:/music/menu.mp3").unwrap()).unwrap();
+///         Music::play(track.looping(true)).unwrap();
+///     }
+///
+///     fn update(&mut self) {
+///         if InputFrame::pad_down(Controller::One).contains(Buttons::START) {
+///             let track = Track::mp3(File::open("sd:/music/level1.mp3").unwrap()).unwrap();
+///             Music::crossfade(track.looping(true), Duration::from_secs(2)).unwrap();
+///         }
+///     }
+/// }
+/// ```
+pub struct Music;
+
+impl Music {
+    /// Plays `track` from the start, stopping the music that was playing.
+    pub fn play(track: Track) -> Result<()> {
+        Self::stop();
+        CURRENT.replace(Some(Stream::new(track, 1.0)?));
+        Ok(())
+    }
+
+    /// Fades the current music out while `track` fades in, over `duration`.
+    pub fn crossfade(track: Track, duration: Duration) -> Result<()> {
+        // A crossfade that is still going on is cut short.
+        OUTGOING.replace(None);
+        let mut incoming = Stream::new(track, 0.0)?;
+        incoming.fade = Some(Fade::new(0.0, 1.0, duration, false));
+
+        if let Some(mut outgoing) = CURRENT.replace(Some(incoming)) {
+            outgoing.fade = Some(Fade::new(outgoing.volume, 0.0, duration, true));
+            OUTGOING.replace(Some(outgoing));
+        }
+        Ok(())
+    }
+
+    /// Stops the music immediately.
+    pub fn stop() {
+        CURRENT.replace(None);
+        OUTGOING.replace(None);
+    }
+
+    /// Fades the music out over `duration`, then stops it.
+    pub fn fade_out(duration: Duration) {
+        CURRENT.with_mut(|current| {
+            if let Some(stream) = current {
+                stream.fade = Some(Fade::new(stream.volume, 0.0, duration, true));
+            }
+        });
+    }
+
+    pub fn pause(paused: bool) {
+        for_each_stream(|stream| {
+            if let Some(stream) = stream {
+                stream.set_paused(paused);
+            }
+        });
+    }
+
+    pub fn is_playing() -> bool {
+        CURRENT.with(|current| current.is_some())
+    }
+
+    pub fn is_paused() -> bool {
+        CURRENT.with(|current| current.as_ref().map_or(false, |stream| stream.paused))
+    }
+
+    /// Jumps to `position` from the start of the track.
+    pub fn seek(position: Duration) -> Result<()> {
+        CURRENT.with_mut(|current| match current {
+            Some(stream) => {
+                let frame = position.as_secs_f64() * stream.track.rate() as f64;
+                stream.seek(frame as u64)
+            }
+            None => Ok(()),
+        })
+    }
+
+    /// Position in the track.
+    pub fn position() -> Duration {
+        CURRENT.with(|current| {
+            current.as_ref().map_or(Duration::ZERO, |stream| {
+                Duration::from_secs_f64(stream.played() as f64 / stream.track.rate().max(1) as f64)
+            })
+        })
+    }
+
+    /// Id and playing frame of the current track. The id changes with every track played.
+    pub(crate) fn current() -> Option<(u32, u64)> {
+        CURRENT.with(|current| current.as_ref().map(|stream| (stream.id, stream.played())))
+    }
+
+    /// Volume of the music, in `0.0..=1.0`.
+    pub fn set_volume(volume: f32) {
+        VOLUME.set(volume);
+        Self::apply_mixer();
+    }
+
+    /// Follows changes to the volume of `Bus::Music`.
+    pub(crate) fn apply_mixer() {
+        for_each_stream(|stream| {
+            if let Some(stream) = stream {
+                stream.apply_volume();
+            }
+        });
+    }
+
+    /// Feeds the voices and advances crossfades. Tracks that fail to decode are stopped.
+    pub(crate) fn update() {
+        let now = System::get_time();
+        for_each_stream(|stream| {
+            if let Some(playing) = stream.as_mut() {
+                if !playing.update(now).unwrap_or(false) {
+                    *stream = None;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{display::Display, frame::InputFrame, global::Global};
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::{Point, Size},
+    primitives::{ContainsPoint, Triangle},
+};
+use ogc::prelude::*;
+
+/// Where a Wii Remote points, see `Pointer::state`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PointerState {
+    /// Smoothed position in display coordinates. Kept at the last known position
+    /// while the pointer is lost.
+    pub x: f32,
+    pub y: f32,
+    /// The remote sees the sensor bar, or lost it less than the grace period ago.
+    pub visible: bool,
+    /// The position lies within the display.
+    pub on_screen: bool,
+    /// Frames since the remote last saw the sensor bar.
+    pub frames_lost: u32,
+}
+
+impl PointerState {
+    const HIDDEN: PointerState = PointerState {
+        x: 0.0,
+        y: 0.0,
+        visible: false,
+        on_screen: false,
+        frames_lost: u32::MAX,
+    };
+
+    pub fn position(&self) -> Point {
+        Point::new(libm::roundf(self.x) as i32, libm::roundf(self.y) as i32)
+    }
+
+    /// Position clamped to the edge of `bounds`, for indicators pointing towards an
+    /// off-screen pointer.
+    pub fn clamped(&self, bounds: Size) -> Point {
+        let position = self.position();
+        Point::new(
+            position.x.max(0).min(bounds.width as i32 - 1),
+            position.y.max(0).min(bounds.height as i32 - 1),
+        )
+    }
+}
+
+static STATES: Global<[PointerState; 4]> = Global::new([PointerState::HIDDEN; 4]);
+static BOUNDS: Global<Size> = Global::new(Size::new(640, 528));
+static SMOOTHING: Global<f32> = Global::new(0.5);
+static GRACE_FRAMES: Global<u32> = Global::new(10);
+static CURSORS_VISIBLE: Global<bool> = Global::new(false);
+
+/// Colors the Wii uses for the cursors of players one to four.
+pub const CURSOR_COLORS: [Rgb888; 4] = [
+    Rgb888::new(0x00, 0x9A, 0xFF),
+    Rgb888::new(0xFF, 0x40, 0x40),
+    Rgb888::new(0x30, 0xD0, 0x30),
+    Rgb888::new(0xFF, 0xD0, 0x00),
+];
+
+/// The Wii Remotes as pointers on the display, updated from `InputFrame` once per frame.
+///
+/// Remotes briefly losing the sensor bar keep their cursor for a grace period, so it does
+/// not flicker near the edges of the screen.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::{embedded_graphics::primitives::Rectangle, prelude::*};
+///
+/// struct Menu {
+///     start: Rectangle,
+/// }
+///
+/// impl State for Menu {
+///     fn init() {
+///         Pointer::set_cursors_visible(true);
+///     }
+///
+///     fn update(&mut self) {
+///         if Pointer::clicked(Remote::One, &self.start) {
+///             // Start the game
+///         }
+///     }
+///
+///     fn draw(&self, display: &mut Display) -> Result<(), DrawError> {
+///         let color = if Pointer::hovers(Remote::One, &self.start) {
+///             Rgb::YELLOW
+///         } else {
+///             Rgb::WHITE
+///         };
+///         display.fill_solid(&self.start, color)
+///     }
+/// }
+/// ```
+pub struct Pointer;
+
+impl Pointer {
+    pub fn state(remote: Remote) -> PointerState {
+        STATES.with(|states| states[remote as usize])
+    }
+
+    /// Position of the pointer, if it is visible.
+    pub fn position(remote: Remote) -> Option<Point> {
+        let state = Self::state(remote);
+        if state.visible {
+            Some(state.position())
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the pointer of `remote` is visible and within `area`.
+    pub fn hovers<T: ContainsPoint>(remote: Remote, area: &T) -> bool {
+        Self::position(remote).map_or(false, |position| area.contains(position))
+    }
+
+    /// Returns true if A was pressed this frame while hovering `area`.
+    pub fn clicked<T: ContainsPoint>(remote: Remote, area: &T) -> bool {
+        Self::hovers(remote, area) && InputFrame::remote_down(remote).contains(WpadButtons::A)
+    }
+
+    /// Returns true if A is held while hovering `area`.
+    pub fn pressing<T: ContainsPoint>(remote: Remote, area: &T) -> bool {
+        Self::hovers(remote, area) && InputFrame::remote_held(remote).contains(WpadButtons::A)
+    }
+
+    /// First remote hovering `area`.
+    pub fn hovered_by<T: ContainsPoint>(area: &T) -> Option<Remote> {
+        Remote::ALL
+            .iter()
+            .copied()
+            .find(|&remote| Self::hovers(remote, area))
+    }
+
+    /// First remote that clicked `area` this frame.
+    pub fn clicked_by<T: ContainsPoint>(area: &T) -> Option<Remote> {
+        Remote::ALL
+            .iter()
+            .copied()
+            .find(|&remote| Self::clicked(remote, area))
+    }
+
+    /// How much of the previous position is kept every frame, in `0.0..1.0`.
+    /// Higher values are steadier but lag behind. Defaults to 0.5.
+    pub fn set_smoothing(smoothing: f32) {
+        SMOOTHING.set(smoothing.max(0.0).min(0.99))
+    }
+
+    /// Frames the pointer stays visible after the sensor bar was lost. Defaults to 10.
+    pub fn set_grace_frames(frames: u32) {
+        GRACE_FRAMES.set(frames)
+    }
+
+    /// Lets `Engine::run` draw a cursor for every visible pointer after `State::draw`.
+    pub fn set_cursors_visible(visible: bool) {
+        CURSORS_VISIBLE.set(visible)
+    }
+
+    pub fn are_cursors_visible() -> bool {
+        CURSORS_VISIBLE.get()
+    }
+
+    /// Draws the default arrow cursor with its tip at `position`.
+    pub fn draw_cursor(
+        display: &mut Display,
+        position: Point,
+        color: Rgb888,
+    ) -> Result<(), crate::DrawError> {
+        let arrow = |inset: i32| {
+            Triangle::new(
+                position + Point::new(inset, 2 * inset),
+                position + Point::new(inset, 22 - inset),
+                position + Point::new(16 - 2 * inset, 16 - inset),
+            )
+        };
+        display.fill_triangle(&arrow(0), Rgb888::new(0x10, 0x10, 0x10))?;
+        display.fill_triangle(&arrow(2), color)
+    }
+
+    pub(crate) fn set_bounds(bounds: Size) {
+        BOUNDS.set(bounds)
+    }
+
+    pub(crate) fn draw_cursors(display: &mut Display) -> Result<(), crate::DrawError> {
+        for remote in Remote::ALL {
+            if let Some(position) = Self::position(remote) {
+                Self::draw_cursor(display, position, CURSOR_COLORS[remote as usize])?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn record(frame: &InputFrame) {
+        let (smoothing, grace, bounds) = (SMOOTHING.get(), GRACE_FRAMES.get(), BOUNDS.get());
+
+        STATES.with_mut(|states| {
+            for remote in Remote::ALL {
+                let state = &mut states[remote as usize];
+
+                match frame.remote(remote).pointer {
+                    Some((x, y)) => {
+                        if state.frames_lost > grace {
+                            // Reacquired, snap instead of sliding in from the old position
+                            state.x = x;
+                            state.y = y;
+                        } else {
+                            state.x += (x - state.x) * (1.0 - smoothing);
+                            state.y += (y - state.y) * (1.0 - smoothing);
+                        }
+                        state.frames_lost = 0;
+                    }
+                    None => state.frames_lost = state.frames_lost.saturating_add(1),
+                }
+
+                state.visible = state.frames_lost <= grace;
+                state.on_screen = state.visible
+                    && (0.0..bounds.width as f32).contains(&state.x)
+                    && (0.0..bounds.height as f32).contains(&state.y);
+            }
+        });
+    }
+}
@@ -0,0 +1,113 @@
+use crate::global::Global;
+
+/// A xorshift64* generator of its own, for sequences that must not depend on `Random`,
+/// such as the noise of `Oscillator` and the presets of `Sfx`.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// let mut rng = Rng::new(42);
+/// let x = rng.range(0, 640);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        // xorshift gets stuck at zero
+        Self(if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        })
+    }
+
+    /// Next number of the xorshift64* sequence.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// A number in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// A number in `min..max`. Returns `min` if the range is empty.
+    pub fn range(&mut self, min: i32, max: i32) -> i32 {
+        if max <= min {
+            return min;
+        }
+        let span = (max as i64 - min as i64) as u64;
+        (min as i64 + (self.next_u64() % span) as i64) as i32
+    }
+
+    /// A number in `min..max`.
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// Returns true with a probability of `probability`, in `0.0..=1.0`.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+static SEED: Global<u64> = Global::new(0);
+static RNG: Global<Rng> = Global::new(Rng::new(0));
+
+/// Pseudo-random numbers shared by the engine and the game.
+///
+/// `Engine::run` seeds the generator from the clock, and replays restore the seed
+/// they were recorded with, so games drawing every random number from here stay
+/// deterministic under replays.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// let x = Random::range(0, 640);
+/// let chance = Random::next_f32();
+/// ```
+pub struct Random;
+
+impl Random {
+    /// Seed the generator was last reset with.
+    pub fn seed() -> u64 {
+        SEED.get()
+    }
+
+    /// Resets the generator, so it produces the same sequence as the last time it had this seed.
+    pub fn set_seed(seed: u64) {
+        SEED.set(seed);
+        RNG.set(Rng::new(seed));
+    }
+
+    /// Next number of the xorshift64* sequence.
+    pub fn next_u64() -> u64 {
+        RNG.with_mut(Rng::next_u64)
+    }
+
+    pub fn next_u32() -> u32 {
+        RNG.with_mut(Rng::next_u32)
+    }
+
+    /// A number in `0.0..1.0`.
+    pub fn next_f32() -> f32 {
+        RNG.with_mut(Rng::next_f32)
+    }
+
+    /// A number in `min..max`. Returns `min` if the range is empty.
+    pub fn range(min: i32, max: i32) -> i32 {
+        RNG.with_mut(|rng| rng.range(min, max))
+    }
+}
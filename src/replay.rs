@@ -0,0 +1,419 @@
+extern crate alloc;
+
+use crate::{
+    connection::Connections,
+    engine::State,
+    frame::{InputFrame, FRAME_SIZE},
+    global::Global,
+    random::Random,
+};
+use alloc::{format, vec::Vec};
+use ogc::{prelude::*, OgcError, Result};
+
+const MAGIC: &[u8; 4] = b"OGCR";
+const VERSION: u8 = 2;
+const HEADER_SIZE: usize = 4 + 1 + 8 + 4;
+
+/// Input of every frame of a play session, with the seed of `Random` it started from.
+///
+/// # Format
+///
+/// All numbers are big-endian.
+///
+/// | Bytes | Content                                   |
+/// |-------|-------------------------------------------|
+/// | 4     | `OGCR`                                    |
+/// | 1     | Version, currently 2                      |
+/// | 8     | Seed of `Random`                          |
+/// | 4     | Number of frames                          |
+/// | ...   | Runs of `u16` repeat count and a frame    |
+///
+/// Consecutive identical frames are stored once, so idle stretches take almost no space.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    seed: u64,
+    frames: Vec<InputFrame>,
+}
+
+impl Recording {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn frames(&self) -> &[InputFrame] {
+        &self.frames
+    }
+
+    pub fn push(&mut self, frame: InputFrame) {
+        self.frames.push(frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HEADER_SIZE);
+        data.extend_from_slice(MAGIC);
+        data.push(VERSION);
+        data.extend_from_slice(&self.seed.to_be_bytes());
+        data.extend_from_slice(&(self.frames.len() as u32).to_be_bytes());
+
+        let mut frames = self.frames.iter().peekable();
+        let mut bytes = [0; FRAME_SIZE];
+        while let Some(frame) = frames.next() {
+            let mut repeat = 1u16;
+            while repeat < u16::MAX && frames.peek() == Some(&frame) {
+                frames.next();
+                repeat += 1;
+            }
+
+            frame.encode(&mut bytes);
+            data.extend_from_slice(&repeat.to_be_bytes());
+            data.extend_from_slice(&bytes);
+        }
+
+        data
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < HEADER_SIZE || &data[0..4] != MAGIC {
+            return Err(OgcError::Fs(
+                "Recording::decode() found no recording".into(),
+            ));
+        }
+        if data[4] != VERSION {
+            return Err(OgcError::Fs(format!(
+                "Recording::decode() does not support version {}",
+                data[4]
+            )));
+        }
+
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&data[5..13]);
+        let count = u32::from_be_bytes([data[13], data[14], data[15], data[16]]) as usize;
+
+        // The count comes from the file, so only reserve what the runs can actually hold.
+        let runs = data[HEADER_SIZE..].chunks(2 + FRAME_SIZE);
+        let mut recording = Self::new(u64::from_be_bytes(seed));
+        recording
+            .frames
+            .reserve(count.min(runs.len() * u16::MAX as usize));
+        for run in runs {
+            if run.len() != 2 + FRAME_SIZE {
+                return Err(OgcError::Fs(
+                    "Recording::decode() found a truncated frame".into(),
+                ));
+            }
+            let repeat = u16::from_be_bytes([run[0], run[1]]);
+            if repeat as usize > count - recording.frames.len() {
+                return Err(OgcError::Fs(format!(
+                    "Recording::decode() found more than the {} frames expected",
+                    count
+                )));
+            }
+            let frame = InputFrame::decode(&run[2..]);
+            recording
+                .frames
+                .extend(core::iter::repeat_n(frame, repeat as usize));
+        }
+
+        if recording.frames.len() != count {
+            return Err(OgcError::Fs(format!(
+                "Recording::decode() expected {} frames, found {}",
+                count,
+                recording.frames.len()
+            )));
+        }
+        Ok(recording)
+    }
+
+    /// Writes the recording to a file, for instance on the SD card.
+    #[cfg(target_os = "rvl-ios")]
+    pub fn save(&self, path: &str) -> Result<()> {
+        File::create(path)?.write_all(&self.encode())
+    }
+
+    #[cfg(target_os = "rvl-ios")]
+    pub fn load(path: &str) -> Result<Self> {
+        Self::decode(&File::open(path)?.read_to_end()?)
+    }
+}
+
+enum Mode {
+    Live,
+    Recording(Recording),
+    Playing(Recording, usize),
+}
+
+static MODE: Global<Mode> = Global::new(Mode::Live);
+
+/// Records the input `Engine::run` reads every frame, or plays it back.
+///
+/// During playback the controllers are ignored and `InputFrame::current`, and with it
+/// `Input`, report the recorded frames instead. Games that read their input from there
+/// and their random numbers from `Random` play out identically.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Game;
+///
+/// impl State for Game {
+///     fn update(&mut self) {
+///         if InputFrame::pad_down(Controller::One).contains(Buttons::X) {
+///             match Replay::stop_recording() {
+///                 Some(recording) => recording.save("sd:/bug.rec").unwrap(),
+///                 None => Replay::start_recording(),
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub struct Replay;
+
+impl Replay {
+    /// Starts recording from the next frame on. Reseeds `Random`, so the game should be
+    /// in the state it will be in when the recording is played back, usually at a fresh start.
+    pub fn start_recording() {
+        let seed = System::get_time();
+        Random::set_seed(seed);
+        MODE.replace(Mode::Recording(Recording::new(seed)));
+    }
+
+    /// Stops recording and returns what was recorded, if anything was.
+    pub fn stop_recording() -> Option<Recording> {
+        match MODE.replace(Mode::Live) {
+            Mode::Recording(recording) => Some(recording),
+            mode => {
+                MODE.replace(mode);
+                None
+            }
+        }
+    }
+
+    /// Plays `recording` back from the next frame on, restoring its seed of `Random`.
+    pub fn play(recording: Recording) {
+        Random::set_seed(recording.seed());
+        MODE.replace(Mode::Playing(recording, 0));
+    }
+
+    /// Stops playback and returns to the controllers.
+    pub fn stop_playback() {
+        MODE.with_mut(|mode| {
+            if let Mode::Playing(..) = mode {
+                *mode = Mode::Live;
+            }
+        });
+    }
+
+    pub fn is_recording() -> bool {
+        MODE.with(|mode| matches!(mode, Mode::Recording(_)))
+    }
+
+    pub fn is_playing() -> bool {
+        MODE.with(|mode| matches!(mode, Mode::Playing(..)))
+    }
+
+    /// Frames played so far and the length of the recording, during playback.
+    pub fn progress() -> Option<(usize, usize)> {
+        MODE.with(|mode| match mode {
+            Mode::Playing(recording, position) => Some((*position, recording.len())),
+            _ => None,
+        })
+    }
+
+    /// Runs `state` through every frame of `recording` without drawing or waiting for
+    /// the display, calling `State::update` once per frame. Meant for test drivers
+    /// that check the outcome of a recorded session.
+    pub fn run<T: State>(state: &mut T, recording: &Recording) {
+        Random::set_seed(recording.seed());
+        for frame in recording.frames() {
+            InputFrame::advance(*frame);
+            Connections::update(state);
+            state.update();
+        }
+    }
+
+    /// Makes the input for this frame current and reports the controllers plugged in or
+    /// removed to `state`: the next recorded frame during playback, `live` otherwise.
+    /// Playback ends after the last recorded frame.
+    #[cfg_attr(not(target_os = "rvl-ios"), allow(dead_code))]
+    pub(crate) fn advance<T: State>(state: &mut T, live: impl FnOnce() -> InputFrame) {
+        InputFrame::advance(Self::next_frame(live));
+        Connections::update(state);
+    }
+
+    #[cfg_attr(not(target_os = "rvl-ios"), allow(dead_code))]
+    fn next_frame(live: impl FnOnce() -> InputFrame) -> InputFrame {
+        let recorded = MODE.with_mut(|mode| match mode {
+            Mode::Playing(recording, position) => {
+                let frame = recording.frames().get(*position).copied();
+                match frame {
+                    Some(_) => *position += 1,
+                    None => *mode = Mode::Live,
+                }
+                frame
+            }
+            _ => None,
+        });
+        if let Some(frame) = recorded {
+            return frame;
+        }
+
+        let frame = live();
+        MODE.with_mut(|mode| {
+            if let Mode::Recording(recording) = mode {
+                recording.push(frame);
+            }
+        });
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analog::StickVector,
+        connection::{ConnectionEvent, Device},
+        frame::ExpansionFrame,
+    };
+
+    /// Keeps what it saw every frame and draws a random number, like a game would.
+    #[derive(Default)]
+    struct Game {
+        frames: Vec<InputFrame>,
+        events: Vec<ConnectionEvent>,
+        numbers: Vec<u64>,
+    }
+
+    impl State for Game {
+        fn connection(&mut self, event: ConnectionEvent) {
+            self.events.push(event);
+        }
+
+        fn update(&mut self) {
+            self.frames.push(InputFrame::current());
+            self.numbers.push(Random::next_u64());
+        }
+    }
+
+    /// Steps `game` through `live` the way `Engine::run` does, without the hardware.
+    fn drive(game: &mut Game, live: &[InputFrame]) {
+        InputFrame::advance(InputFrame::EMPTY);
+        for frame in live {
+            Replay::advance(game, || *frame);
+            game.update();
+        }
+    }
+
+    /// Input that survives quantization, with held buttons for runs and a controller
+    /// plugged in and out.
+    fn script() -> Vec<InputFrame> {
+        let mut frames = Vec::new();
+        let mut frame = InputFrame::EMPTY;
+        frames.push(frame);
+
+        frame.pads[0].connected = true;
+        frame.pads[0].buttons = Buttons::A;
+        frame.pads[0].stick_x = -72;
+        frame.pads[0].trigger_r = 150;
+        frames.extend(core::iter::repeat_n(frame, 5));
+
+        frame.remotes[1].connected = true;
+        frame.remotes[1].buttons = WpadButtons::B | WpadButtons::NUNCHUK_Z;
+        frame.remotes[1].pointer = Some((320.5, -12.25));
+        frame.remotes[1].expansion = ExpansionFrame::Nunchuk {
+            stick: StickVector::new(64.0 / 127.0, -1.0),
+        };
+        frames.push(frame);
+
+        frame.pads[0] = Default::default();
+        frame.remotes[1].pointer = None;
+        frame.remotes[1].expansion = ExpansionFrame::Classic {
+            left_stick: StickVector::new(1.0, 0.0),
+            right_stick: StickVector::new(0.0, -5.0 / 127.0),
+            left_trigger: 51.0 / 255.0,
+            right_trigger: 1.0,
+        };
+        frames.extend(core::iter::repeat_n(frame, 3));
+        frames
+    }
+
+    // The only test touching the statics of the engine, as the tests run in parallel.
+    #[test]
+    fn round_trip() {
+        let script = script();
+
+        let mut recorded = Game::default();
+        Replay::start_recording();
+        drive(&mut recorded, &script);
+        let recording = Replay::stop_recording().unwrap();
+        assert_eq!(recording.frames(), &script[..]);
+        assert_eq!(recorded.frames, script);
+        assert_eq!(
+            recorded.events,
+            [
+                ConnectionEvent::Connected(Device::Pad(Controller::One)),
+                ConnectionEvent::Connected(Device::Remote(Remote::Two)),
+                ConnectionEvent::Disconnected(Device::Pad(Controller::One)),
+            ]
+        );
+
+        let decoded = Recording::decode(&recording.encode()).unwrap();
+        assert_eq!(decoded, recording);
+
+        let mut run = Game::default();
+        InputFrame::advance(InputFrame::EMPTY);
+        Replay::run(&mut run, &decoded);
+        assert_eq!(run.frames, recorded.frames);
+        assert_eq!(run.events, recorded.events);
+        assert_eq!(run.numbers, recorded.numbers);
+
+        // Playback ignores the controllers until the recording ends.
+        let mut played = Game::default();
+        Replay::play(decoded);
+        let live = vec![InputFrame::EMPTY; script.len() + 1];
+        drive(&mut played, &live);
+        assert!(!Replay::is_playing());
+        assert_eq!(played.frames[..script.len()], recorded.frames[..]);
+        assert_eq!(played.frames[script.len()], InputFrame::EMPTY);
+        assert_eq!(played.numbers[..script.len()], recorded.numbers[..]);
+    }
+
+    #[test]
+    fn decode_rejects_frame_counts() {
+        let mut recording = Recording::new(7);
+        for _ in 0..3 {
+            recording.push(InputFrame::EMPTY);
+        }
+        let data = recording.encode();
+
+        // More frames than the header announces fail in the first run that exceeds it.
+        let mut short = data.clone();
+        short[13..17].copy_from_slice(&2u32.to_be_bytes());
+        assert!(Recording::decode(&short).is_err());
+
+        // A huge count with a single run neither reserves for it nor decodes.
+        let mut long = data.clone();
+        long[13..17].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(Recording::decode(&long).is_err());
+
+        assert!(Recording::decode(&data[..data.len() - 1]).is_err());
+        assert_eq!(Recording::decode(&data).unwrap(), recording);
+    }
+}
@@ -0,0 +1,271 @@
+extern crate alloc;
+
+use crate::{
+    connection::{Connections, Device},
+    global::Global,
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+use ogc::prelude::*;
+
+/// A segment of a `RumblePattern`, ramping linearly from one intensity to another.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RumbleStep {
+    pub duration: Duration,
+    /// Intensity at the start of the step, in `0.0..=1.0`.
+    pub from: f32,
+    /// Intensity at the end of the step, in `0.0..=1.0`.
+    pub to: f32,
+}
+
+/// A sequence of rumble intensities over time.
+///
+/// The motors can only be switched on or off, so intensities in between are
+/// approximated by switching the motor every frame.
+///
+/// # Example
+///
+/// ```rust
+/// let heartbeat = RumblePattern::new()
+///     .hold(1.0, Duration::from_millis(80))
+///     .pause(Duration::from_millis(120))
+///     .hold(0.6, Duration::from_millis(80))
+///     .pause(Duration::from_millis(600))
+///     .looping(true);
+/// Rumble::play(Device::Pad(Controller::One), &heartbeat);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RumblePattern {
+    steps: Vec<RumbleStep>,
+    looping: bool,
+}
+
+impl RumblePattern {
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            looping: false,
+        }
+    }
+
+    /// Rumbles at a constant intensity.
+    pub fn hold(self, intensity: f32, duration: Duration) -> Self {
+        self.ramp(intensity, intensity, duration)
+    }
+
+    /// Rumbles from `from` to `to`, changing linearly over `duration`.
+    pub fn ramp(mut self, from: f32, to: f32, duration: Duration) -> Self {
+        self.steps.push(RumbleStep { duration, from, to });
+        self
+    }
+
+    /// Stays still for `duration`.
+    pub fn pause(self, duration: Duration) -> Self {
+        self.hold(0.0, duration)
+    }
+
+    /// Restarts the pattern when it ends, until stopped.
+    pub fn looping(self, looping: bool) -> Self {
+        Self { looping, ..self }
+    }
+
+    pub fn steps(&self) -> &[RumbleStep] {
+        &self.steps
+    }
+
+    /// Total duration of a single run of the pattern.
+    pub fn duration(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    /// Intensity `elapsed` after the start, or `None` once a non-looping pattern ended.
+    fn intensity_at(&self, elapsed: Duration) -> Option<f32> {
+        let total = self.duration();
+        if total.is_zero() {
+            return None;
+        }
+
+        let mut elapsed = if self.looping {
+            Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64)
+        } else if elapsed >= total {
+            return None;
+        } else {
+            elapsed
+        };
+
+        for step in self.steps.iter() {
+            if elapsed < step.duration {
+                let t = elapsed.as_secs_f32() / step.duration.as_secs_f32();
+                return Some(step.from + (step.to - step.from) * t);
+            }
+            elapsed -= step.duration;
+        }
+        None
+    }
+}
+
+struct Channel {
+    pattern: Option<RumblePattern>,
+    started: u64,
+    accumulator: f32,
+    motor: bool,
+}
+
+impl Channel {
+    const IDLE: Channel = Channel {
+        pattern: None,
+        started: 0,
+        accumulator: 0.0,
+        motor: false,
+    };
+}
+
+// Channels 0 to 3 are the GameCube controllers, 4 to 7 the Wii Remotes.
+static CHANNELS: Global<[Channel; 8]> = Global::new([Channel::IDLE; 8]);
+
+/// Calls `f` with the channel of `device`.
+fn with_channel<R>(device: Device, f: impl FnOnce(&mut Channel) -> R) -> R {
+    let index = match device {
+        Device::Pad(pad) => pad as usize,
+        Device::Remote(remote) => 4 + remote as usize,
+    };
+    CHANNELS.with_mut(|channels| f(&mut channels[index]))
+}
+
+fn devices() -> impl Iterator<Item = Device> {
+    Controller::ALL
+        .iter()
+        .map(|&pad| Device::Pad(pad))
+        .chain(Remote::ALL.iter().map(|&remote| Device::Remote(remote)))
+}
+
+fn set_motor(device: Device, on: bool, hard: bool) {
+    let changed = with_channel(device, |channel| {
+        let changed = channel.motor != on;
+        channel.motor = on;
+        changed
+    });
+    if !changed && !hard {
+        return;
+    }
+
+    match device {
+        Device::Pad(pad) => {
+            let command = match (on, hard) {
+                (true, _) => MotorCommand::Rumble,
+                (false, true) => MotorCommand::StopHard,
+                (false, false) => MotorCommand::Stop,
+            };
+            Pad::control_motor(pad, command);
+        }
+        Device::Remote(remote) => {
+            // Fails if the remote is not connected, in which case it is not rumbling anyway.
+            let _ = Wpad::rumble(remote, on);
+        }
+    }
+}
+
+/// Rumble motors of the GameCube controllers and Wii Remotes.
+///
+/// Patterns are advanced by `Engine::run` once per frame, and stop when the
+/// controller is disconnected.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// Rumble::pulse(Device::Pad(Controller::One), Duration::from_millis(150));
+/// ```
+pub struct Rumble;
+
+impl Rumble {
+    /// Rumbles at full intensity until stopped.
+    pub fn start(device: Device) {
+        Self::play(
+            device,
+            &RumblePattern::new()
+                .hold(1.0, Duration::from_secs(1))
+                .looping(true),
+        );
+    }
+
+    /// Stops the motor, letting it spin down.
+    pub fn stop(device: Device) {
+        with_channel(device, |channel| channel.pattern = None);
+        set_motor(device, false, false);
+    }
+
+    /// Stops the motor immediately. Wii Remotes have no brake, so this is the same as `stop` for them.
+    pub fn hard_stop(device: Device) {
+        with_channel(device, |channel| channel.pattern = None);
+        set_motor(device, false, true);
+    }
+
+    /// Stops every motor.
+    pub fn stop_all() {
+        for device in devices() {
+            Self::stop(device);
+        }
+    }
+
+    /// Rumbles at full intensity for `duration`.
+    pub fn pulse(device: Device, duration: Duration) {
+        Self::play(device, &RumblePattern::new().hold(1.0, duration));
+    }
+
+    /// Plays `pattern`, replacing the current one.
+    pub fn play(device: Device, pattern: &RumblePattern) {
+        let started = System::get_time();
+        with_channel(device, |channel| {
+            channel.pattern = Some(pattern.clone());
+            channel.started = started;
+            channel.accumulator = 0.0;
+        });
+        Self::update_device(device, started);
+    }
+
+    /// Returns true while a pattern is playing.
+    pub fn is_active(device: Device) -> bool {
+        with_channel(device, |channel| channel.pattern.is_some())
+    }
+
+    pub(crate) fn update() {
+        let now = System::get_time();
+        for device in devices() {
+            Self::update_device(device, now);
+        }
+    }
+
+    fn update_device(device: Device, now: u64) {
+        let connected = Connections::is_connected(device);
+        // The motor is switched once the channel is no longer borrowed.
+        let motor = with_channel(device, |channel| {
+            let pattern = channel.pattern.as_ref()?;
+            if !connected {
+                channel.pattern = None;
+                channel.motor = false;
+                return None;
+            }
+
+            let elapsed = System::ticks_to_duration(now - channel.started);
+            match pattern.intensity_at(elapsed) {
+                Some(intensity) => {
+                    channel.accumulator += intensity.max(0.0).min(1.0);
+                    let on = channel.accumulator >= 0.5;
+                    if on {
+                        channel.accumulator -= 1.0;
+                    }
+                    Some(on)
+                }
+                None => {
+                    channel.pattern = None;
+                    Some(false)
+                }
+            }
+        });
+        if let Some(on) = motor {
+            set_motor(device, on, false);
+        }
+    }
+}
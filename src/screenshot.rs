@@ -0,0 +1,263 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+/// Image format used by `Display::screenshot`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ImageFormat {
+    /// Uncompressed 24-bit Windows bitmap.
+    Bmp,
+    /// 24-bit PNG using uncompressed deflate blocks.
+    Png,
+}
+
+impl ImageFormat {
+    /// Encodes `rgb`, tightly packed 8-bit RGB pixels in rows from top to bottom.
+    pub fn encode(self, width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+        match self {
+            ImageFormat::Bmp => encode_bmp(width, height, rgb),
+            ImageFormat::Png => encode_png(width, height, rgb),
+        }
+    }
+}
+
+/// Converts a YUYV (4:2:2) external framebuffer into 8-bit RGB pixels.
+///
+/// Every word of `xfb` holds two horizontally adjacent pixels as `Y0 U Y1 V`.
+pub fn yuyv_to_rgb(xfb: &[u32]) -> Vec<u8> {
+    let mut rgb = Vec::with_capacity(xfb.len() * 6);
+
+    for word in xfb {
+        let [y0, u, y1, v] = word.to_be_bytes();
+        rgb.extend_from_slice(&ycbcr_to_rgb(y0, u, v));
+        rgb.extend_from_slice(&ycbcr_to_rgb(y1, u, v));
+    }
+
+    rgb
+}
+
+/// Converts a single BT.601 studio swing pixel to RGB.
+pub fn ycbcr_to_rgb(y: u8, cb: u8, cr: u8) -> [u8; 3] {
+    // Coefficients scaled by 2^16, with 0.5 added for rounding.
+    let y = (y as i32 - 16) * 76284 + (1 << 15);
+    let cb = cb as i32 - 128;
+    let cr = cr as i32 - 128;
+
+    let r = (y + 104595 * cr) >> 16;
+    let g = (y - 25625 * cb - 53281 * cr) >> 16;
+    let b = (y + 132252 * cb) >> 16;
+
+    [clamp(r), clamp(g), clamp(b)]
+}
+
+fn clamp(value: i32) -> u8 {
+    value.clamp(0, 255) as u8
+}
+
+/// Encodes RGB pixels as a 24-bit bottom-up BMP.
+pub fn encode_bmp(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+    assert_eq!(rgb.len(), (width * height * 3) as usize);
+
+    let stride = (width * 3 + 3) & !3;
+    let image_size = stride * height;
+    let offset = 14 + 40;
+
+    let mut bmp = Vec::with_capacity((offset + image_size) as usize);
+
+    // File header
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&(offset + image_size).to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&offset.to_le_bytes());
+
+    // Info header
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&(width as i32).to_le_bytes());
+    bmp.extend_from_slice(&(height as i32).to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&image_size.to_le_bytes());
+    bmp.extend_from_slice(&2835i32.to_le_bytes());
+    bmp.extend_from_slice(&2835i32.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+
+    // Pixels, stored bottom-up as BGR with rows padded to 4 bytes
+    let padding = (stride - width * 3) as usize;
+    for row in rgb.chunks_exact((width * 3) as usize).rev() {
+        for pixel in row.chunks_exact(3) {
+            bmp.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+        }
+        bmp.extend(core::iter::repeat_n(0, padding));
+    }
+
+    bmp
+}
+
+/// Encodes RGB pixels as a 24-bit PNG without compression.
+pub fn encode_png(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+    assert_eq!(rgb.len(), (width * height * 3) as usize);
+
+    let mut png = Vec::new();
+    png.extend_from_slice(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, truecolor, deflate, adaptive filtering, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    // Every scanline starts with filter type 0 (none)
+    let mut scanlines = Vec::with_capacity(rgb.len() + height as usize);
+    for row in rgb.chunks_exact((width * 3) as usize) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream made of stored (uncompressed) deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+
+    let blocks = data.len().div_ceil(MAX_BLOCK);
+    let mut zlib = Vec::with_capacity(data.len() + blocks.max(1) * 5 + 6);
+    zlib.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        zlib.push(last as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(chunk);
+    }
+
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows of a 2×2 image, top to bottom.
+    const RGB: [u8; 12] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+
+    fn be32(bytes: &[u8]) -> u32 {
+        u32::from_be_bytes(bytes.try_into().unwrap())
+    }
+
+    fn le32(bytes: &[u8]) -> u32 {
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    #[test]
+    fn ycbcr_known_values() {
+        assert_eq!(ycbcr_to_rgb(16, 128, 128), [0, 0, 0]);
+        assert_eq!(ycbcr_to_rgb(235, 128, 128), [255, 255, 255]);
+        // BT.601 red is (81.5, 90.3, 240), which lands a step short of 255 once quantized.
+        assert_eq!(ycbcr_to_rgb(81, 90, 240), [254, 0, 0]);
+    }
+
+    #[test]
+    fn yuyv_words_hold_two_pixels() {
+        let xfb = [u32::from_be_bytes([16, 128, 235, 128])];
+        assert_eq!(yuyv_to_rgb(&xfb), [0, 0, 0, 255, 255, 255]);
+    }
+
+    #[test]
+    fn bmp_header() {
+        let bmp = encode_bmp(2, 2, &RGB);
+        assert_eq!(&bmp[0..2], b"BM");
+        assert_eq!(le32(&bmp[2..6]), 70);
+        assert_eq!(le32(&bmp[10..14]), 54);
+        assert_eq!(le32(&bmp[14..18]), 40);
+        assert_eq!(le32(&bmp[18..22]), 2);
+        assert_eq!(le32(&bmp[22..26]), 2);
+        assert_eq!(&bmp[26..30], &[1, 0, 24, 0]);
+        assert_eq!(le32(&bmp[34..38]), 16);
+        assert_eq!(bmp.len(), 70);
+    }
+
+    #[test]
+    fn bmp_rows_are_padded_bottom_up_bgr() {
+        let bmp = encode_bmp(2, 2, &RGB);
+        assert_eq!(
+            &bmp[54..],
+            &[9, 8, 7, 12, 11, 10, 0, 0, 3, 2, 1, 6, 5, 4, 0, 0]
+        );
+    }
+
+    #[test]
+    fn png_chunks() {
+        let png = encode_png(2, 2, &RGB);
+        assert_eq!(
+            &png[0..8],
+            &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]
+        );
+
+        assert_eq!(be32(&png[8..12]), 13);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(be32(&png[16..20]), 2);
+        assert_eq!(be32(&png[20..24]), 2);
+        assert_eq!(&png[24..29], &[8, 2, 0, 0, 0]);
+        assert_eq!(be32(&png[29..33]), 0xFDD4_9A73);
+
+        // Two scanlines of filter type 0 in a single stored block.
+        assert_eq!(be32(&png[33..37]), 25);
+        assert_eq!(&png[37..41], b"IDAT");
+        assert_eq!(&png[41..48], &[0x78, 0x01, 1, 14, 0, 0xF1, 0xFF]);
+        assert_eq!(&png[48..62], &[0, 1, 2, 3, 4, 5, 6, 0, 7, 8, 9, 10, 11, 12]);
+        assert_eq!(be32(&png[62..66]), 0x018F_004F);
+        assert_eq!(be32(&png[66..70]), 0x9E35_8949);
+
+        assert_eq!(
+            &png[70..],
+            &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+        );
+    }
+}
@@ -0,0 +1,315 @@
+extern crate alloc;
+
+use crate::{
+    audio::{Audio, PlayOptions, Sound, SoundHandle},
+    global::Global,
+};
+use alloc::vec::Vec;
+use embedded_graphics::prelude::Point;
+use ogc::{OgcError, Result};
+
+/// How the volume of an `Emitter` falls off between the minimum and maximum distance.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Rolloff {
+    /// Full volume at any distance.
+    None,
+    /// Falls off evenly, reaching silence at the maximum distance.
+    Linear,
+    /// Halves every time the distance doubles, like sound in open air.
+    Inverse,
+    /// `(distance / min_distance)^-factor`, steeper than `Inverse` for factors above 1.
+    Exponential(f32),
+}
+
+/// Distances an `Emitter` is heard at, in display pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Attenuation {
+    min_distance: f32,
+    max_distance: f32,
+    rolloff: Rolloff,
+}
+
+impl Attenuation {
+    /// Full volume up to 32 pixels away, falling off linearly until 480 pixels.
+    pub fn new() -> Self {
+        Self {
+            min_distance: 32.0,
+            max_distance: 480.0,
+            rolloff: Rolloff::Linear,
+        }
+    }
+
+    /// Distance up to which the sound plays at full volume.
+    pub fn min_distance(self, min_distance: f32) -> Self {
+        Self {
+            min_distance,
+            ..self
+        }
+    }
+
+    /// Distance from which the sound is silent, or no longer falls off for
+    /// `Rolloff::Inverse` and `Rolloff::Exponential`.
+    pub fn max_distance(self, max_distance: f32) -> Self {
+        Self {
+            max_distance,
+            ..self
+        }
+    }
+
+    pub fn rolloff(self, rolloff: Rolloff) -> Self {
+        Self { rolloff, ..self }
+    }
+
+    /// Volume factor at `distance`, in `0.0..=1.0`.
+    pub fn gain(&self, distance: f32) -> f32 {
+        let min = self.min_distance.max(1.0);
+        let max = self.max_distance.max(min);
+        let distance = distance.max(min).min(max);
+
+        let gain = match self.rolloff {
+            Rolloff::None => 1.0,
+            Rolloff::Linear if max > min => 1.0 - (distance - min) / (max - min),
+            Rolloff::Linear => 1.0,
+            Rolloff::Inverse => min / distance,
+            Rolloff::Exponential(factor) => libm::powf(distance / min, -factor),
+        };
+        gain.max(0.0).min(1.0)
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct EmitterSlot {
+    handle: SoundHandle,
+    position: Point,
+    attenuation: Attenuation,
+    generation: u32,
+}
+
+static EMITTERS: Global<Vec<EmitterSlot>> = Global::new(Vec::new());
+static GENERATION: Global<u32> = Global::new(0);
+static LISTENER: Global<Point> = Global::new(Point::zero());
+/// Horizontal distance at which a sound plays from one speaker only.
+static PAN_WIDTH: Global<f32> = Global::new(320.0);
+static MAX_EMITTERS: Global<usize> = Global::new(8);
+
+/// Attenuation and balance of a sound at `position`, heard from the listener.
+fn spatialize(position: Point, attenuation: &Attenuation) -> (f32, f32) {
+    let offset = position - LISTENER.get();
+    let (x, y) = (offset.x as f32, offset.y as f32);
+
+    let gain = attenuation.gain(libm::sqrtf(x * x + y * y));
+    let pan = (x / PAN_WIDTH.get().max(1.0)).max(-1.0).min(1.0);
+    (gain, pan)
+}
+
+/// Attenuation and balance of the sound of `slot`, to apply once `EMITTERS` is no
+/// longer borrowed, so `Audio` is never called from within the borrow.
+fn spatial(slot: &EmitterSlot) -> (SoundHandle, f32, f32) {
+    let (gain, pan) = spatialize(slot.position, &slot.attenuation);
+    (slot.handle, gain, pan)
+}
+
+fn apply((handle, gain, pan): (SoundHandle, f32, f32)) {
+    handle.set_spatial(gain, pan);
+}
+
+/// Follows the listener with every emitter.
+fn apply_all() {
+    let spatial: Vec<_> = EMITTERS.with(|emitters| emitters.iter().map(spatial).collect());
+    spatial.into_iter().for_each(apply);
+}
+
+/// Drops the emitters whose sound has ended.
+fn release_finished() {
+    let handles: Vec<_> =
+        EMITTERS.with(|emitters| emitters.iter().map(|slot| slot.handle).collect());
+    let finished: Vec<_> = handles
+        .into_iter()
+        .filter(|handle| !handle.is_playing())
+        .collect();
+    if !finished.is_empty() {
+        EMITTERS.with_mut(|emitters| emitters.retain(|slot| !finished.contains(&slot.handle)));
+    }
+}
+
+/// Where positioned sounds are heard from, usually the camera or the player.
+///
+/// `Emitter`s pan with their horizontal offset from the listener and grow quieter with
+/// their distance from it, recomputed every frame.
+pub struct Listener;
+
+impl Listener {
+    pub fn position() -> Point {
+        LISTENER.get()
+    }
+
+    pub fn set_position(position: Point) {
+        LISTENER.set(position);
+        apply_all();
+    }
+
+    /// Horizontal distance at which a sound plays from one speaker only, in pixels.
+    /// Defaults to half the display width.
+    pub fn set_pan_width(width: f32) {
+        PAN_WIDTH.set(width);
+    }
+
+    /// Most emitters playing at the same time, 8 by default. The rest of the voices
+    /// stay free for sounds played with `Audio`.
+    pub fn set_max_emitters(count: usize) {
+        MAX_EMITTERS.set(count);
+    }
+
+    /// Number of emitters playing.
+    pub fn emitters() -> usize {
+        EMITTERS.with(|emitters| emitters.len())
+    }
+
+    /// Releases finished emitters and follows moving ones.
+    pub(crate) fn update() {
+        release_finished();
+        apply_all();
+    }
+}
+/// A sound playing at a position in the world. Does nothing once the sound has ended.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Game {
+///     player: Point,
+///     torch: Point,
+///     crackle: Sound,
+///     emitter: Option<Emitter>,
+/// }
+///
+/// impl State for Game {
+///     fn update(&mut self) {
+///         Listener::set_position(self.player);
+///         if self.emitter.is_none() {
+///             let options = PlayOptions::new().looping(true);
+///             let attenuation = Attenuation::new().rolloff(Rolloff::Inverse);
+///             self.emitter = Emitter::play_with(&self.crackle, self.torch, attenuation, options).ok();
+///         }
+///     }
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Emitter {
+    generation: u32,
+}
+
+impl Emitter {
+    /// Plays `sound` once at `position`, with the default `Attenuation`.
+    pub fn play(sound: &Sound, position: Point) -> Result<Self> {
+        Self::play_with(sound, position, Attenuation::new(), PlayOptions::new())
+    }
+
+    /// Plays `sound` at `position`. The pan of `options` is replaced by the one of the
+    /// position. Once the maximum number of emitters play, the quietest one is stopped
+    /// if it is quieter than `sound` would be, otherwise this fails.
+    pub fn play_with(
+        sound: &Sound,
+        position: Point,
+        attenuation: Attenuation,
+        options: PlayOptions,
+    ) -> Result<Self> {
+        let (gain, pan) = spatialize(position, &attenuation);
+        release_finished();
+
+        let evicted = EMITTERS.with_mut(|emitters| {
+            if emitters.len() < MAX_EMITTERS.get() {
+                return Ok(None);
+            }
+            let quietest = emitters
+                .iter()
+                .enumerate()
+                .map(|(i, slot)| (i, spatialize(slot.position, &slot.attenuation).0))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match quietest {
+                Some((i, quietest)) if quietest < gain => Ok(Some(emitters.swap_remove(i).handle)),
+                _ => Err(OgcError::Audio(
+                    "Emitter::play() found no emitter quieter than the sound".into(),
+                )),
+            }
+        })?;
+        if let Some(handle) = evicted {
+            handle.stop();
+        }
+
+        let handle = Audio::play_spatial(sound, options.pan(pan), gain)?;
+        let generation = GENERATION.get().wrapping_add(1);
+        GENERATION.set(generation);
+        EMITTERS.with_mut(|emitters| {
+            emitters.push(EmitterSlot {
+                handle,
+                position,
+                attenuation,
+                generation,
+            })
+        });
+        Ok(Self { generation })
+    }
+
+    /// Calls `f` with the slot of the emitter, unless it was released.
+    fn with_slot<R>(&self, f: impl FnOnce(&mut EmitterSlot) -> R) -> Option<R> {
+        EMITTERS.with_mut(|emitters| {
+            emitters
+                .iter_mut()
+                .find(|slot| slot.generation == self.generation)
+                .map(f)
+        })
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.handle().map_or(false, |handle| handle.is_playing())
+    }
+
+    /// The sound, for instance to change its volume or pitch.
+    pub fn handle(&self) -> Option<SoundHandle> {
+        self.with_slot(|slot| slot.handle)
+    }
+
+    pub fn position(&self) -> Option<Point> {
+        self.with_slot(|slot| slot.position)
+    }
+
+    /// Moves the sound, taking effect immediately.
+    pub fn set_position(&self, position: Point) {
+        if let Some(changed) = self.with_slot(|slot| {
+            slot.position = position;
+            spatial(slot)
+        }) {
+            apply(changed);
+        }
+    }
+
+    pub fn set_attenuation(&self, attenuation: Attenuation) {
+        if let Some(changed) = self.with_slot(|slot| {
+            slot.attenuation = attenuation;
+            spatial(slot)
+        }) {
+            apply(changed);
+        }
+    }
+
+    /// Stops the sound immediately.
+    pub fn stop(&self) {
+        let handle = EMITTERS.with_mut(|emitters| {
+            emitters
+                .iter()
+                .position(|slot| slot.generation == self.generation)
+                .map(|i| emitters.swap_remove(i).handle)
+        });
+        if let Some(handle) = handle {
+            handle.stop();
+        }
+    }
+}
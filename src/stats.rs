@@ -0,0 +1,196 @@
+extern crate alloc;
+
+use crate::{display::Display, frame::InputFrame, global::Global};
+use alloc::format;
+use core::time::Duration;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::Rgb888,
+    prelude::{Point, RgbColor, Size},
+    primitives::Rectangle,
+    text::Text,
+    Drawable,
+};
+use ogc::prelude::*;
+
+/// Buttons that have to be held on the first controller to toggle the overlay.
+/// The overlay toggles when the last of them is pressed.
+pub const OVERLAY_COMBO: Buttons =
+    Buttons::from_bits_truncate(Buttons::L.bits() | Buttons::R.bits() | Buttons::Z.bits());
+
+/// Measurements taken during a single frame.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FrameStats {
+    /// Time spent in `State::update`.
+    pub update: Duration,
+    /// Time spent in `State::draw`, submitting commands to the graphics processor.
+    pub draw: Duration,
+    /// Time spent waiting for the graphics processor to finish drawing.
+    pub gpu: Duration,
+    /// Time between the start of this frame and the start of the previous one.
+    pub frame: Duration,
+    /// Primitives started with `Gx::begin`.
+    pub primitives: u32,
+    /// Vertices submitted with `Gx::begin`.
+    pub vertices: u32,
+    /// Free bytes left in the MEM1 arena.
+    pub mem1_free: u32,
+    /// Free bytes left in the MEM2 arena.
+    pub mem2_free: u32,
+    /// DSP usage, in percent `(0..=100)`.
+    pub dsp_usage: u32,
+}
+
+impl FrameStats {
+    /// Frames per second, derived from the duration of this frame.
+    pub fn fps(&self) -> f32 {
+        let micros = self.frame.as_micros();
+        if micros == 0 {
+            0.0
+        } else {
+            1_000_000.0 / micros as f32
+        }
+    }
+}
+
+static LAST: Global<FrameStats> = Global::new(FrameStats {
+    update: Duration::from_secs(0),
+    draw: Duration::from_secs(0),
+    gpu: Duration::from_secs(0),
+    frame: Duration::from_secs(0),
+    primitives: 0,
+    vertices: 0,
+    mem1_free: 0,
+    mem2_free: 0,
+    dsp_usage: 0,
+});
+
+static OVERLAY_VISIBLE: Global<bool> = Global::new(false);
+
+/// Access to the statistics collected by `Engine::run`.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Game;
+///
+/// impl State for Game {
+///     fn update(&mut self) {
+///         if Stats::last().fps() < 55.0 {
+///             Stats::set_overlay_visible(true);
+///         }
+///     }
+/// }
+/// ```
+pub struct Stats;
+
+impl Stats {
+    /// Statistics of the previous frame.
+    pub fn last() -> FrameStats {
+        LAST.get()
+    }
+
+    /// Returns true if the debug overlay is drawn.
+    pub fn is_overlay_visible() -> bool {
+        OVERLAY_VISIBLE.get()
+    }
+
+    /// Shows or hides the debug overlay.
+    pub fn set_overlay_visible(visible: bool) {
+        OVERLAY_VISIBLE.set(visible)
+    }
+
+    pub(crate) fn record(stats: FrameStats) {
+        LAST.set(stats)
+    }
+
+    pub(crate) fn handle_combo() {
+        let held = InputFrame::pad_held(Controller::One);
+        let down = InputFrame::pad_down(Controller::One);
+        if held.contains(OVERLAY_COMBO) && down.any(OVERLAY_COMBO) {
+            Self::set_overlay_visible(!Self::is_overlay_visible());
+        }
+    }
+}
+
+/// Timestamps taken by `Engine::run` during a frame.
+pub(crate) struct FrameTimer {
+    previous: u64,
+    start: u64,
+    update: u64,
+    draw: u64,
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        let now = System::get_time();
+        Self {
+            previous: now,
+            start: now,
+            update: now,
+            draw: now,
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.previous = self.start;
+        self.start = System::get_time();
+    }
+
+    pub fn update_done(&mut self) {
+        self.update = System::get_time();
+    }
+
+    pub fn draw_done(&mut self) {
+        self.draw = System::get_time();
+    }
+
+    pub fn gpu_done(&mut self) -> FrameStats {
+        let gpu = System::get_time();
+        let (primitives, vertices) = Gx::take_draw_counts();
+
+        FrameStats {
+            update: System::ticks_to_duration(self.update - self.start),
+            draw: System::ticks_to_duration(self.draw - self.update),
+            gpu: System::ticks_to_duration(gpu - self.draw),
+            frame: System::ticks_to_duration(self.start - self.previous),
+            primitives,
+            vertices,
+            mem1_free: System::get_arena_1_size(),
+            mem2_free: System::get_arena_2_size(),
+            dsp_usage: Asnd::get_dsp_percent_use(),
+        }
+    }
+}
+
+/// Draws `stats` in the top left corner. Must be called after the frame was drawn.
+pub(crate) fn draw_overlay(display: &mut Display, stats: &FrameStats) {
+    let background = Rectangle::new(Point::new(16, 16), Size::new(168, 92));
+    display
+        .fill_solid(&background, Rgb888::BLACK)
+        .expect("Error occured while drawing");
+    Gx::draw_done();
+
+    let lines = [
+        format!("FPS     {:>6.1}", stats.fps()),
+        format!("update  {:>6} us", stats.update.as_micros()),
+        format!("draw    {:>6} us", stats.draw.as_micros()),
+        format!("gpu     {:>6} us", stats.gpu.as_micros()),
+        format!("prims   {:>6}", stats.primitives),
+        format!("verts   {:>6}", stats.vertices),
+        format!("mem1    {:>6} KiB", stats.mem1_free / 1024),
+        format!("mem2    {:>6} KiB", stats.mem2_free / 1024),
+        format!("dsp     {:>6} %", stats.dsp_usage),
+    ];
+
+    let style = MonoTextStyle::new(&FONT_6X10, Rgb888::WHITE);
+    for (i, line) in lines.iter().enumerate() {
+        let position = Point::new(20, 26 + i as i32 * 10);
+        Text::new(line, position, style)
+            .draw(display)
+            .expect("Error occured while drawing");
+    }
+}
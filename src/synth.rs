@@ -0,0 +1,485 @@
+extern crate alloc;
+
+use crate::random::Rng;
+#[cfg(target_os = "rvl-ios")]
+use crate::{audio::Sound, wav::DSP_RATE};
+use alloc::vec::Vec;
+use core::f32::consts::TAU;
+#[cfg(target_os = "rvl-ios")]
+use ogc::prelude::*;
+
+/// Shape of the wave an `Oscillator` produces.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Waveform {
+    /// High for `duty` of the period, low for the rest. Hollow at a duty of 0.5,
+    /// thinner towards the extremes.
+    Square,
+    Sawtooth,
+    Triangle,
+    Sine,
+    /// Random values held for a sixteenth of the period, so higher frequencies hiss
+    /// and lower ones rumble.
+    Noise,
+}
+
+/// A periodic wave at a frequency that can change every sample.
+#[derive(Copy, Clone, Debug)]
+pub struct Oscillator {
+    waveform: Waveform,
+    duty: f32,
+    /// Position in the period, in `0.0..1.0`.
+    phase: f32,
+    noise: Rng,
+    noise_value: f32,
+}
+
+impl Oscillator {
+    /// `seed` only matters for `Waveform::Noise`.
+    pub fn new(waveform: Waveform, seed: u64) -> Self {
+        let mut noise = Rng::new(seed);
+        let noise_value = noise.range_f32(-1.0, 1.0);
+        Self {
+            waveform,
+            duty: 0.5,
+            phase: 0.0,
+            noise,
+            noise_value,
+        }
+    }
+
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    /// Fraction of the period a square wave is high, in `0.0..1.0`.
+    pub fn set_duty(&mut self, duty: f32) {
+        self.duty = duty.clamp(0.01, 0.99);
+    }
+
+    /// Restarts the period.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Next sample, in `-1.0..=1.0`, of a wave at `frequency` Hz sampled at `rate` Hz.
+    pub fn next(&mut self, frequency: f32, rate: u32) -> f32 {
+        let phase = self.phase;
+        let value = match self.waveform {
+            Waveform::Square => {
+                if phase < self.duty {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+            Waveform::Triangle => 1.0 - 4.0 * libm::fabsf(phase - 0.5),
+            Waveform::Sine => libm::sinf(phase * TAU),
+            Waveform::Noise => self.noise_value,
+        };
+
+        let next = phase + frequency.max(0.0) / rate.max(1) as f32;
+        if self.waveform == Waveform::Noise && (next * 16.0) as u32 != (phase * 16.0) as u32 {
+            self.noise_value = self.noise.range_f32(-1.0, 1.0);
+        }
+        self.phase = next - libm::floorf(next);
+        value
+    }
+}
+
+/// Volume over the life of a note: rising for `attack`, falling to `sustain` for
+/// `decay`, holding `sustain` until the note is released, then fading out for `release`.
+/// Times are in seconds, `sustain` in `0.0..=1.0`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    pub fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Self {
+            attack,
+            decay,
+            sustain,
+            release,
+        }
+    }
+
+    /// Level at `time` seconds into a note released after `held` seconds.
+    pub fn level(&self, time: f32, held: f32) -> f32 {
+        if time < held {
+            self.held_level(time)
+        } else if self.release > 0.0 {
+            self.held_level(held) * (1.0 - (time - held) / self.release).max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn held_level(&self, time: f32) -> f32 {
+        if time < self.attack {
+            time / self.attack
+        } else if time < self.attack + self.decay {
+            1.0 - (1.0 - self.sustain) * (time - self.attack) / self.decay
+        } else {
+            self.sustain
+        }
+    }
+}
+
+/// A chip-style sound effect, rendered to PCM at runtime instead of shipped as a sample.
+///
+/// The parameters follow sfxr: a waveform whose pitch slides, wobbles and jumps over
+/// an envelope. Presets such as `Sfx::pickup` randomize them from a seed, and the same
+/// parameters always render the same samples.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Game {
+///     coin: Sound,
+///     laser: Sound,
+/// }
+///
+/// let game = Game {
+///     coin: Sfx::pickup(7).to_sound(),
+///     laser: Sfx::new(Waveform::Sawtooth, 1200.0)
+///         .slide(-4.0)
+///         .length(0.1)
+///         .to_sound(),
+/// };
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sfx {
+    waveform: Waveform,
+    frequency: f32,
+    min_frequency: f32,
+    slide: f32,
+    delta_slide: f32,
+    vibrato_depth: f32,
+    vibrato_speed: f32,
+    arpeggio: f32,
+    arpeggio_time: f32,
+    duty: f32,
+    duty_sweep: f32,
+    envelope: Envelope,
+    length: f32,
+    volume: f32,
+    seed: u64,
+}
+
+impl Sfx {
+    /// A tenth of a second of `waveform` at `frequency` Hz, without any effect.
+    pub fn new(waveform: Waveform, frequency: f32) -> Self {
+        Self {
+            waveform,
+            frequency,
+            min_frequency: 20.0,
+            slide: 0.0,
+            delta_slide: 0.0,
+            vibrato_depth: 0.0,
+            vibrato_speed: 0.0,
+            arpeggio: 1.0,
+            arpeggio_time: 0.0,
+            duty: 0.5,
+            duty_sweep: 0.0,
+            envelope: Envelope::new(0.0, 0.0, 1.0, 0.05),
+            length: 0.1,
+            volume: 0.5,
+            seed: 1,
+        }
+    }
+
+    /// Pitch change, in octaves per second. Negative slides down.
+    pub fn slide(self, slide: f32) -> Self {
+        Self { slide, ..self }
+    }
+
+    /// Change of the slide, in octaves per second squared.
+    pub fn delta_slide(self, delta_slide: f32) -> Self {
+        Self {
+            delta_slide,
+            ..self
+        }
+    }
+
+    /// The sound ends once a slide takes it below `min_frequency` Hz.
+    pub fn min_frequency(self, min_frequency: f32) -> Self {
+        Self {
+            min_frequency,
+            ..self
+        }
+    }
+
+    /// Pitch wobble of `depth`, a fraction of the frequency, at `speed` Hz.
+    pub fn vibrato(self, depth: f32, speed: f32) -> Self {
+        Self {
+            vibrato_depth: depth,
+            vibrato_speed: speed,
+            ..self
+        }
+    }
+
+    /// Multiplies the frequency by `multiplier` after `time` seconds, the jump of a
+    /// coin pickup.
+    pub fn arpeggio(self, multiplier: f32, time: f32) -> Self {
+        Self {
+            arpeggio: multiplier,
+            arpeggio_time: time,
+            ..self
+        }
+    }
+
+    /// Duty cycle of `Waveform::Square`, changing by `sweep` per second.
+    pub fn duty(self, duty: f32, sweep: f32) -> Self {
+        Self {
+            duty,
+            duty_sweep: sweep,
+            ..self
+        }
+    }
+
+    pub fn envelope(self, envelope: Envelope) -> Self {
+        Self { envelope, ..self }
+    }
+
+    /// Seconds until the envelope releases.
+    pub fn length(self, length: f32) -> Self {
+        Self { length, ..self }
+    }
+
+    /// Volume, in `0.0..=1.0`.
+    pub fn volume(self, volume: f32) -> Self {
+        Self { volume, ..self }
+    }
+
+    /// Seed of `Waveform::Noise`.
+    pub fn seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Playing time, unless a slide ends it early.
+    pub fn duration(&self) -> f32 {
+        self.length.max(0.0) + self.envelope.release.max(0.0)
+    }
+
+    /// Renders 16-bit mono samples at `rate` Hz.
+    pub fn render(&self, rate: u32) -> Vec<i16> {
+        let frames = libm::ceilf(self.duration() * rate as f32) as usize;
+        let mut samples = Vec::with_capacity(frames);
+        let mut oscillator = Oscillator::new(self.waveform, self.seed);
+
+        for i in 0..frames {
+            let time = i as f32 / rate as f32;
+
+            let octaves = self.slide * time + 0.5 * self.delta_slide * time * time;
+            let mut frequency = self.frequency * libm::exp2f(octaves);
+            if self.arpeggio_time > 0.0 && time >= self.arpeggio_time {
+                frequency *= self.arpeggio;
+            }
+            if frequency < self.min_frequency {
+                break;
+            }
+            if self.vibrato_depth != 0.0 {
+                frequency *= 1.0 + self.vibrato_depth * libm::sinf(TAU * self.vibrato_speed * time);
+            }
+
+            oscillator.set_duty(self.duty + self.duty_sweep * time);
+            let value = oscillator.next(frequency, rate)
+                * self.envelope.level(time, self.length)
+                * self.volume.clamp(0.0, 1.0);
+            samples.push((value * i16::MAX as f32) as i16);
+        }
+
+        samples
+    }
+
+    /// Renders signed big-endian 16-bit samples at `rate` Hz, the layout `Asnd` reads
+    /// as `VoiceFormat::Mono16Bit`.
+    pub fn to_pcm(&self, rate: u32) -> Vec<u8> {
+        self.render(rate)
+            .iter()
+            .flat_map(|sample| sample.to_be_bytes())
+            .collect()
+    }
+
+    /// A `Sound` rendered at the rate of the DSP.
+    #[cfg(target_os = "rvl-ios")]
+    pub fn to_sound(&self) -> Sound {
+        Sound::new(&self.to_pcm(DSP_RATE), VoiceFormat::Mono16Bit, DSP_RATE)
+    }
+
+    /// A coin or item pickup: a short blip jumping up in pitch.
+    pub fn pickup(seed: u64) -> Self {
+        let mut random = Rng::new(seed);
+        let waveform = if random.chance(0.5) {
+            Waveform::Square
+        } else {
+            Waveform::Sawtooth
+        };
+        let mut sfx = Self::new(waveform, random.range_f32(800.0, 1600.0))
+            .envelope(Envelope::new(0.0, 0.0, 1.0, random.range_f32(0.1, 0.3)))
+            .length(random.range_f32(0.04, 0.1))
+            .seed(seed);
+        if random.chance(0.5) {
+            sfx = sfx.arpeggio(random.range_f32(1.3, 1.6), random.range_f32(0.03, 0.08));
+        }
+        sfx
+    }
+
+    /// A laser or shot: a fast slide down.
+    pub fn laser(seed: u64) -> Self {
+        let mut random = Rng::new(seed);
+        let waveform = match random.next_u64() % 3 {
+            0 => Waveform::Square,
+            1 => Waveform::Sawtooth,
+            _ => Waveform::Sine,
+        };
+        Self::new(waveform, random.range_f32(500.0, 2000.0))
+            .slide(-random.range_f32(2.0, 6.0))
+            .min_frequency(random.range_f32(60.0, 200.0))
+            .duty(random.range_f32(0.2, 0.5), random.range_f32(0.0, 2.0))
+            .envelope(Envelope::new(0.0, 0.0, 1.0, random.range_f32(0.05, 0.2)))
+            .length(random.range_f32(0.05, 0.15))
+            .seed(seed)
+    }
+
+    /// An explosion: low noise sliding down and fading out slowly.
+    pub fn explosion(seed: u64) -> Self {
+        let mut random = Rng::new(seed);
+        let mut sfx = Self::new(Waveform::Noise, random.range_f32(80.0, 400.0))
+            .slide(-random.range_f32(0.2, 1.5))
+            .envelope(Envelope::new(0.0, 0.0, 1.0, random.range_f32(0.3, 0.8)))
+            .length(random.range_f32(0.05, 0.2))
+            .seed(seed);
+        if random.chance(0.3) {
+            sfx = sfx.vibrato(random.range_f32(0.1, 0.4), random.range_f32(4.0, 12.0));
+        }
+        sfx
+    }
+
+    /// A power-up: a rising slide with a wobble.
+    pub fn powerup(seed: u64) -> Self {
+        let mut random = Rng::new(seed);
+        let waveform = if random.chance(0.5) {
+            Waveform::Square
+        } else {
+            Waveform::Sawtooth
+        };
+        Self::new(waveform, random.range_f32(300.0, 800.0))
+            .slide(random.range_f32(1.0, 3.0))
+            .vibrato(random.range_f32(0.0, 0.15), random.range_f32(8.0, 20.0))
+            .envelope(Envelope::new(0.0, 0.0, 1.0, random.range_f32(0.2, 0.4)))
+            .length(random.range_f32(0.1, 0.3))
+            .seed(seed)
+    }
+
+    /// A hit or hurt: a short, harsh drop.
+    pub fn hit(seed: u64) -> Self {
+        let mut random = Rng::new(seed);
+        let waveform = match random.next_u64() % 3 {
+            0 => Waveform::Square,
+            1 => Waveform::Sawtooth,
+            _ => Waveform::Noise,
+        };
+        Self::new(waveform, random.range_f32(200.0, 800.0))
+            .slide(-random.range_f32(3.0, 6.0))
+            .envelope(Envelope::new(0.0, 0.0, 1.0, random.range_f32(0.1, 0.2)))
+            .length(random.range_f32(0.02, 0.06))
+            .seed(seed)
+    }
+
+    /// A jump: a square wave sliding up.
+    pub fn jump(seed: u64) -> Self {
+        let mut random = Rng::new(seed);
+        Self::new(Waveform::Square, random.range_f32(250.0, 600.0))
+            .slide(random.range_f32(1.5, 3.0))
+            .duty(random.range_f32(0.3, 0.6), 0.0)
+            .envelope(Envelope::new(0.0, 0.0, 1.0, random.range_f32(0.1, 0.2)))
+            .length(random.range_f32(0.05, 0.15))
+            .seed(seed)
+    }
+
+    /// A menu blip: short and steady.
+    pub fn blip(seed: u64) -> Self {
+        let mut random = Rng::new(seed);
+        let waveform = if random.chance(0.5) {
+            Waveform::Square
+        } else {
+            Waveform::Sine
+        };
+        Self::new(waveform, random.range_f32(400.0, 1200.0))
+            .envelope(Envelope::new(0.0, 0.0, 1.0, random.range_f32(0.02, 0.05)))
+            .length(random.range_f32(0.05, 0.08))
+            .seed(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_render_the_same_for_a_seed() {
+        for seed in [0, 1, 0xDEAD_BEEF] {
+            assert_eq!(
+                Sfx::pickup(seed).render(22050),
+                Sfx::pickup(seed).render(22050)
+            );
+            assert_eq!(
+                Sfx::explosion(seed).render(22050),
+                Sfx::explosion(seed).render(22050)
+            );
+        }
+        assert_ne!(Sfx::pickup(1).render(22050), Sfx::pickup(2).render(22050));
+    }
+
+    #[test]
+    fn render_covers_length_and_release() {
+        let sfx = Sfx::new(Waveform::Square, 440.0)
+            .envelope(Envelope::new(0.0, 0.0, 1.0, 0.125))
+            .length(0.25);
+        assert_eq!(sfx.render(8000).len(), 3000);
+        // 8268.75 frames, rounded up.
+        assert_eq!(sfx.render(22050).len(), 8269);
+    }
+
+    #[test]
+    fn envelope_levels_at_boundaries() {
+        let envelope = Envelope::new(0.25, 0.5, 0.5, 1.0);
+        assert_eq!(envelope.level(0.0, 2.0), 0.0);
+        assert_eq!(envelope.level(0.125, 2.0), 0.5);
+        // Attack ends at full level, decay ends at the sustain level.
+        assert_eq!(envelope.level(0.25, 2.0), 1.0);
+        assert_eq!(envelope.level(0.5, 2.0), 0.75);
+        assert_eq!(envelope.level(0.75, 2.0), 0.5);
+        assert_eq!(envelope.level(1.5, 2.0), 0.5);
+
+        // Release fades out from wherever the note was released.
+        assert_eq!(envelope.level(2.0, 2.0), 0.5);
+        assert_eq!(envelope.level(2.5, 2.0), 0.25);
+        assert_eq!(envelope.level(3.0, 2.0), 0.0);
+        assert_eq!(envelope.level(0.75, 0.5), 0.75 * 0.75);
+
+        // Without attack nor decay, the note starts at the sustain level.
+        let instant = Envelope::new(0.0, 0.0, 0.8, 0.0);
+        assert_eq!(instant.level(0.0, 1.0), 0.8);
+        assert_eq!(instant.level(1.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn min_frequency_ends_the_render() {
+        // Two octaves down after half a second.
+        let sfx = Sfx::new(Waveform::Sine, 1000.0)
+            .slide(-4.0)
+            .min_frequency(250.0)
+            .length(1.0);
+        assert_eq!(sfx.render(8000).len(), 4001);
+        assert_eq!(sfx.min_frequency(0.0).render(8000).len(), 8400);
+    }
+}
@@ -0,0 +1,1072 @@
+extern crate alloc;
+
+use crate::{decoder::Decoder, wav::DSP_RATE};
+#[cfg(target_os = "rvl-ios")]
+use crate::{
+    engine::State,
+    global::Global,
+    music::{Music, Track},
+};
+use alloc::{collections::VecDeque, format, rc::Rc, string::String, vec::Vec};
+use core::cell::RefCell;
+#[cfg(target_os = "rvl-ios")]
+use core::time::Duration;
+use ogc::{OgcError, Result};
+
+/// Clock of the Amiga sound chip on PAL machines. A note of period `p` plays its
+/// sample at `PAULA_CLOCK / p` Hz.
+const PAULA_CLOCK: f64 = 3_546_894.6;
+const ROWS: usize = 64;
+const SAMPLES: usize = 31;
+const MIN_PERIOD: i32 = 113;
+const MAX_PERIOD: i32 = 856;
+/// Amiga channels are hard panned left, right, right, left. Halfway towards the
+/// center is easier on headphones.
+const PANNING: [f32; 4] = [0.25, 0.75, 0.75, 0.25];
+/// Quarter of a sine period, as used by vibrato and tremolo.
+const SINE: [i32; 32] = [
+    0, 24, 49, 74, 97, 120, 141, 161, 180, 197, 212, 224, 235, 244, 250, 253, 255, 253, 250, 244,
+    235, 224, 212, 197, 180, 161, 141, 120, 97, 74, 49, 24,
+];
+
+#[derive(Copy, Clone)]
+struct Note {
+    /// Sample number, 0 for none.
+    sample: u8,
+    /// Period, 0 for none.
+    period: u16,
+    effect: u8,
+    param: u8,
+}
+
+struct Sample {
+    data: Vec<i8>,
+    volume: u8,
+    finetune: i8,
+    /// Start and end of the loop, in bytes.
+    repeat: Option<(usize, usize)>,
+}
+
+struct ModuleData {
+    title: String,
+    channels: usize,
+    samples: Vec<Sample>,
+    orders: Vec<u8>,
+    restart: usize,
+    notes: Vec<Note>,
+}
+
+impl ModuleData {
+    fn note(&self, order: usize, row: usize, channel: usize) -> Note {
+        let pattern = self.orders[order] as usize;
+        self.notes[(pattern * ROWS + row) * self.channels + channel]
+    }
+}
+
+fn u16_be(bytes: &[u8], at: usize) -> u16 {
+    u16::from_be_bytes([bytes[at], bytes[at + 1]])
+}
+
+/// A ProTracker module, the MOD format of the Amiga, with 4 to 32 channels.
+/// Cloning is cheap, the patterns and samples are shared.
+///
+/// Only MODs with 31 samples are read. Older 15-sample MODs and the XM, S3M and IT
+/// formats are not supported.
+#[derive(Clone)]
+pub struct Module {
+    data: Rc<ModuleData>,
+}
+
+impl Module {
+    /// Reads a module with 31 samples, as saved by ProTracker, FastTracker and most
+    /// other trackers.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 1084 {
+            return Err(OgcError::Audio(
+                "Module::parse() found no MOD header".into(),
+            ));
+        }
+
+        let channels = match &bytes[1080..1084] {
+            b"M.K." | b"M!K!" | b"M&K!" | b"FLT4" | b"4CHN" => 4,
+            b"OKTA" | b"CD81" => 8,
+            [d, b'C', b'H', b'N'] if d.is_ascii_digit() => (d - b'0') as usize,
+            [a, b, b'C', b'H'] if a.is_ascii_digit() && b.is_ascii_digit() => {
+                ((a - b'0') * 10 + (b - b'0')) as usize
+            }
+            _ => {
+                return Err(OgcError::Audio(
+                    "Module::parse() only supports MOD files with 31 samples".into(),
+                ))
+            }
+        };
+        if channels == 0 || channels > 32 {
+            return Err(OgcError::Audio(format!(
+                "Module::parse() does not support {} channels",
+                channels
+            )));
+        }
+
+        let title = bytes[..20]
+            .iter()
+            .take_while(|&&byte| byte != 0)
+            .map(|&byte| byte as char)
+            .collect();
+
+        let length = (bytes[950] as usize).clamp(1, 128);
+        let orders = bytes[952..952 + length].to_vec();
+        let restart = match bytes[951] as usize {
+            restart if restart < length => restart,
+            _ => 0,
+        };
+        // Patterns listed past the song length are stored too.
+        let patterns = bytes[952..1080].iter().max().copied().unwrap_or(0) as usize + 1;
+
+        let pattern_size = ROWS * channels * 4;
+        let mut at = 1084;
+        let end = at + patterns * pattern_size;
+        if bytes.len() < end {
+            return Err(OgcError::Audio(
+                "Module::parse() found truncated patterns".into(),
+            ));
+        }
+        let notes = bytes[at..end]
+            .chunks_exact(4)
+            .map(|note| Note {
+                sample: (note[0] & 0xF0) | (note[2] >> 4),
+                period: u16::from_be_bytes([note[0] & 0x0F, note[1]]),
+                effect: note[2] & 0x0F,
+                param: note[3],
+            })
+            .collect();
+        at = end;
+
+        let mut samples = Vec::with_capacity(SAMPLES);
+        for i in 0..SAMPLES {
+            let header = 20 + i * 30;
+            let length = u16_be(bytes, header + 22) as usize * 2;
+            // Samples cut off at the end of the file keep what is there.
+            let data: Vec<i8> = bytes[at.min(bytes.len())..(at + length).min(bytes.len())]
+                .iter()
+                .map(|&byte| byte as i8)
+                .collect();
+            at += length;
+
+            let repeat_start = u16_be(bytes, header + 26) as usize * 2;
+            let repeat_length = u16_be(bytes, header + 28) as usize * 2;
+            let repeat_end = (repeat_start + repeat_length).min(data.len());
+            samples.push(Sample {
+                repeat: if repeat_length > 2 && repeat_start < repeat_end {
+                    Some((repeat_start, repeat_end))
+                } else {
+                    None
+                },
+                data,
+                volume: bytes[header + 25].min(64),
+                // A signed nibble.
+                finetune: ((bytes[header + 24] << 4) as i8) >> 4,
+            });
+        }
+
+        Ok(Self {
+            data: Rc::new(ModuleData {
+                title,
+                channels,
+                samples,
+                orders,
+                restart,
+                notes,
+            }),
+        })
+    }
+
+    pub fn title(&self) -> &str {
+        &self.data.title
+    }
+
+    pub fn channels(&self) -> usize {
+        self.data.channels
+    }
+
+    /// Number of entries in the order list, the patterns in the order they play.
+    pub fn song_length(&self) -> usize {
+        self.data.orders.len()
+    }
+}
+
+/// Where a module is, see `Tracker::position`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TrackerPosition {
+    /// Entry of the order list.
+    pub order: usize,
+    /// Pattern played at that entry.
+    pub pattern: usize,
+    /// Row of the pattern, in `0..64`.
+    pub row: usize,
+}
+
+/// Something that happened in a module, see `State::tracker`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TrackerEvent {
+    /// A row started playing.
+    Row(TrackerPosition),
+    /// An `E8x` effect played, with its `x`. Composers place these in patterns to sync
+    /// gameplay to the music.
+    Sync(u8),
+    /// The song reached its end and started over.
+    Looped,
+}
+
+#[derive(Default)]
+struct Channel {
+    /// Index into the samples.
+    sample: Option<usize>,
+    active: bool,
+    /// Playing position in the sample, in bytes.
+    position: f64,
+    /// Bytes to advance per output frame.
+    step: f64,
+    period: i32,
+    finetune: i8,
+    volume: i32,
+    pan: f32,
+    effect: u8,
+    param: u8,
+    /// Changes of the period and volume by vibrato and tremolo for this tick.
+    period_offset: i32,
+    volume_offset: i32,
+    porta_target: i32,
+    porta_speed: i32,
+    vibrato_speed: u8,
+    vibrato_depth: i32,
+    vibrato_position: u8,
+    tremolo_speed: u8,
+    tremolo_depth: i32,
+    tremolo_position: u8,
+    offset: u8,
+    loop_row: usize,
+    loop_count: u8,
+    /// Note held back by an `EDx` effect.
+    delayed: Option<Note>,
+}
+
+/// Value of the vibrato and tremolo waveform at `position`, in `-255..=255`.
+fn sine(position: u8) -> i32 {
+    let value = SINE[(position & 31) as usize];
+    if position & 32 != 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+/// `period` adjusted by a finetune of eighths of a semitone.
+fn tune(period: u16, finetune: i8) -> i32 {
+    if finetune == 0 {
+        period as i32
+    } else {
+        libm::roundf(period as f32 * libm::powf(2.0, -(finetune as f32) / 96.0)) as i32
+    }
+}
+
+fn volume_slide(channel: &mut Channel, param: u8) {
+    let (up, down) = ((param >> 4) as i32, (param & 0x0F) as i32);
+    channel.volume = if up > 0 {
+        channel.volume + up
+    } else {
+        channel.volume - down
+    }
+    .clamp(0, 64);
+}
+
+fn tone_portamento(channel: &mut Channel) {
+    if channel.porta_target == 0 {
+        return;
+    }
+    channel.period = if channel.period < channel.porta_target {
+        (channel.period + channel.porta_speed).min(channel.porta_target)
+    } else {
+        (channel.period - channel.porta_speed).max(channel.porta_target)
+    };
+}
+
+/// Plays a `Module` as a music `Decoder`, in 48 kHz stereo.
+///
+/// `Tracker` plays modules through one of these, use it directly to play a module
+/// with `Music` like any other track.
+pub struct ModPlayer {
+    module: Rc<ModuleData>,
+    channels: Vec<Channel>,
+    looping: bool,
+    speed: u32,
+    tempo: u32,
+    order: usize,
+    row: usize,
+    /// Tick of the current row.
+    tick: u32,
+    /// Times the current row repeats, set by `EEx`.
+    row_delay: u32,
+    /// Row is being repeated by `EEx`, its notes already played.
+    repeating: bool,
+    jump_order: Option<usize>,
+    break_row: Option<usize>,
+    loop_row: Option<usize>,
+    /// Frames left to render of the current tick.
+    frames_left: usize,
+    /// Remainder of the division giving the frames of a tick.
+    tick_error: u32,
+    rendered: u64,
+    ended: bool,
+    /// Advances without mixing or events, to seek.
+    silent: bool,
+    mix: Vec<i32>,
+    /// Events with the frame they happen at. Only filled while `Tracker` holds a clone.
+    events: Rc<RefCell<VecDeque<(u64, TrackerEvent)>>>,
+}
+
+impl ModPlayer {
+    pub fn new(module: &Module) -> Self {
+        let mut player = Self {
+            module: module.data.clone(),
+            channels: Vec::new(),
+            looping: false,
+            speed: 6,
+            tempo: 125,
+            order: 0,
+            row: 0,
+            tick: 0,
+            row_delay: 0,
+            repeating: false,
+            jump_order: None,
+            break_row: None,
+            loop_row: None,
+            frames_left: 0,
+            tick_error: 0,
+            rendered: 0,
+            ended: false,
+            silent: false,
+            mix: Vec::new(),
+            events: Rc::new(RefCell::new(VecDeque::new())),
+        };
+        player.reset();
+        player
+    }
+
+    /// Starts over at the restart position of the module once it ends.
+    pub fn looping(self, looping: bool) -> Self {
+        Self { looping, ..self }
+    }
+
+    /// Position of the row being rendered, ahead of the one being heard.
+    pub fn position(&self) -> TrackerPosition {
+        TrackerPosition {
+            order: self.order,
+            pattern: self.module.orders[self.order] as usize,
+            row: self.row,
+        }
+    }
+
+    fn reset(&mut self) {
+        let count = self.module.channels;
+        self.channels = (0..count)
+            .map(|i| Channel {
+                pan: PANNING[i % 4],
+                ..Channel::default()
+            })
+            .collect();
+        self.speed = 6;
+        self.tempo = 125;
+        self.order = 0;
+        self.row = 0;
+        self.tick = 0;
+        self.row_delay = 0;
+        self.repeating = false;
+        self.jump_order = None;
+        self.break_row = None;
+        self.loop_row = None;
+        self.frames_left = 0;
+        self.tick_error = 0;
+        self.rendered = 0;
+        self.ended = false;
+    }
+
+    fn push(&self, event: TrackerEvent) {
+        if !self.silent && Rc::strong_count(&self.events) > 1 {
+            self.events.borrow_mut().push_back((self.rendered, event));
+        }
+    }
+
+    /// Plays the notes of the row and the effects of its first tick.
+    fn start_row(&mut self) {
+        self.push(TrackerEvent::Row(self.position()));
+        let module = &self.module;
+        let mut syncs = Vec::new();
+
+        for (i, channel) in self.channels.iter_mut().enumerate() {
+            let note = module.note(self.order, self.row, i);
+            let (x, y) = (note.param >> 4, note.param & 0x0F);
+            channel.effect = note.effect;
+            channel.param = note.param;
+            channel.period_offset = 0;
+            channel.volume_offset = 0;
+
+            if note.effect == 0xE && x == 0xD && y > 0 {
+                channel.delayed = Some(note);
+            } else {
+                trigger(module, channel, note);
+            }
+
+            match note.effect {
+                0x3 if note.param > 0 => channel.porta_speed = note.param as i32,
+                0x4 => {
+                    if x > 0 {
+                        channel.vibrato_speed = x;
+                    }
+                    if y > 0 {
+                        channel.vibrato_depth = y as i32;
+                    }
+                }
+                0x7 => {
+                    if x > 0 {
+                        channel.tremolo_speed = x;
+                    }
+                    if y > 0 {
+                        channel.tremolo_depth = y as i32;
+                    }
+                }
+                0x8 => channel.pan = note.param as f32 / 255.0,
+                0xB => self.jump_order = Some(note.param as usize),
+                0xC => channel.volume = note.param.min(64) as i32,
+                0xD => {
+                    let row = (x * 10 + y) as usize;
+                    self.break_row = Some(if row < ROWS { row } else { 0 });
+                }
+                0xE => match x {
+                    0x1 => channel.period = (channel.period - y as i32).max(MIN_PERIOD),
+                    0x2 => channel.period = (channel.period + y as i32).min(MAX_PERIOD),
+                    0x6 if y == 0 => channel.loop_row = self.row,
+                    0x6 => {
+                        if channel.loop_count == 0 {
+                            channel.loop_count = y;
+                            self.loop_row = Some(channel.loop_row);
+                        } else {
+                            channel.loop_count -= 1;
+                            if channel.loop_count > 0 {
+                                self.loop_row = Some(channel.loop_row);
+                            }
+                        }
+                    }
+                    0x8 => syncs.push(y),
+                    0xA => channel.volume = (channel.volume + y as i32).min(64),
+                    0xB => channel.volume = (channel.volume - y as i32).max(0),
+                    0xC if y == 0 => channel.volume = 0,
+                    0xE if self.row_delay == 0 => self.row_delay = y as u32,
+                    _ => {}
+                },
+                0xF if note.param == 0 => {}
+                0xF if note.param < 32 => self.speed = note.param as u32,
+                0xF => self.tempo = note.param as u32,
+                _ => {}
+            }
+        }
+
+        for sync in syncs {
+            self.push(TrackerEvent::Sync(sync));
+        }
+    }
+
+    /// Advances the effects on the ticks after the first of a row.
+    fn update_effects(&mut self) {
+        let tick = self.tick;
+        let module = &self.module;
+
+        for channel in self.channels.iter_mut() {
+            let (x, y) = (channel.param >> 4, channel.param & 0x0F);
+            match channel.effect {
+                0x1 => channel.period = (channel.period - channel.param as i32).max(MIN_PERIOD),
+                0x2 => channel.period = (channel.period + channel.param as i32).min(MAX_PERIOD),
+                0x3 => tone_portamento(channel),
+                0x4 | 0x6 => {
+                    channel.period_offset =
+                        (sine(channel.vibrato_position) * channel.vibrato_depth) >> 7;
+                    channel.vibrato_position =
+                        channel.vibrato_position.wrapping_add(channel.vibrato_speed) & 63;
+                    if channel.effect == 0x6 {
+                        volume_slide(channel, channel.param);
+                    }
+                }
+                0x5 => {
+                    tone_portamento(channel);
+                    volume_slide(channel, channel.param);
+                }
+                0x7 => {
+                    channel.volume_offset =
+                        (sine(channel.tremolo_position) * channel.tremolo_depth) >> 6;
+                    channel.tremolo_position =
+                        channel.tremolo_position.wrapping_add(channel.tremolo_speed) & 63;
+                }
+                0xA => volume_slide(channel, channel.param),
+                0xE => match x {
+                    0x9 if y > 0 && tick.is_multiple_of(y as u32) => channel.position = 0.0,
+                    0xC if tick == y as u32 => channel.volume = 0,
+                    0xD if tick == y as u32 => {
+                        if let Some(note) = channel.delayed.take() {
+                            trigger(module, channel, note);
+                        }
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves to the next row, following jumps, breaks and loops.
+    fn next_row(&mut self) {
+        let (jump, row_break) = (self.jump_order.take(), self.break_row.take());
+        if let Some(row) = self.loop_row.take() {
+            self.row = row;
+        } else if jump.is_some() || row_break.is_some() {
+            self.order = jump.unwrap_or(self.order + 1);
+            self.row = row_break.unwrap_or(0);
+        } else {
+            self.row += 1;
+            if self.row == ROWS {
+                self.row = 0;
+                self.order += 1;
+            }
+        }
+
+        if self.order >= self.module.orders.len() {
+            if self.looping {
+                self.order = self.module.restart;
+                self.push(TrackerEvent::Looped);
+            } else {
+                self.order = 0;
+                self.ended = true;
+            }
+        }
+    }
+
+    /// Runs one tick and sets how many frames it lasts.
+    fn process_tick(&mut self) {
+        if self.tick == 0 && !self.repeating {
+            self.start_row();
+        } else if self.tick > 0 {
+            self.update_effects();
+        }
+
+        for channel in self.channels.iter_mut() {
+            let mut period = (channel.period + channel.period_offset) as f32;
+            if channel.effect == 0x0 && channel.param != 0 {
+                let semitones = match self.tick % 3 {
+                    0 => 0,
+                    1 => channel.param >> 4,
+                    _ => channel.param & 0x0F,
+                };
+                period /= libm::powf(2.0, semitones as f32 / 12.0);
+            }
+            channel.step = if period > 0.0 {
+                PAULA_CLOCK / period as f64 / DSP_RATE as f64
+            } else {
+                0.0
+            };
+        }
+
+        // A tick lasts 2.5 / tempo seconds.
+        let total = DSP_RATE * 5 / 2 + self.tick_error;
+        let tempo = self.tempo.max(1);
+        self.frames_left = (total / tempo) as usize;
+        self.tick_error = total % tempo;
+
+        self.tick += 1;
+        if self.tick >= self.speed.max(1) {
+            self.tick = 0;
+            if self.row_delay > 0 {
+                self.row_delay -= 1;
+                self.repeating = true;
+            } else {
+                self.repeating = false;
+                self.next_row();
+            }
+        }
+    }
+
+    /// Mixes `frames` frames of every channel into `mix`, or only advances the channels
+    /// while seeking.
+    fn render(&mut self, frames: usize) {
+        let module = &self.module;
+        self.mix.clear();
+        self.mix.resize(frames * 2, 0);
+
+        for channel in self.channels.iter_mut() {
+            let sample = match (channel.active, channel.sample) {
+                (true, Some(sample)) => &module.samples[sample],
+                _ => continue,
+            };
+            let end = sample.repeat.map_or(sample.data.len(), |(_, end)| end) as f64;
+
+            if self.silent {
+                channel.position += channel.step * frames as f64;
+                wrap(channel, sample, end);
+                continue;
+            }
+
+            let volume = (channel.volume + channel.volume_offset).clamp(0, 64);
+            let left = ((1.0 - channel.pan) * volume as f32 * 4.0) as i32;
+            let right = (channel.pan * volume as f32 * 4.0) as i32;
+
+            for frame in self.mix.chunks_exact_mut(2) {
+                if !wrap(channel, sample, end) {
+                    break;
+                }
+                let index = channel.position as usize;
+                let fraction = (channel.position - index as f64) as f32;
+                let a = sample.data[index] as f32;
+                let b = match sample.repeat {
+                    _ if ((index + 1) as f64) < end => sample.data[index + 1],
+                    Some((start, _)) => sample.data[start],
+                    None => 0,
+                } as f32;
+
+                let value = ((a + (b - a) * fraction) * 256.0) as i32;
+                frame[0] += (value * left) >> 8;
+                frame[1] += (value * right) >> 8;
+                channel.position += channel.step;
+            }
+        }
+    }
+}
+
+/// Plays a note on `channel`: picks its sample, and starts it unless the note slides
+/// towards it with tone portamento.
+fn trigger(module: &ModuleData, channel: &mut Channel, note: Note) {
+    let (x, y) = (note.param >> 4, note.param & 0x0F);
+    let index = note.sample as usize;
+    if index > 0 && index <= module.samples.len() {
+        let sample = &module.samples[index - 1];
+        channel.sample = Some(index - 1);
+        channel.volume = sample.volume as i32;
+        channel.finetune = sample.finetune;
+    }
+    if note.effect == 0xE && x == 0x5 {
+        channel.finetune = ((y << 4) as i8) >> 4;
+    }
+
+    if note.period > 0 {
+        let period = tune(note.period, channel.finetune);
+        if note.effect == 0x3 || note.effect == 0x5 {
+            channel.porta_target = period;
+        } else {
+            channel.period = period;
+            channel.porta_target = 0;
+            channel.active = channel.sample.is_some();
+            channel.vibrato_position = 0;
+            channel.tremolo_position = 0;
+            channel.position = 0.0;
+            if note.effect == 0x9 {
+                if note.param > 0 {
+                    channel.offset = note.param;
+                }
+                channel.position = channel.offset as f64 * 256.0;
+            }
+        }
+    }
+}
+
+/// Moves a channel that played past `end` back into the loop of its sample, or stops
+/// it. Returns whether it still plays.
+fn wrap(channel: &mut Channel, sample: &Sample, end: f64) -> bool {
+    if channel.position >= end {
+        match sample.repeat {
+            Some((start, end)) => {
+                let start = start as f64;
+                let length = end as f64 - start;
+                channel.position = start + (channel.position - start) % length;
+            }
+            None => channel.active = false,
+        }
+    }
+    channel.active
+}
+
+impl Decoder for ModPlayer {
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn rate(&self) -> u32 {
+        DSP_RATE
+    }
+
+    fn decode(&mut self, samples: &mut [i16]) -> Result<usize> {
+        // Channels are panned to add up to at most full scale on each side.
+        let gain = 2.0 / self.module.channels.max(2) as f32;
+        let mut written = 0;
+
+        while written + 2 <= samples.len() {
+            if self.frames_left == 0 {
+                if self.ended {
+                    break;
+                }
+                self.process_tick();
+            }
+
+            let frames = self.frames_left.min((samples.len() - written) / 2);
+            self.render(frames);
+            for (output, &value) in samples[written..written + frames * 2]
+                .iter_mut()
+                .zip(&self.mix)
+            {
+                *output = (value as f32 * gain).clamp(-32768.0, 32767.0) as i16;
+            }
+
+            written += frames * 2;
+            self.frames_left -= frames;
+            self.rendered += frames as u64;
+        }
+        Ok(written)
+    }
+
+    /// Replays the module from the start without mixing up to `frame`, so effects
+    /// such as tempo changes and jumps are followed.
+    fn seek(&mut self, frame: u64) -> Result<()> {
+        self.reset();
+        self.silent = true;
+        while self.rendered < frame && !(self.ended && self.frames_left == 0) {
+            if self.frames_left == 0 {
+                self.process_tick();
+            }
+            let frames = (self.frames_left as u64).min(frame - self.rendered) as usize;
+            self.render(frames);
+            self.frames_left -= frames;
+            self.rendered += frames as u64;
+        }
+        self.silent = false;
+
+        self.events.borrow_mut().clear();
+        self.push(TrackerEvent::Row(self.position()));
+        Ok(())
+    }
+}
+
+/// The module `Tracker` plays: the id of its music stream and its events.
+#[cfg(target_os = "rvl-ios")]
+struct Playing {
+    id: u32,
+    events: Rc<RefCell<VecDeque<(u64, TrackerEvent)>>>,
+}
+
+#[cfg(target_os = "rvl-ios")]
+static PLAYING: Global<Option<Playing>> = Global::new(None);
+#[cfg(target_os = "rvl-ios")]
+static POSITION: Global<Option<TrackerPosition>> = Global::new(None);
+
+/// Tracker music: MOD files replayed as music, with events in time with what is heard.
+///
+/// Only ProTracker MODs with 31 samples are supported, see `Module::parse`.
+///
+/// Modules play through `Music`, so they pause, fade and crossfade with it.
+/// Every row, `E8x` sync effect and loop of the module calls `State::tracker` as it
+/// is heard, for rhythm-based gameplay.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// const SONG: &[u8] = include_bytes!("song.mod");
+///
+/// struct Game {
+///     beat: bool,
+/// }
+///
+/// impl State for Game {
+///     fn init() {
+///         let module = Module::parse(SONG).unwrap();
+///         Tracker::play(&module, true).unwrap();
+///     }
+///
+///     fn tracker(&mut self, event: TrackerEvent) {
+///         if let TrackerEvent::Row(position) = event {
+///             self.beat = position.row % 4 == 0;
+///         }
+///     }
+/// }
+/// ```
+#[cfg(target_os = "rvl-ios")]
+pub struct Tracker;
+
+#[cfg(target_os = "rvl-ios")]
+impl Tracker {
+    /// Plays `module` from the start, stopping the music that was playing. With
+    /// `looping`, it starts over at its restart position once it ends.
+    pub fn play(module: &Module, looping: bool) -> Result<()> {
+        let player = ModPlayer::new(module).looping(looping);
+        let events = player.events.clone();
+        Music::play(Track::new(player))?;
+        Self::track(events);
+        Ok(())
+    }
+
+    /// Fades the current music out while `module` fades in, over `duration`.
+    pub fn crossfade(module: &Module, looping: bool, duration: Duration) -> Result<()> {
+        let player = ModPlayer::new(module).looping(looping);
+        let events = player.events.clone();
+        Music::crossfade(Track::new(player), duration)?;
+        Self::track(events);
+        Ok(())
+    }
+
+    fn track(events: Rc<RefCell<VecDeque<(u64, TrackerEvent)>>>) {
+        PLAYING.replace(Music::current().map(|(id, _)| Playing { id, events }));
+        POSITION.set(None);
+    }
+
+    /// Stops the module, if it is the music playing.
+    pub fn stop() {
+        if Self::is_playing() {
+            Music::stop();
+        }
+        PLAYING.replace(None);
+        POSITION.set(None);
+    }
+
+    pub fn pause(paused: bool) {
+        if Self::is_playing() {
+            Music::pause(paused);
+        }
+    }
+
+    pub fn is_paused() -> bool {
+        Self::is_playing() && Music::is_paused()
+    }
+
+    /// Returns true while a module played by `Tracker` is the current music.
+    pub fn is_playing() -> bool {
+        let playing = PLAYING.with(|playing| playing.as_ref().map(|playing| playing.id));
+        match (playing, Music::current()) {
+            (Some(playing), Some((id, _))) => playing == id,
+            _ => false,
+        }
+    }
+
+    /// Volume of the music, in `0.0..=1.0`, see `Music::set_volume`.
+    pub fn set_volume(volume: f32) {
+        Music::set_volume(volume);
+    }
+
+    /// Row being heard, once the first one played.
+    pub fn position() -> Option<TrackerPosition> {
+        POSITION.get()
+    }
+
+    /// Sends the events of the module that are now heard to the state.
+    pub(crate) fn update<T: State>(state: &mut T) {
+        // Cloned, since the state may play another module from its callback.
+        let playing = PLAYING.with(|playing| {
+            playing
+                .as_ref()
+                .map(|playing| (playing.id, playing.events.clone()))
+        });
+        let (id, events) = match playing {
+            Some(playing) => playing,
+            None => return,
+        };
+        let frame = match Music::current() {
+            Some((current, frame)) if current == id => frame,
+            _ => {
+                PLAYING.replace(None);
+                POSITION.set(None);
+                return;
+            }
+        };
+
+        loop {
+            let event = {
+                let mut events = events.borrow_mut();
+                match events.front() {
+                    Some(&(at, _)) if at <= frame => events.pop_front().map(|(_, event)| event),
+                    _ => None,
+                }
+            };
+            let event = match event {
+                Some(event) => event,
+                None => break,
+            };
+
+            if let TrackerEvent::Row(position) = event {
+                POSITION.set(Some(position));
+            }
+            state.tracker(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module playing empty patterns in `orders`, with `effects` placed as
+    /// `(pattern, row, channel, effect, param)`.
+    fn build(
+        tag: &[u8; 4],
+        channels: usize,
+        orders: &[u8],
+        effects: &[(usize, usize, usize, u8, u8)],
+    ) -> Vec<u8> {
+        let patterns = orders.iter().max().map_or(1, |&max| max as usize + 1);
+        let mut bytes = vec![0; 1084 + patterns * ROWS * channels * 4];
+        bytes[..4].copy_from_slice(b"Song");
+        bytes[950] = orders.len() as u8;
+        bytes[951] = 127;
+        bytes[952..952 + orders.len()].copy_from_slice(orders);
+        bytes[1080..1084].copy_from_slice(tag);
+
+        for &(pattern, row, channel, effect, param) in effects {
+            let at = 1084 + ((pattern * ROWS + row) * channels + channel) * 4;
+            bytes[at + 2] = effect;
+            bytes[at + 3] = param;
+        }
+        bytes
+    }
+
+    /// Rows the player starts, as `(order, row)`, until `count` of them or the end.
+    fn rows(player: &mut ModPlayer, count: usize) -> Vec<(usize, usize)> {
+        let events = player.events.clone();
+        let mut rows = Vec::new();
+        while rows.len() < count && !player.ended {
+            player.process_tick();
+            rows.extend(
+                events
+                    .borrow_mut()
+                    .drain(..)
+                    .filter_map(|(_, event)| match event {
+                        TrackerEvent::Row(position) => Some((position.order, position.row)),
+                        _ => None,
+                    }),
+            );
+        }
+        rows
+    }
+
+    fn play(
+        orders: &[u8],
+        effects: &[(usize, usize, usize, u8, u8)],
+        count: usize,
+    ) -> Vec<(usize, usize)> {
+        let module = Module::parse(&build(b"M.K.", 4, orders, effects)).unwrap();
+        rows(&mut ModPlayer::new(&module), count)
+    }
+
+    #[test]
+    fn parse_header() {
+        let module = Module::parse(&build(b"M.K.", 4, &[0, 1, 0], &[])).unwrap();
+        assert_eq!("Song", module.title());
+        assert_eq!(4, module.channels());
+        assert_eq!(3, module.song_length());
+        assert_eq!(0, module.data.restart);
+        assert_eq!(SAMPLES, module.data.samples.len());
+
+        let module = Module::parse(&build(b"6CHN", 6, &[0], &[])).unwrap();
+        assert_eq!(6, module.channels());
+        let module = Module::parse(&build(b"12CH", 12, &[0], &[])).unwrap();
+        assert_eq!(12, module.channels());
+    }
+
+    #[test]
+    fn parse_rejects_unsupported_and_truncated_files() {
+        assert!(Module::parse(&[0; 1083]).is_err());
+        // XM, S3M and IT modules have no MOD tag
+        assert!(Module::parse(&build(b"SCRM", 4, &[0], &[])).is_err());
+        assert!(Module::parse(&build(b"40CH", 40, &[0], &[])).is_err());
+
+        let bytes = build(b"M.K.", 4, &[0, 1], &[]);
+        assert!(Module::parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn parse_keeps_samples_cut_off_by_the_end_of_the_file() {
+        let mut bytes = build(b"M.K.", 4, &[0], &[]);
+        // 100 words long, with a loop past what is there
+        bytes[20 + 22..20 + 24].copy_from_slice(&100u16.to_be_bytes());
+        bytes[20 + 25] = 80;
+        bytes[20 + 26..20 + 28].copy_from_slice(&2u16.to_be_bytes());
+        bytes[20 + 28..20 + 30].copy_from_slice(&50u16.to_be_bytes());
+        bytes.extend_from_slice(&[1; 10]);
+
+        let module = Module::parse(&bytes).unwrap();
+        let sample = &module.data.samples[0];
+        assert_eq!(10, sample.data.len());
+        assert_eq!(64, sample.volume);
+        assert_eq!(Some((4, 10)), sample.repeat);
+    }
+
+    #[test]
+    fn rows_play_in_order_until_the_end() {
+        let rows = play(&[1, 0], &[], usize::MAX);
+        assert_eq!(2 * ROWS, rows.len());
+        assert_eq!((0, 0), rows[0]);
+        assert_eq!((0, ROWS - 1), rows[ROWS - 1]);
+        assert_eq!((1, 0), rows[ROWS]);
+    }
+
+    #[test]
+    fn position_jump_moves_to_the_order() {
+        let rows = play(&[0, 1, 2], &[(0, 2, 0, 0xB, 2)], 4);
+        assert_eq!(vec![(0, 0), (0, 1), (0, 2), (2, 0)], rows);
+    }
+
+    #[test]
+    fn pattern_break_moves_to_the_row_of_the_next_order() {
+        // The row is given in decimal digits
+        let rows = play(&[0, 1], &[(0, 1, 0, 0xD, 0x12)], 3);
+        assert_eq!(vec![(0, 0), (0, 1), (1, 12)], rows);
+
+        // Past the last row starts the next order over
+        let rows = play(&[0, 1], &[(0, 0, 0, 0xD, 0x64)], 2);
+        assert_eq!(vec![(0, 0), (1, 0)], rows);
+    }
+
+    #[test]
+    fn jump_and_break_on_the_same_row() {
+        let rows = play(&[0, 1, 2], &[(0, 0, 0, 0xB, 2), (0, 0, 1, 0xD, 0x05)], 2);
+        assert_eq!(vec![(0, 0), (2, 5)], rows);
+    }
+
+    #[test]
+    fn pattern_loop_repeats_rows() {
+        let rows = play(&[0], &[(0, 1, 0, 0xE, 0x60), (0, 2, 0, 0xE, 0x62)], 8);
+        assert_eq!(
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (0, 1),
+                (0, 2),
+                (0, 1),
+                (0, 2),
+                (0, 3)
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn looping_restarts_with_an_event() {
+        let module = Module::parse(&build(b"M.K.", 4, &[0], &[(0, 0, 0, 0xD, 0)])).unwrap();
+        let mut player = ModPlayer::new(&module).looping(true);
+        let events = player.events.clone();
+        for _ in 0..12 {
+            player.process_tick();
+        }
+
+        assert!(!player.ended);
+        let events: Vec<_> = events.borrow().iter().map(|&(_, event)| event).collect();
+        let row = TrackerEvent::Row(TrackerPosition::default());
+        assert_eq!(
+            vec![row, TrackerEvent::Looped, row, TrackerEvent::Looped],
+            events
+        );
+    }
+}
@@ -0,0 +1,239 @@
+extern crate alloc;
+
+use crate::{engine::State, global::Global};
+use alloc::string::String;
+use ogc::{prelude::*, Result};
+
+struct Devices {
+    enabled: bool,
+    keyboard: bool,
+    mouse: bool,
+    held: [u32; 8],
+    previous: [u32; 8],
+    modifiers: Modifiers,
+    text: String,
+    buttons: MouseButtons,
+    previous_buttons: MouseButtons,
+    motion: (i32, i32),
+    wheel: i32,
+}
+
+static DEVICES: Global<Devices> = Global::new(Devices {
+    enabled: false,
+    keyboard: false,
+    mouse: false,
+    held: [0; 8],
+    previous: [0; 8],
+    modifiers: Modifiers::empty(),
+    text: String::new(),
+    buttons: MouseButtons::empty(),
+    previous_buttons: MouseButtons::empty(),
+    motion: (0, 0),
+    wheel: 0,
+});
+
+/// USB keyboards and mice, polled by `Engine::run` once per frame after being enabled.
+///
+/// Every event is passed to `State::key` and `State::mouse` before `update`, and typed
+/// characters to `State::text`. The state of the keys and buttons can also be queried
+/// from `update`, or bound to actions with `ButtonBinding::Key` and `ButtonBinding::Mouse`.
+///
+/// Keyboard and mouse input is not part of `InputFrame`, so it is not recorded by `Replay`.
+/// To keep replays deterministic, `ButtonBinding::Key` and `ButtonBinding::Mouse` stay
+/// inactive while a replay is recorded or played.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// struct Console {
+///     line: String,
+/// }
+///
+/// impl State for Console {
+///     fn init() {
+///         Usb::enable().unwrap();
+///     }
+///
+///     fn text(&mut self, c: char) {
+///         self.line.push(c);
+///     }
+///
+///     fn update(&mut self) {
+///         if Usb::key_down(Key::BACKSPACE) {
+///             self.line.pop();
+///         }
+///     }
+/// }
+/// ```
+pub struct Usb;
+
+impl Usb {
+    /// Starts the keyboard and mouse services. Keyboards and mice can be plugged in at any
+    /// time afterwards.
+    pub fn enable() -> Result<()> {
+        if !Self::is_enabled() {
+            Keyboard::init()?;
+            if let Err(error) = Mouse::init() {
+                Keyboard::deinit();
+                return Err(error);
+            }
+            DEVICES.with_mut(|devices| devices.enabled = true);
+        }
+        Ok(())
+    }
+
+    /// Stops the keyboard and mouse services and releases every key and button.
+    pub fn disable() {
+        if Self::is_enabled() {
+            Keyboard::deinit();
+            Mouse::deinit();
+            DEVICES.with_mut(|devices| {
+                devices.enabled = false;
+                devices.keyboard = false;
+                devices.mouse = false;
+                devices.held = [0; 8];
+                devices.modifiers = Modifiers::empty();
+                devices.buttons = MouseButtons::empty();
+            });
+        }
+    }
+
+    pub fn is_enabled() -> bool {
+        DEVICES.with(|devices| devices.enabled)
+    }
+
+    pub fn is_keyboard_connected() -> bool {
+        DEVICES.with(|devices| devices.keyboard)
+    }
+
+    pub fn is_mouse_connected() -> bool {
+        DEVICES.with(|devices| devices.mouse)
+    }
+
+    /// Returns true if `key` was pressed this frame.
+    pub fn key_down(key: Key) -> bool {
+        let (held, previous) = Self::key_pair(key);
+        held && !previous
+    }
+
+    /// Returns true if `key` is held down.
+    pub fn key_held(key: Key) -> bool {
+        Self::key_pair(key).0
+    }
+
+    /// Returns true if `key` was released this frame.
+    pub fn key_up(key: Key) -> bool {
+        let (held, previous) = Self::key_pair(key);
+        !held && previous
+    }
+
+    /// Modifiers of the latest key event.
+    pub fn modifiers() -> Modifiers {
+        DEVICES.with(|devices| devices.modifiers)
+    }
+
+    /// Characters typed this frame, in the keyboard layout set in the console settings.
+    pub fn text() -> String {
+        DEVICES.with(|devices| devices.text.clone())
+    }
+
+    /// Mouse buttons pressed this frame.
+    pub fn mouse_down() -> MouseButtons {
+        DEVICES.with(|devices| devices.buttons & !devices.previous_buttons)
+    }
+
+    /// Mouse buttons held down.
+    pub fn mouse_held() -> MouseButtons {
+        DEVICES.with(|devices| devices.buttons)
+    }
+
+    /// Mouse buttons released this frame.
+    pub fn mouse_up() -> MouseButtons {
+        DEVICES.with(|devices| !devices.buttons & devices.previous_buttons)
+    }
+
+    /// Relative motion of the mouse this frame, in mouse counts. Positive is right and down.
+    pub fn mouse_motion() -> (i32, i32) {
+        DEVICES.with(|devices| devices.motion)
+    }
+
+    /// Wheel motion this frame. Positive is away from the user.
+    pub fn mouse_wheel() -> i32 {
+        DEVICES.with(|devices| devices.wheel)
+    }
+
+    fn key_pair(key: Key) -> (bool, bool) {
+        let (word, bit) = (key.0 as usize / 32, 1 << (key.0 % 32));
+        DEVICES.with(|devices| {
+            (
+                devices.held[word] & bit != 0,
+                devices.previous[word] & bit != 0,
+            )
+        })
+    }
+
+    /// Drains the pending events, passing each to `state`. The devices are updated before
+    /// each call, so `state` sees the event applied when it queries `Usb`.
+    pub(crate) fn update<T: State>(state: &mut T) {
+        let enabled = DEVICES.with_mut(|devices| {
+            devices.previous = devices.held;
+            devices.previous_buttons = devices.buttons;
+            devices.text.clear();
+            devices.motion = (0, 0);
+            devices.wheel = 0;
+            devices.enabled
+        });
+
+        if !enabled {
+            return;
+        }
+
+        while let Some(event) = Keyboard::event() {
+            let (word, bit) = (event.key.0 as usize / 32, 1 << (event.key.0 % 32));
+            let is_key = DEVICES.with_mut(|devices| {
+                match event.kind {
+                    KeyEventKind::Connected => devices.keyboard = true,
+                    KeyEventKind::Disconnected => {
+                        devices.keyboard = false;
+                        devices.held = [0; 8];
+                        devices.modifiers = Modifiers::empty();
+                    }
+                    KeyEventKind::Pressed => devices.held[word] |= bit,
+                    KeyEventKind::Released => devices.held[word] &= !bit,
+                }
+
+                if let KeyEventKind::Pressed | KeyEventKind::Released = event.kind {
+                    devices.modifiers = event.modifiers;
+                    devices.text.extend(event.char());
+                    true
+                } else {
+                    false
+                }
+            });
+
+            if is_key {
+                state.key(event);
+                if let Some(c) = event.char() {
+                    state.text(c);
+                }
+            }
+        }
+
+        let mouse = Mouse::is_connected();
+        DEVICES.with_mut(|devices| devices.mouse = mouse);
+        while let Some(event) = Mouse::event() {
+            DEVICES.with_mut(|devices| {
+                devices.buttons = event.buttons;
+                devices.motion.0 += event.dx;
+                devices.motion.1 += event.dy;
+                devices.wheel += event.wheel;
+            });
+            state.mouse(event);
+        }
+        if !mouse {
+            DEVICES.with_mut(|devices| devices.buttons = MouseButtons::empty());
+        }
+    }
+}
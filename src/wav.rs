@@ -0,0 +1,367 @@
+extern crate alloc;
+
+#[cfg(target_os = "rvl-ios")]
+use crate::audio::Sound;
+use alloc::{format, vec::Vec};
+use core::time::Duration;
+use ogc::{prelude::*, OgcError, Result};
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Sample rate of the DSP, in Hz.
+pub const DSP_RATE: u32 = 48_000;
+
+/// PCM samples of a WAV file, borrowed from its bytes.
+///
+/// Supports 8-bit and 16-bit integer PCM, mono or stereo, at any sample rate.
+///
+/// # Example
+///
+/// ```rust
+/// use ogc_engine::prelude::*;
+///
+/// const JUMP: &[u8] = include_bytes!("jump.wav");
+///
+/// let wav = Wav::parse(JUMP).unwrap();
+/// let jump = wav.resample(DSP_RATE);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Wav<'a> {
+    channels: u16,
+    rate: u32,
+    bits: u16,
+    data: &'a [u8],
+}
+
+fn u16_le(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_le(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+impl<'a> Wav<'a> {
+    /// Reads the `fmt ` and `data` chunks of a RIFF WAVE file, skipping any other chunk.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(OgcError::Audio(
+                "Wav::parse() found no RIFF WAVE header".into(),
+            ));
+        }
+
+        let mut format = None;
+        let mut data = None;
+        let mut at = 12;
+        while at + 8 <= bytes.len() {
+            let id = &bytes[at..at + 4];
+            let size = u32_le(bytes, at + 4) as usize;
+            let start = at + 8;
+            // Writers that stream the file leave the size of the last chunk at its maximum.
+            let end = start.saturating_add(size).min(bytes.len());
+            let chunk = &bytes[start..end];
+
+            match id {
+                b"fmt " => {
+                    if chunk.len() < 16 {
+                        return Err(OgcError::Audio(
+                            "Wav::parse() found a truncated fmt chunk".into(),
+                        ));
+                    }
+                    format = Some(chunk);
+                }
+                b"data" => data = Some(chunk),
+                _ => {}
+            }
+
+            // Chunks are padded to an even size.
+            at = end + (size & 1);
+        }
+
+        let format =
+            format.ok_or_else(|| OgcError::Audio("Wav::parse() found no fmt chunk".into()))?;
+        let data =
+            data.ok_or_else(|| OgcError::Audio("Wav::parse() found no data chunk".into()))?;
+
+        let mut encoding = u16_le(format, 0);
+        if encoding == WAVE_FORMAT_EXTENSIBLE && format.len() >= 26 {
+            // The first two bytes of the sub-format GUID hold the actual encoding.
+            encoding = u16_le(format, 24);
+        }
+        let channels = u16_le(format, 2);
+        let rate = u32_le(format, 4);
+        let bits = u16_le(format, 14);
+
+        if encoding != WAVE_FORMAT_PCM {
+            return Err(OgcError::Audio(format!(
+                "Wav::parse() does not support encoding {:#06x}, only integer PCM",
+                encoding
+            )));
+        }
+        if channels != 1 && channels != 2 {
+            return Err(OgcError::Audio(format!(
+                "Wav::parse() does not support {} channels",
+                channels
+            )));
+        }
+        if bits != 8 && bits != 16 {
+            return Err(OgcError::Audio(format!(
+                "Wav::parse() does not support {}-bit samples",
+                bits
+            )));
+        }
+        if rate == 0 {
+            return Err(OgcError::Audio(
+                "Wav::parse() found a sample rate of 0".into(),
+            ));
+        }
+
+        let frame = (channels * bits / 8) as usize;
+        Ok(Self {
+            channels,
+            rate,
+            bits,
+            data: &data[..data.len() / frame * frame],
+        })
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Sample rate, in Hz.
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    pub fn bits_per_sample(&self) -> u16 {
+        self.bits
+    }
+
+    /// Number of samples per channel.
+    pub fn frames(&self) -> usize {
+        self.data.len() / (self.channels * self.bits / 8) as usize
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.frames() as f32 / self.rate as f32)
+    }
+
+    /// Format the samples have once converted by `Wav::to_sound`.
+    pub fn voice_format(&self) -> VoiceFormat {
+        match (self.channels, self.bits) {
+            (1, 8) => VoiceFormat::Mono8Bit,
+            (1, _) => VoiceFormat::Mono16Bit,
+            (_, 8) => VoiceFormat::Stereo8Bit,
+            (_, _) => VoiceFormat::Stereo16Bit,
+        }
+    }
+
+    /// Converts the samples to the signed, big-endian layout the DSP reads, keeping
+    /// the sample rate. `Asnd` resamples on the fly while playing.
+    pub fn to_pcm(&self) -> Vec<u8> {
+        if self.bits == 8 {
+            // WAV stores 8-bit samples unsigned.
+            self.data.iter().map(|&sample| sample ^ 0x80).collect()
+        } else {
+            self.data
+                .chunks_exact(2)
+                .flat_map(|sample| [sample[1], sample[0]])
+                .collect()
+        }
+    }
+
+    /// Returns the sample at `frame` of `channel`, as 16-bit.
+    fn sample(&self, frame: usize, channel: usize) -> i16 {
+        let index = frame * self.channels as usize + channel;
+        if self.bits == 8 {
+            ((self.data[index] ^ 0x80) as i8 as i16) << 8
+        } else {
+            i16::from_le_bytes([self.data[index * 2], self.data[index * 2 + 1]])
+        }
+    }
+
+    /// Resamples to `rate` Hz with linear interpolation, as big-endian 16-bit samples.
+    pub fn resample_pcm(&self, rate: u32) -> Vec<u8> {
+        let frames = self.frames();
+        if frames == 0 || rate == 0 {
+            return Vec::new();
+        }
+
+        let channels = self.channels as usize;
+        let length = (frames as u64 * rate as u64 / self.rate as u64) as usize;
+        let step = self.rate as f32 / rate as f32;
+        let mut pcm = Vec::with_capacity(length * channels * 2);
+
+        for i in 0..length {
+            let position = i as f32 * step;
+            let frame = (position as usize).min(frames - 1);
+            let next = (frame + 1).min(frames - 1);
+            let t = position - frame as f32;
+
+            for channel in 0..channels {
+                let a = self.sample(frame, channel) as f32;
+                let b = self.sample(next, channel) as f32;
+                let sample = (a + (b - a) * t) as i16;
+                pcm.extend_from_slice(&sample.to_be_bytes());
+            }
+        }
+
+        pcm
+    }
+
+    /// A `Sound` of the samples at their own rate.
+    #[cfg(target_os = "rvl-ios")]
+    pub fn to_sound(&self) -> Sound {
+        Sound::new(&self.to_pcm(), self.voice_format(), self.rate)
+    }
+
+    /// A 16-bit `Sound` resampled to `rate` Hz, usually `DSP_RATE` to spare the DSP
+    /// from resampling while playing.
+    #[cfg(target_os = "rvl-ios")]
+    pub fn resample(&self, rate: u32) -> Sound {
+        let format = if self.channels == 1 {
+            VoiceFormat::Mono16Bit
+        } else {
+            VoiceFormat::Stereo16Bit
+        };
+        Sound::new(&self.resample_pcm(rate), format, rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn riff(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        for (id, chunk) in chunks {
+            bytes.extend_from_slice(*id);
+            bytes.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(chunk);
+            if chunk.len() % 2 == 1 {
+                bytes.push(0);
+            }
+        }
+        let size = (bytes.len() - 8) as u32;
+        bytes[4..8].copy_from_slice(&size.to_le_bytes());
+        bytes
+    }
+
+    fn fmt(encoding: u16, channels: u16, rate: u32, bits: u16) -> Vec<u8> {
+        let align = channels * bits / 8;
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(&encoding.to_le_bytes());
+        chunk.extend_from_slice(&channels.to_le_bytes());
+        chunk.extend_from_slice(&rate.to_le_bytes());
+        chunk.extend_from_slice(&(rate * align as u32).to_le_bytes());
+        chunk.extend_from_slice(&align.to_le_bytes());
+        chunk.extend_from_slice(&bits.to_le_bytes());
+        chunk
+    }
+
+    fn extensible(channels: u16, rate: u32, bits: u16) -> Vec<u8> {
+        let mut chunk = fmt(WAVE_FORMAT_EXTENSIBLE, channels, rate, bits);
+        chunk.extend_from_slice(&22u16.to_le_bytes());
+        chunk.extend_from_slice(&bits.to_le_bytes());
+        chunk.extend_from_slice(&3u32.to_le_bytes());
+        // KSDATAFORMAT_SUBTYPE_PCM
+        chunk.extend_from_slice(&[
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38,
+            0x9B, 0x71,
+        ]);
+        chunk
+    }
+
+    #[test]
+    fn parse_pcm() {
+        let bytes = riff(&[
+            (b"fmt ", &fmt(WAVE_FORMAT_PCM, 1, 22050, 16)),
+            (b"data", &[1, 0, 2, 0]),
+        ]);
+        let wav = Wav::parse(&bytes).unwrap();
+        assert_eq!(wav.channels(), 1);
+        assert_eq!(wav.rate(), 22050);
+        assert_eq!(wav.bits_per_sample(), 16);
+        assert_eq!(wav.frames(), 2);
+        assert_eq!(wav.voice_format(), VoiceFormat::Mono16Bit);
+    }
+
+    #[test]
+    fn parse_extensible() {
+        let bytes = riff(&[(b"fmt ", &extensible(2, 44100, 16)), (b"data", &[0; 8])]);
+        let wav = Wav::parse(&bytes).unwrap();
+        assert_eq!(wav.channels(), 2);
+        assert_eq!(wav.rate(), 44100);
+        assert_eq!(wav.frames(), 2);
+        assert_eq!(wav.voice_format(), VoiceFormat::Stereo16Bit);
+    }
+
+    #[test]
+    fn parse_skips_odd_sized_chunks() {
+        let bytes = riff(&[
+            (b"LIST", &[1, 2, 3]),
+            (b"fmt ", &fmt(WAVE_FORMAT_PCM, 1, 8000, 16)),
+            (b"junk", &[0; 5]),
+            (b"data", &[1, 0, 2, 0, 3]),
+        ]);
+        let wav = Wav::parse(&bytes).unwrap();
+        assert_eq!(wav.rate(), 8000);
+        // The odd byte left of the data is not a whole frame.
+        assert_eq!(wav.frames(), 2);
+    }
+
+    #[test]
+    fn parse_rejects_broken_files() {
+        let missing_data = riff(&[(b"fmt ", &fmt(WAVE_FORMAT_PCM, 1, 8000, 8))]);
+        assert!(matches!(Wav::parse(&missing_data), Err(OgcError::Audio(_))));
+
+        let truncated_fmt = riff(&[
+            (b"fmt ", &fmt(WAVE_FORMAT_PCM, 1, 8000, 8)[..10]),
+            (b"data", &[0; 4]),
+        ]);
+        assert!(matches!(
+            Wav::parse(&truncated_fmt),
+            Err(OgcError::Audio(_))
+        ));
+    }
+
+    #[test]
+    fn to_pcm_signs_8_bit_samples() {
+        let bytes = riff(&[
+            (b"fmt ", &fmt(WAVE_FORMAT_PCM, 1, 8000, 8)),
+            (b"data", &[0x00, 0x80, 0xFF, 0x7F]),
+        ]);
+        let wav = Wav::parse(&bytes).unwrap();
+        assert_eq!(wav.to_pcm(), [0x80, 0x00, 0x7F, 0xFF]);
+    }
+
+    #[test]
+    fn to_pcm_swaps_16_bit_samples() {
+        let bytes = riff(&[
+            (b"fmt ", &fmt(WAVE_FORMAT_PCM, 2, 8000, 16)),
+            (b"data", &[0x34, 0x12, 0xCD, 0xAB]),
+        ]);
+        let wav = Wav::parse(&bytes).unwrap();
+        assert_eq!(wav.to_pcm(), [0x12, 0x34, 0xAB, 0xCD]);
+    }
+
+    #[test]
+    fn resample_pcm_to_dsp_rate() {
+        // A ramp of 441 frames, 20 ms at 22050 Hz.
+        let data: Vec<u8> = (0..441i16).flat_map(|i| (i * 10).to_le_bytes()).collect();
+        let bytes = riff(&[
+            (b"fmt ", &fmt(WAVE_FORMAT_PCM, 1, 22050, 16)),
+            (b"data", &data),
+        ]);
+        let pcm = Wav::parse(&bytes).unwrap().resample_pcm(DSP_RATE);
+
+        assert_eq!(pcm.len(), 960 * 2);
+        let sample = |i: usize| i16::from_be_bytes([pcm[i * 2], pcm[i * 2 + 1]]);
+        assert_eq!(sample(0), 0);
+        // Halfway between the first two frames.
+        assert_eq!(sample(1), 4);
+        assert_eq!(sample(959), 4400);
+    }
+}